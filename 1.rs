@@ -54,6 +54,10 @@ Each variant can be a unit, tuple, or struct-like.
 
 Handled using match.
 
+The full model is web_event.rs, shared by event_replay.rs, input_decoder.rs and widget_tree.rs: Key(KeyEvent) with modifiers, non-character keys and releases, Mouse(MouseEvent) with the button and drag, Scroll, Resize, Paste(String), FocusIn and FocusOut. WebEvent::from(KeyEvent) and WebEvent::from(MouseEvent) still give KeyPress(c) and Click for the plain cases, and the three variants above convert with into(). The model is #[non_exhaustive], so a match in another crate needs a `_` arm and new kinds of input can be added later.
+
+Its Dispatcher routes events to handlers instead of one central match (events_example below feeds it the three variants): on(EventKind::Click, f) or on_any(f) returns a HandlerId for off(id); handlers run in registration order and can return Propagation::Stop to hide the event from the rest.
+
 ✅ Type Alias
 rust
 Copy
@@ -63,20 +67,32 @@ Used for shorter names in code.
 
 Often used in impl blocks via Self.
 
+In the code below Ops aliases Operation from operation.rs: 13 operations over every primitive number type, errors instead of panics, and Checked / Wrapping / Saturating modes. conformance.rs checks it against exact models.
+
 🔸 4. Enums as Linked Lists
 rust
 Copy
 Edit
-enum List {
-    Cons(u32, Box<List>),
+enum List<T> {
+    Cons(T, Box<List<T>>),
     Nil,
 }
 Recursive structures like linked lists.
 
 Box used for heap allocation.
 
+Generic over the element type T, so the same list holds numbers, strings or structs.
+
 Custom methods (new, prepend, len, stringify) added via impl.
 
+iter(), iter_mut(), IntoIterator, FromIterator and Extend plug it into the std iterator ecosystem (for loops, collect, map, ...).
+
+len, stringify and Drop walk the list with a loop instead of recursing, so lists with millions of nodes don't overflow the stack.
+
+reverse, append, split_at, nth, contains, map, filter, fold and a stable merge sort work on the cons cells directly (no Vec copy); each lists its complexity.
+
+Display prints the stringify format and honours flags per element ({:>4}, {:.2}); FromStr parses it back with byte-positioned errors; to_json / from_json round-trip through a JSON array. The readers are in list_format.rs, which arena_list.rs shares.
+
 🔸 5. use Declaration
 rust
 Copy
@@ -169,67 +185,487 @@ fn structs_example() {
 }
 
 // ---------- 3. ENUMS ----------
+// The three variants from the notes. The full event model (keys with modifiers,
+// mouse buttons, scrolling, a Dispatcher, ...) is web_event.rs; `into()` converts.
+mod web_event;
+
 enum WebEvent {
     PageLoad,
     KeyPress(char),
     Click { x: i64, y: i64 },
 }
 
-fn inspect(event: WebEvent) {
-    match event {
-        WebEvent::PageLoad => println!("Page loaded"),
-        WebEvent::KeyPress(c) => println!("Key pressed: {}", c),
-        WebEvent::Click { x, y } => println!("Clicked at: {}, {}", x, y),
+// Each variant has the same name and data in the full model.
+impl From<WebEvent> for web_event::WebEvent {
+    fn from(event: WebEvent) -> Self {
+        match event {
+            WebEvent::PageLoad => web_event::WebEvent::PageLoad,
+            WebEvent::KeyPress(c) => web_event::WebEvent::KeyPress(c),
+            WebEvent::Click { x, y } => web_event::WebEvent::Click { x, y },
+        }
     }
 }
 
-// ---------- 4. TYPE ALIAS ----------
-enum Operation {
-    Add,
-    Subtract,
+// No central match: handlers register for one kind of event or for all of them.
+fn events_example() {
+    use web_event::{Dispatcher, EventKind, Propagation};
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.on_any(web_event::inspect);
+    let clicks = dispatcher.on(EventKind::Click, |event| {
+        println!("  click handler saw {:?}", event);
+        Propagation::Continue
+    });
+    for event in [WebEvent::PageLoad, WebEvent::KeyPress('A'), WebEvent::Click { x: 10, y: 20 }] {
+        dispatcher.dispatch(&event.into());
+    }
+    dispatcher.off(clicks);
 }
 
-type Ops = Operation;
+// ---------- 4. TYPE ALIAS ----------
+// The alias names `Operation` from operation.rs, the one definition the calculator
+// programs share: 13 operations on every number type, and `run` returns a Result.
+mod operation;
 
-impl Operation {
-    fn run(&self, x: i32, y: i32) -> i32 {
-        match self {
-            Self::Add => x + y,
-            Self::Subtract => x - y,
-        }
-    }
+type Ops = operation::Operation;
+
+fn ops_example() {
+    let op = Ops::Add;
+    println!("Ops result: {:?}", op.run(3, 2));
+    // Overflow is an error in every build, instead of a panic in debug builds.
+    println!("Ops overflow: {:?}", op.run(i32::MAX, 1));
+    println!("Ops wrapping: {:?}", op.run_with(i32::MAX, 1, operation::Mode::Wrapping));
+    println!("Ops divide: {:?}", Ops::Divide.run(7, 0));
 }
 
 // ---------- 5. LINKED LIST WITH ENUM ----------
-enum List {
-    Cons(u32, Box<List>),
+enum List<T> {
+    Cons(T, Box<List<T>>),
     Nil,
 }
 
-impl List {
+impl<T> List<T> {
     fn new() -> Self {
         List::Nil
     }
 
-    fn prepend(self, val: u32) -> Self {
+    fn prepend(self, val: T) -> Self {
         List::Cons(val, Box::new(self))
     }
 
     fn len(&self) -> u32 {
+        self.iter().count() as u32
+    }
+
+    // Removes the head and makes its tail the new list.
+    // The unsafe block is only here because List implements Drop (the iterative
+    // drop below), and Rust won't let a pattern move fields out of a Drop type.
+    fn pop(&mut self) -> Option<T> {
+        let list = std::mem::ManuallyDrop::new(std::mem::replace(self, List::Nil));
+        match &*list {
+            List::Cons(head, tail) => {
+                // SAFETY: this is the move out of `List::Cons(head, tail)` that the
+                // Drop impl forbids. `list` is wrapped in ManuallyDrop, so its Drop never
+                // runs, and it is never used again: `head` and `tail` are each read
+                // exactly once and dropped only by their new owners.
+                let (head, tail) = unsafe { (std::ptr::read(head), std::ptr::read(tail)) };
+                *self = *tail;
+                Some(head)
+            }
+            List::Nil => None,
+        }
+    }
+
+    fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self }
+    }
+
+    fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut { next: Some(self) }
+    }
+
+    fn is_empty(&self) -> bool {
+        matches!(self, List::Nil)
+    }
+
+    fn head(&self) -> Option<&T> {
         match self {
-            List::Cons(_, tail) => 1 + tail.len(),
-            List::Nil => 0,
+            List::Cons(head, _) => Some(head),
+            List::Nil => None,
         }
     }
 
-    fn stringify(&self) -> String {
+    // Cuts the list after its first cell and returns the rest; `self` keeps only the head.
+    fn detach_tail(&mut self) -> List<T> {
         match self {
-            List::Cons(head, tail) => format!("{}, {}", head, tail.stringify()),
-            List::Nil => "Nil".to_string(),
+            List::Cons(_, tail) => std::mem::replace(&mut **tail, List::Nil),
+            List::Nil => List::Nil,
+        }
+    }
+
+    // Moves the first cell of `self` (box and all) onto the end of `slot`,
+    // and returns the slot's new tail.
+    fn move_head_to<'a>(&mut self, slot: &'a mut List<T>) -> &'a mut List<T> {
+        let rest = self.detach_tail();
+        *slot = std::mem::replace(self, rest);
+        match slot {
+            List::Cons(_, tail) => tail,
+            List::Nil => slot,
+        }
+    }
+}
+
+// ---------- 5b. LIST ALGORITHMS ----------
+// All of these relink the existing cons cells (or walk them) - nothing is copied into a Vec.
+// n = length of the list; "extra space" excludes the list itself.
+impl<T> List<T> {
+    // O(n) time, O(1) extra space: flips every link in place.
+    fn reverse(mut self) -> Self {
+        let mut reversed = List::Nil;
+        while !self.is_empty() {
+            let rest = self.detach_tail();
+            let mut cell = std::mem::replace(&mut self, rest);
+            if let List::Cons(_, tail) = &mut cell {
+                **tail = reversed;
+            }
+            reversed = cell;
+        }
+        reversed
+    }
+
+    // O(n) in the length of `self`, O(1) extra space: `other` is linked on as-is.
+    fn append(mut self, other: List<T>) -> Self {
+        let mut slot = &mut self;
+        while let List::Cons(_, tail) = slot {
+            slot = tail;
+        }
+        *slot = other;
+        self
+    }
+
+    // O(at) time, O(1) extra space: first `at` elements, then the rest.
+    // If `at` is past the end, the second list is empty.
+    fn split_at(mut self, at: usize) -> (Self, Self) {
+        let mut slot = &mut self;
+        for _ in 0..at {
+            match slot {
+                List::Cons(_, tail) => slot = tail,
+                List::Nil => break,
+            }
+        }
+        let back = std::mem::replace(slot, List::Nil);
+        (self, back)
+    }
+
+    // O(n) time, O(1) extra space.
+    fn nth(&self, n: usize) -> Option<&T> {
+        self.iter().nth(n)
+    }
+
+    // O(n) time, O(1) extra space.
+    fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+
+    // O(n) time, O(1) extra space besides the new cells; keeps the order.
+    fn map<U, F: FnMut(T) -> U>(self, f: F) -> List<U> {
+        self.into_iter().map(f).collect()
+    }
+
+    // O(n) time, O(1) extra space: kept cells are relinked, the others are freed.
+    fn filter<F: FnMut(&T) -> bool>(mut self, mut keep: F) -> Self {
+        let mut kept = List::Nil;
+        let mut slot = &mut kept;
+        while let List::Cons(head, _) = &self {
+            if keep(head) {
+                slot = self.move_head_to(slot);
+            } else {
+                let rest = self.detach_tail();
+                self = rest;
+            }
+        }
+        kept
+    }
+
+    // O(n) time, O(1) extra space: combines the elements from head to tail.
+    fn fold<B, F: FnMut(B, &T) -> B>(&self, init: B, f: F) -> B {
+        self.iter().fold(init, f)
+    }
+
+    // Stable merge sort on the cons cells themselves.
+    // O(n log n) comparisons, no allocation, O(log n) stack for the halving.
+    fn sort_by<F: FnMut(&T, &T) -> std::cmp::Ordering>(self, mut compare: F) -> Self {
+        let len = self.len() as usize;
+        Self::merge_sort(self, len, &mut compare)
+    }
+
+    fn sort(self) -> Self
+    where
+        T: Ord,
+    {
+        self.sort_by(|a, b| a.cmp(b))
+    }
+
+    fn merge_sort<F: FnMut(&T, &T) -> std::cmp::Ordering>(self, len: usize, compare: &mut F) -> Self {
+        if len < 2 {
+            return self;
+        }
+        let (front, back) = self.split_at(len / 2);
+        let front = Self::merge_sort(front, len / 2, compare);
+        let back = Self::merge_sort(back, len - len / 2, compare);
+        Self::merge(front, back, compare)
+    }
+
+    // Takes from `front` on ties, which is what keeps the sort stable.
+    fn merge<F: FnMut(&T, &T) -> std::cmp::Ordering>(mut front: Self, mut back: Self, compare: &mut F) -> Self {
+        let mut merged = List::Nil;
+        let mut slot = &mut merged;
+        while let (Some(a), Some(b)) = (front.head(), back.head()) {
+            let take_front = compare(b, a) != std::cmp::Ordering::Less;
+            slot = if take_front { front.move_head_to(slot) } else { back.move_head_to(slot) };
+        }
+        *slot = if front.is_empty() { back } else { front };
+        merged
+    }
+}
+
+impl<T: std::fmt::Display> List<T> {
+    fn stringify(&self) -> String {
+        self.to_string()
+    }
+}
+
+// The default drop glue would recurse once per `Box<List>`; unlink the cells one at a time instead.
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        if let List::Cons(_, tail) = self {
+            let mut cur = std::mem::replace(&mut **tail, List::Nil);
+            while let List::Cons(_, tail) = &mut cur {
+                cur = std::mem::replace(&mut **tail, List::Nil);
+            }
+        }
+    }
+}
+
+// Borrowing iterator: walks the cons cells and yields `&T`.
+struct Iter<'a, T> {
+    next: &'a List<T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next {
+            List::Cons(head, tail) => {
+                self.next = tail;
+                Some(head)
+            }
+            List::Nil => None,
+        }
+    }
+}
+
+// Mutable iterator: takes the remaining list out of `next` so each `&mut T` is handed out once.
+struct IterMut<'a, T> {
+    next: Option<&'a mut List<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next.take() {
+            Some(List::Cons(head, tail)) => {
+                self.next = Some(tail);
+                Some(head)
+            }
+            _ => None,
+        }
+    }
+}
+
+// Owning iterator: moves each element out of its cons cell.
+struct IntoIter<T>(List<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// Appends at the tail, so `extend` and `collect` keep the iterator's order.
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut tail = self;
+        while let List::Cons(_, next) = tail {
+            tail = next;
         }
+        for val in iter {
+            *tail = List::Cons(val, Box::new(List::Nil));
+            if let List::Cons(_, next) = tail {
+                tail = next;
+            }
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// ---------- 5c. TEXT AND JSON FORMATS ----------
+// Display writes the `stringify` format ("3, 2, 1, Nil") and FromStr reads it back.
+// Formatter flags apply to every element, the way `{:?}` treats a Vec:
+// format!("{:>3}", list) → "  3,   2,   1, Nil", format!("{:.1}", floats) → "0.5, 1.0, Nil".
+impl<T: std::fmt::Display> std::fmt::Display for List<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for head in self {
+            head.fmt(f)?;
+            f.write_str(", ")?;
+        }
+        f.write_str("Nil")
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// The readers live in list_format.rs, shared with the arena list in arena_list.rs.
+mod list_format;
+
+use list_format::{Json, ParseListError};
+
+// Reads "3, 2, 1, Nil"; see list_format::parse_items for the edge cases.
+impl<T: std::str::FromStr> std::str::FromStr for List<T>
+where
+    T::Err: std::fmt::Display,
+{
+    type Err = ParseListError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        list_format::parse_items(input).map(|items| items.into_iter().collect())
+    }
+}
+
+// A list is a JSON array: 3, 2, 1, Nil ⇄ [3,2,1]. Lists of lists nest as arrays of arrays.
+impl<T: Json> Json for List<T> {
+    fn write_json(&self, out: &mut String) {
+        list_format::write_json_items(self, out);
+    }
+
+    fn read_json(input: &str, pos: &mut usize) -> Result<Self, ParseListError> {
+        list_format::read_json_items(input, pos).map(|items| items.into_iter().collect())
     }
 }
 
+impl<T: Json> List<T> {
+    fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+
+    fn from_json(input: &str) -> Result<Self, ParseListError> {
+        list_format::from_json_with(input, Self::read_json)
+    }
+}
+
+fn list_formats_example() {
+    let list: List<f64> = vec![0.5, 1.0, 2.25].into_iter().collect();
+    println!("Display: {}", list);
+    println!("Padded:  [{:>6.2}]", list);
+
+    let parsed: List<i32> = "3, 2, 1, Nil".parse().unwrap();
+    println!("Parsed: {}", parsed);
+    for bad in ["3, two, 1, Nil", "3, 2", "3, , Nil", "3, Nil, 4"] {
+        let err = bad.parse::<List<i32>>().unwrap_err();
+        println!("{:?} → {}", bad, err);
+    }
+
+    let words: List<String> = vec!["plain".to_string(), "with, comma".to_string(), "quote \" \\ \n".to_string()]
+        .into_iter()
+        .collect();
+    println!("JSON: {}", words.to_json());
+    let err = List::<u32>::from_json("[1, 2 3]").unwrap_err();
+    println!("JSON error: {}", err);
+}
+
+fn generic_list_example() {
+    let mut words: List<String> = ["alpha", "beta"].iter().map(|w| w.to_string()).collect();
+    words.extend(vec!["gamma".to_string()]);
+    for word in words.iter_mut() {
+        word.make_ascii_uppercase();
+    }
+    println!("Words: {}", words.stringify());
+
+    let lengths: Vec<usize> = words.iter().map(|w| w.len()).collect();
+    println!("Word lengths: {:?}", lengths);
+
+    let total: u32 = List::new().prepend(3).prepend(2).prepend(1).into_iter().sum();
+    println!("Sum of list: {}", total);
+}
+
+fn list_algorithms_example() {
+    let list: List<i32> = vec![5, 3, 8, 1, 9, 2].into_iter().collect();
+    println!("List: {}", list.stringify());
+
+    let list = list.reverse();
+    println!("Reversed: {}", list.stringify());
+
+    let (front, back) = list.split_at(2);
+    println!("Split at 2: {} | {}", front.stringify(), back.stringify());
+
+    let list = back.append(front);
+    println!("Sum: {}", list.fold(0, |acc, x| acc + x));
+    println!("Third: {:?}, contains 9: {}", list.nth(2), list.contains(&9));
+
+    let evens = list.map(|x| x * 2).filter(|x| x % 4 == 0);
+    println!("Doubled, divisible by 4: {}", evens.stringify());
+
+    let sorted: List<i32> = vec![5, 3, 8, 1, 9, 2].into_iter().collect::<List<_>>().sort();
+    println!("Sorted: {}", sorted.stringify());
+}
+
 // ---------- 6. USE DECLARATION ----------
 enum Stage {
     Beginner,
@@ -303,20 +739,152 @@ fn variable_bindings() {
 fn main() {
     structs_example();
 
-    inspect(WebEvent::PageLoad);
-    inspect(WebEvent::KeyPress('A'));
-    inspect(WebEvent::Click { x: 10, y: 20 });
+    events_example();
 
-    let op = Ops::Add;
-    println!("Ops result: {}", op.run(3, 2));
+    ops_example();
 
     let mut list = List::new();
     list = list.prepend(1).prepend(2).prepend(3);
     println!("List length: {}", list.len());
     println!("List: {}", list.stringify());
+    generic_list_example();
+    list_algorithms_example();
+    list_formats_example();
 
     role_example();
     color_enum();
     const_example(16);
     variable_bindings();
 }
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Regression check: these used to overflow the stack at a few hundred thousand nodes.
+    #[test]
+    fn large_list_check() {
+        const N: u32 = 2_000_000;
+
+        let mut list = List::new();
+        for i in 0..N {
+            list = list.prepend(i);
+        }
+        assert_eq!(list.len(), N);
+
+        let text = list.stringify();
+        assert!(text.starts_with("1999999, 1999998, "));
+        assert!(text.ends_with("1, 0, Nil"));
+        drop(list);
+
+        let collected: List<u64> = (0..N as u64).collect();
+        assert_eq!(collected.len(), N);
+        assert_eq!(collected.iter().next(), Some(&0));
+        assert_eq!(collected.into_iter().sum::<u64>(), (N as u64 - 1) * N as u64 / 2);
+
+        let mut nested: List<List<u32>> = List::new();
+        for _ in 0..1_000 {
+            nested = nested.prepend((0..1_000).collect());
+        }
+        assert_eq!(nested.iter().map(List::len).sum::<u32>(), 1_000_000);
+    }
+
+    #[test]
+    fn ops_check() {
+        use operation::{Mode, OpError};
+        assert_eq!(Ops::Add.run(3, 2), Ok(5));
+        assert_eq!(Ops::Subtract.run(i32::MIN, 1), Err(OpError::Overflow));
+        assert_eq!(Ops::Add.run_with(i32::MAX, 1, Mode::Wrapping), Ok(i32::MIN));
+        assert_eq!(Ops::Add.run_with(i32::MAX, 1, Mode::Saturating), Ok(i32::MAX));
+        assert_eq!(Ops::Remainder.run(i32::MIN, -1), Ok(0));
+        assert_eq!(Ops::Divide.run(7, 0), Err(OpError::DivideByZero));
+    }
+
+    #[test]
+    fn event_conversion_check() {
+        use web_event::{KeyEvent, MouseAction, MouseEvent, Modifiers};
+        assert_eq!(web_event::WebEvent::from(WebEvent::PageLoad), web_event::WebEvent::PageLoad);
+        // The converted variants are the ones the full model gives for plain input.
+        assert_eq!(web_event::WebEvent::from(WebEvent::KeyPress('a')), KeyEvent::from('a').into());
+        let press = MouseEvent { action: MouseAction::Press(web_event::Button::Left), x: 3, y: -4, modifiers: Modifiers::NONE };
+        assert_eq!(web_event::WebEvent::from(WebEvent::Click { x: 3, y: -4 }), press.into());
+    }
+
+    #[test]
+    fn list_algorithms_check() {
+        let list: List<i32> = vec![5, 3, 8, 1, 9, 2].into_iter().collect();
+        let list = list.reverse();
+        assert_eq!(list.stringify(), "2, 9, 1, 8, 3, 5, Nil");
+
+        let (front, back) = list.split_at(2);
+        assert_eq!(front.stringify(), "2, 9, Nil");
+        assert_eq!(back.nth(1), Some(&8));
+        assert!(back.contains(&3) && !back.contains(&9));
+
+        let list = back.append(front);
+        assert_eq!(list.fold(0, |acc, x| acc + x), 28);
+        assert_eq!(list.map(|x| x * 2).filter(|x| x % 4 == 0).stringify(), "16, 4, Nil");
+
+        let sorted: List<i32> = vec![5, 3, 8, 1, 9, 2].into_iter().collect::<List<_>>().sort();
+        assert_eq!(sorted.stringify(), "1, 2, 3, 5, 8, 9, Nil");
+
+        // Stable: equal keys keep their original order.
+        let pairs: List<(u8, char)> = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')].into_iter().collect();
+        let by_key = pairs.sort_by(|a, b| a.0.cmp(&b.0));
+        let order: String = by_key.iter().map(|p| p.1).collect();
+        assert_eq!(order, "bdac");
+
+        // Large inputs stay off the stack too.
+        let big: List<u32> = (0..1_000_000u32).map(|i| i.wrapping_mul(2_654_435_761) % 1_000).collect();
+        let big = big.sort();
+        assert!(big.iter().zip(big.iter().skip(1)).all(|(a, b)| a <= b));
+        assert_eq!(big.reverse().len(), 1_000_000);
+    }
+
+    #[test]
+    fn list_formats_check() {
+        let parsed: List<i32> = "3, 2, 1, Nil".parse().unwrap();
+        assert_eq!(parsed.to_string().parse::<List<i32>>().unwrap().stringify(), "3, 2, 1, Nil");
+        assert!("Nil".parse::<List<i32>>().unwrap().is_empty());
+        assert_eq!("3, two, Nil".parse::<List<i32>>().unwrap_err().pos, 3);
+        assert_eq!("3, 2".parse::<List<i32>>().unwrap_err().pos, 4);
+        assert_eq!("3, Nil, 4".parse::<List<i32>>().unwrap_err().pos, 6);
+
+        let words: List<String> = vec!["plain".to_string(), "with, comma".to_string(), "quote \" \\ \n".to_string()]
+            .into_iter()
+            .collect();
+        let json = words.to_json();
+        assert_eq!(json, r#"["plain","with, comma","quote \" \\ \n"]"#);
+        let back = List::<String>::from_json(&json).unwrap();
+        assert!(back.iter().eq(words.iter()));
+
+        // Save a fixture to disk and load it back.
+        let nested: List<List<u8>> = vec![vec![1, 2].into_iter().collect(), List::new()].into_iter().collect();
+        let path = std::env::temp_dir().join(format!("list_fixture_{}.json", std::process::id()));
+        std::fs::write(&path, nested.to_json()).unwrap();
+        let loaded = List::<List<u8>>::from_json(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(loaded.to_json(), "[[1,2],[]]");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(List::<u32>::from_json("[1, 2 3]").unwrap_err().pos, 6);
+
+        // Round trips that used to lose something: padded and empty strings through
+        // Display / FromStr, escapes outside the BMP, and floats JSON has no number for.
+        let padded: List<String> = vec![" x".to_string(), "y ".to_string(), String::new(), " ".to_string()].into_iter().collect();
+        assert_eq!(padded.to_string(), " x, y , ,  , Nil");
+        assert!(padded.to_string().parse::<List<String>>().unwrap().iter().eq(padded.iter()));
+        assert_eq!("3, , Nil".parse::<List<i32>>().unwrap_err().kind, list_format::ParseListErrorKind::Expected("an element"));
+        let emoji = List::<String>::from_json(r#"["\ud83d\ude00", "\u00e9"]"#).unwrap();
+        assert_eq!(emoji.to_json(), r#"["😀","é"]"#);
+        for lone in [r#"["\ud83d"]"#, r#"["\ude00"]"#, r#"["\ud83dx"]"#, r#"["\u12"]"#] {
+            assert!(List::<String>::from_json(lone).is_err(), "{}", lone);
+        }
+        let floats: List<f64> = vec![1.5, f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -0.0].into_iter().collect();
+        let json = floats.to_json();
+        assert_eq!(json, r#"[1.5,"NaN","Infinity","-Infinity",-0]"#);
+        let back = List::<f64>::from_json(&json).unwrap();
+        assert!(back.iter().zip(floats.iter()).all(|(a, b)| a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())));
+        assert!(List::<f32>::from_json(r#"["inf"]"#).is_err());
+    }
+}
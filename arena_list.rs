@@ -0,0 +1,585 @@
+/*
+Arena-allocated linked list
+
+Every `prepend` on the boxed `List` in 1.rs calls `Box::new`, i.e. one trip to the
+heap allocator per node, and one `free` per node when the list is dropped. When a
+program builds millions of short-lived lists, that allocator traffic dominates.
+
+An arena (a.k.a. region or bump allocator) flips this around:
+
+    1.rs:   List::Cons(val, Box::new(self))      // one heap allocation per node
+    here:   List::Cons(val, arena.alloc(self))   // one slot in a big pre-allocated chunk
+
+✅ Nodes live in a typed arena: big chunks that are filled front to back.
+✅ A node is a plain `&'a mut List<'a, T>` reference into the arena, owned by
+   exactly one list the way a Box is, so iter_mut can hand out `&mut T`.
+✅ Everything is freed together when the arena is dropped: no per-node frees,
+   and no recursive drop problem on long lists.
+⚠️ The borrow checker ties every list to its arena: lists can't outlive it.
+⚠️ Slots aren't reused, so operations that return a new list (reverse, filter,
+   sort, map, ...) allocate its nodes in the arena and the old nodes stay until the
+   arena goes. Those that copy elements need T: Clone.
+
+Same API as the boxed List, with the arena passed in wherever nodes get allocated.
+FromIterator and FromStr have no way to take an arena, so those two become
+constructors, and JSON nesting (lists of lists) isn't supported:
+
+Boxed List (1.rs)	Arena List
+List::new()	List::new()
+list.prepend(x)	list.prepend(&arena, x)
+iter.collect()	List::from_iter_in(iter, &arena)
+list.reverse(), append, split_at, filter, map, sort	same, plus &arena
+text.parse()	List::from_str_in(text, &arena)
+List::from_json(text)	List::from_json_in(text, &arena)
+len, iter, iter_mut, for x in &list, nth, contains, fold, stringify, Display, to_json	same
+
+The text and JSON readers are list_format.rs, shared with 1.rs.
+
+$ rustc -O arena_list.rs
+$ ./arena_list                 # examples
+$ ./arena_list bench 200000 16 # benchmark: 200000 lists of 16 nodes
+$ rustc --test arena_list.rs && ./arena_list
+*/
+
+mod list_format;
+
+use list_format::{Json, ParseListError};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::RefCell;
+use std::fmt;
+use std::hint::black_box;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+// ---------- 1. TYPED ARENA ----------
+pub struct Arena<T> {
+    chunks: RefCell<Vec<Vec<T>>>,
+}
+
+#[allow(dead_code)]
+impl<T> Arena<T> {
+    const FIRST_CHUNK: usize = 64;
+
+    pub fn new() -> Self {
+        Arena { chunks: RefCell::new(vec![Vec::with_capacity(Self::FIRST_CHUNK)]) }
+    }
+
+    // Moves `value` into the arena; it stays put until the arena is dropped.
+    // Each call hands out a different slot, so the `&mut` is never aliased.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+        let last = chunks.last().expect("the arena always has a chunk");
+        if last.len() == last.capacity() {
+            let capacity = last.capacity() * 2;
+            chunks.push(Vec::with_capacity(capacity));
+        }
+        let chunk = chunks.last_mut().expect("the arena always has a chunk");
+        chunk.push(value);
+        // SAFETY: a chunk is never pushed past its capacity, so its buffer never
+        // reallocates, and chunks are only freed when the arena itself is dropped.
+        // `as_mut_ptr` doesn't go through a slice, so the `&mut`s handed out for
+        // earlier slots stay valid.
+        unsafe { &mut *chunk.as_mut_ptr().add(chunk.len() - 1) }
+    }
+
+    // Number of values allocated so far.
+    pub fn len(&self) -> usize {
+        self.chunks.borrow().iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+// ---------- 2. LIST ----------
+pub enum List<'a, T> {
+    Cons(T, &'a mut List<'a, T>),
+    Nil,
+}
+
+pub type ListArena<'a, T> = Arena<List<'a, T>>;
+
+impl<T> Default for List<'_, T> {
+    fn default() -> Self {
+        List::new()
+    }
+}
+
+#[allow(dead_code)]
+impl<'a, T> List<'a, T> {
+    pub fn new() -> Self {
+        List::Nil
+    }
+
+    pub fn prepend(self, arena: &'a ListArena<'a, T>, val: T) -> Self {
+        List::Cons(val, arena.alloc(self))
+    }
+
+    // Builds a list in iterator order: [1, 2, 3] → 1, 2, 3, Nil. O(n) time, n new nodes.
+    pub fn from_iter_in<I: IntoIterator<Item = T>>(iter: I, arena: &'a ListArena<'a, T>) -> Self {
+        let mut list = List::new();
+        let mut slot = &mut list;
+        for val in iter {
+            *slot = List::Cons(val, arena.alloc(List::Nil));
+            if let List::Cons(_, tail) = slot {
+                slot = tail;
+            }
+        }
+        list
+    }
+
+    pub fn len(&self) -> u32 {
+        self.iter().count() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, List::Nil)
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.iter().next()
+    }
+
+    pub fn iter(&self) -> Iter<'_, 'a, T> {
+        Iter { next: self }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, 'a, T> {
+        IterMut { next: Some(self) }
+    }
+
+    // O(n) time.
+    pub fn nth(&self, n: usize) -> Option<&T> {
+        self.iter().nth(n)
+    }
+
+    // O(n) time.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+
+    // O(n) time.
+    pub fn fold<B, F: FnMut(B, &T) -> B>(&self, init: B, f: F) -> B {
+        self.iter().fold(init, f)
+    }
+}
+
+// Operations that copy elements into a new list allocate its nodes in the arena.
+#[allow(dead_code)]
+impl<'a, T: Clone> List<'a, T> {
+    // O(n) time, n new nodes.
+    pub fn reverse(&self, arena: &'a ListArena<'a, T>) -> Self {
+        self.iter().fold(List::new(), |list, val| list.prepend(arena, val.clone()))
+    }
+
+    // O(len(self)) time and new nodes: `other` moves in as the tail, not copied.
+    pub fn append(&self, arena: &'a ListArena<'a, T>, other: List<'a, T>) -> Self {
+        let mut list = List::from_iter_in(self.iter().cloned(), arena);
+        let mut slot = &mut list;
+        while let List::Cons(_, tail) = slot {
+            slot = tail;
+        }
+        *slot = other;
+        list
+    }
+
+    // O(at) time and new nodes: the back half is borrowed from `self`.
+    pub fn split_at(&self, arena: &'a ListArena<'a, T>, at: usize) -> (Self, &Self) {
+        let mut rest = self;
+        for _ in 0..at {
+            if let List::Cons(_, tail) = rest {
+                rest = tail;
+            }
+        }
+        (List::from_iter_in(self.iter().take(at).cloned(), arena), rest)
+    }
+
+    // O(n) time and new nodes; keeps the order.
+    pub fn filter<F: FnMut(&T) -> bool>(&self, arena: &'a ListArena<'a, T>, mut keep: F) -> Self {
+        List::from_iter_in(self.iter().filter(|val| keep(val)).cloned(), arena)
+    }
+
+    // Stable merge sort. O(n log n) time and new nodes, O(log n) stack.
+    pub fn sort_by<F: FnMut(&T, &T) -> std::cmp::Ordering>(&self, arena: &'a ListArena<'a, T>, mut compare: F) -> Self {
+        Self::merge_sort(self, self.len() as usize, arena, &mut compare)
+    }
+
+    pub fn sort(&self, arena: &'a ListArena<'a, T>) -> Self
+    where
+        T: Ord,
+    {
+        self.sort_by(arena, |a, b| a.cmp(b))
+    }
+
+    fn merge_sort<F: FnMut(&T, &T) -> std::cmp::Ordering>(list: &List<'a, T>, len: usize, arena: &'a ListArena<'a, T>, compare: &mut F) -> Self {
+        if len < 2 {
+            return List::from_iter_in(list.iter().take(len).cloned(), arena);
+        }
+        let mut back = list;
+        for _ in 0..len / 2 {
+            if let List::Cons(_, tail) = back {
+                back = tail;
+            }
+        }
+        let front = Self::merge_sort(list, len / 2, arena, compare);
+        let back = Self::merge_sort(back, len - len / 2, arena, compare);
+
+        // Takes from `front` on ties to stay stable.
+        let (mut a, mut b) = (front.iter().peekable(), back.iter().peekable());
+        let merged = std::iter::from_fn(|| match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) if compare(y, x) == std::cmp::Ordering::Less => b.next(),
+            (Some(_), _) => a.next(),
+            (None, _) => b.next(),
+        });
+        List::from_iter_in(merged.cloned(), arena)
+    }
+}
+
+impl<'a, T> List<'a, T> {
+    // O(n) time, n new nodes in the target arena; keeps the order and calls `f` head to tail.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, arena: &'a ListArena<'a, U>, f: F) -> List<'a, U> {
+        List::from_iter_in(self.iter().map(f), arena)
+    }
+}
+
+impl<'a, T: FromStr> List<'a, T>
+where
+    T::Err: fmt::Display,
+{
+    // Reads "3, 2, 1, Nil", like `text.parse()` on the boxed list.
+    pub fn from_str_in(input: &str, arena: &'a ListArena<'a, T>) -> Result<Self, ParseListError> {
+        list_format::parse_items(input).map(|items| List::from_iter_in(items, arena))
+    }
+}
+
+// A list is a JSON array: 3, 2, 1, Nil ⇄ [3,2,1].
+impl<'a, T: Json> List<'a, T> {
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        list_format::write_json_items(self, &mut out);
+        out
+    }
+
+    pub fn from_json_in(input: &str, arena: &'a ListArena<'a, T>) -> Result<Self, ParseListError> {
+        list_format::from_json_with(input, list_format::read_json_items).map(|items| List::from_iter_in(items, arena))
+    }
+}
+
+impl<T: fmt::Display> List<'_, T> {
+    pub fn stringify(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for List<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for head in self {
+            head.fmt(f)?;
+            f.write_str(", ")?;
+        }
+        f.write_str("Nil")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<'l, 'a, T> {
+    next: &'l List<'a, T>,
+}
+
+impl<'l, T> Iterator for Iter<'l, '_, T> {
+    type Item = &'l T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next {
+            List::Cons(head, tail) => {
+                self.next = tail;
+                Some(head)
+            }
+            List::Nil => None,
+        }
+    }
+}
+
+// Takes the remaining list out of `next` so each `&mut T` is handed out once.
+pub struct IterMut<'l, 'a, T> {
+    next: Option<&'l mut List<'a, T>>,
+}
+
+impl<'l, T> Iterator for IterMut<'l, '_, T> {
+    type Item = &'l mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next.take() {
+            Some(List::Cons(head, tail)) => {
+                self.next = Some(tail);
+                Some(head)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'l, 'a, T> IntoIterator for &'l List<'a, T> {
+    type Item = &'l T;
+    type IntoIter = Iter<'l, 'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'l, 'a, T> IntoIterator for &'l mut List<'a, T> {
+    type Item = &'l mut T;
+    type IntoIter = IterMut<'l, 'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+fn arena_list_example() {
+    let arena = ListArena::new();
+    let list = List::new().prepend(&arena, 1).prepend(&arena, 2).prepend(&arena, 3);
+    println!("linked list has length: {}", list.len());
+    println!("{}", list.stringify());
+
+    let mut numbers = List::from_iter_in(vec![5, 3, 8, 1, 9, 2], &arena);
+    let (front, back) = numbers.split_at(&arena, 2);
+    println!("split:  {} | {}", front, back);
+    println!("sorted: {}", numbers.sort(&arena));
+    println!("odd:    {}", numbers.filter(&arena, |x| x % 2 == 1));
+    for x in &mut numbers {
+        *x *= 10;
+    }
+    println!("scaled: {}", numbers);
+
+    let words = ListArena::new();
+    let labels = numbers.map(&words, |x| format!("#{}", x));
+    println!("mapped: {}", labels);
+    println!("JSON:   {}", labels.to_json());
+    match List::<i32>::from_str_in("3, two, 1, Nil", &arena) {
+        Ok(list) => println!("parsed: {}", list),
+        Err(err) => println!("parse error: {}", err),
+    }
+}
+
+// ---------- 3. BENCHMARK ----------
+// Counts heap allocations so the benchmark can report them next to the timings.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// The boxed list from 1.rs, cut down to what the benchmark uses.
+enum BoxedList<T> {
+    Cons(T, Box<BoxedList<T>>),
+    Nil,
+}
+
+impl<T> BoxedList<T> {
+    fn prepend(self, val: T) -> Self {
+        BoxedList::Cons(val, Box::new(self))
+    }
+
+    fn sum(&self) -> u64
+    where
+        T: Copy + Into<u64>,
+    {
+        let mut total = 0;
+        let mut cur = self;
+        while let BoxedList::Cons(head, tail) = cur {
+            total += (*head).into();
+            cur = tail;
+        }
+        total
+    }
+}
+
+impl<T> Drop for BoxedList<T> {
+    fn drop(&mut self) {
+        if let BoxedList::Cons(_, tail) = self {
+            let mut cur = std::mem::replace(&mut **tail, BoxedList::Nil);
+            while let BoxedList::Cons(_, tail) = &mut cur {
+                cur = std::mem::replace(&mut **tail, BoxedList::Nil);
+            }
+        }
+    }
+}
+
+struct Measurement {
+    time: Duration,
+    allocations: usize,
+    checksum: u64,
+}
+
+// Runs `work` and reports its wall time and heap allocations next to its result.
+fn measure(work: impl FnOnce() -> u64) -> Measurement {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let checksum = work();
+    Measurement { time: start.elapsed(), allocations: ALLOCATIONS.load(Ordering::Relaxed) - before, checksum }
+}
+
+// Builds and sums `lists` lists of `len` nodes with each backend: (boxed, arena).
+fn benchmark(lists: usize, len: u32) -> (Measurement, Measurement) {
+    let boxed = measure(|| {
+        let mut checksum = 0;
+        for _ in 0..lists {
+            let list = (0..len).fold(BoxedList::Nil, |list, i| list.prepend(i));
+            checksum += black_box(&list).sum();
+        }
+        checksum
+    });
+
+    let arena = measure(|| {
+        let arena = ListArena::new();
+        let mut checksum = 0;
+        for _ in 0..lists {
+            let list = (0..len).fold(List::new(), |list, i| list.prepend(&arena, i));
+            checksum += black_box(&list).fold(0u64, |acc, &x| acc + x as u64);
+        }
+        checksum
+    });
+    (boxed, arena)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("bench") {
+        let lists = args.get(2).and_then(|n| n.parse().ok()).unwrap_or(200_000);
+        let len = args.get(3).and_then(|n| n.parse().ok()).unwrap_or(16);
+        let (boxed, arena) = benchmark(lists, len);
+        assert_eq!(boxed.checksum, arena.checksum);
+        println!("{} lists x {} nodes", lists, len);
+        println!("{:<8} {:>12} {:>14}", "backend", "time", "allocations");
+        println!("{:<8} {:>12.2?} {:>14}", "boxed", boxed.time, boxed.allocations);
+        println!("{:<8} {:>12.2?} {:>14}", "arena", arena.time, arena.allocations);
+        println!("speedup: {:.2}x", boxed.time.as_secs_f64() / arena.time.as_secs_f64());
+        return;
+    }
+
+    arena_list_example();
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A long list is freed in one go with its arena, no recursion involved.
+    #[test]
+    fn long_list_check() {
+        let big = ListArena::new();
+        let long = (0..1_000_000).fold(List::new(), |list, i| list.prepend(&big, i));
+        assert_eq!(long.len(), 1_000_000);
+        assert_eq!(big.len(), 1_000_000);
+    }
+
+    #[test]
+    fn operations_check() {
+        let arena = ListArena::new();
+        let numbers = List::from_iter_in(vec![5, 3, 8, 1, 9, 2], &arena);
+        let (front, back) = numbers.split_at(&arena, 2);
+        assert_eq!(front.stringify(), "5, 3, Nil");
+        assert_eq!(back.stringify(), "8, 1, 9, 2, Nil");
+        assert_eq!(numbers.split_at(&arena, 10).1.stringify(), "Nil");
+        let joined = front.append(&arena, numbers.reverse(&arena));
+        assert_eq!(joined.stringify(), "5, 3, 2, 9, 1, 8, 3, 5, Nil");
+        assert_eq!(joined.nth(2), Some(&2));
+        assert!(numbers.contains(&9));
+        assert_eq!(numbers.fold(0, |acc, x| acc + x), 28);
+        assert_eq!(numbers.filter(&arena, |x| x % 2 == 1).stringify(), "5, 3, 1, 9, Nil");
+        assert_eq!(numbers.sort(&arena).stringify(), "1, 2, 3, 5, 8, 9, Nil");
+
+        // Stable: equal keys keep their order.
+        let pairs = ListArena::new();
+        let pairs = List::from_iter_in(vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')], &pairs);
+        let sorted = ListArena::new();
+        let sorted = pairs.sort_by(&sorted, |a, b| a.0.cmp(&b.0));
+        assert!(sorted.iter().map(|p| p.1).eq("bdac".chars()));
+    }
+
+    #[test]
+    fn iterators_check() {
+        let arena = ListArena::new();
+        let mut list = List::from_iter_in(1..=4, &arena);
+        for x in list.iter_mut() {
+            *x *= 10;
+        }
+        for x in &mut list {
+            *x += 1;
+        }
+        assert!((&list).into_iter().eq(&[11, 21, 31, 41]));
+
+        // `map` calls `f` once per element, head to tail.
+        let mut seen = Vec::new();
+        let words = ListArena::new();
+        let mapped = list.map(&words, |x| {
+            seen.push(*x);
+            x.to_string()
+        });
+        assert_eq!(seen, [11, 21, 31, 41]);
+        assert_eq!(mapped.stringify(), "11, 21, 31, 41, Nil");
+    }
+
+    #[test]
+    fn formats_check() {
+        let arena = ListArena::new();
+        let list = List::<i32>::from_str_in("3, 2, 1, Nil", &arena).unwrap();
+        assert_eq!(list.to_string(), "3, 2, 1, Nil");
+        assert!(List::<i32>::from_str_in("Nil", &arena).unwrap().is_empty());
+        assert_eq!(List::<i32>::from_str_in("3, two, Nil", &arena).unwrap_err().pos, 3);
+
+        let json = list.to_json();
+        assert_eq!(json, "[3,2,1]");
+        assert_eq!(List::<i32>::from_json_in(&json, &arena).unwrap().to_string(), "3, 2, 1, Nil");
+        assert_eq!(List::<u32>::from_json_in("[1, 2 3]", &ListArena::new()).unwrap_err().pos, 6);
+
+        let strings = ListArena::new();
+        let words = List::from_iter_in(vec!["with, comma".to_string(), "quote \"".to_string()], &strings);
+        let back = List::<String>::from_json_in(&words.to_json(), &strings).unwrap();
+        assert!(back.iter().eq(words.iter()));
+    }
+
+    // Both backends build the same lists; the arena needs a handful of chunk
+    // allocations where the boxed list needs one per node. Other tests allocate
+    // concurrently, so the bound on the arena is loose.
+    #[test]
+    fn benchmark_check() {
+        let (boxed, arena) = benchmark(2_000, 16);
+        assert_eq!(boxed.checksum, arena.checksum);
+        assert_eq!(arena.checksum, 2_000 * (0..16).sum::<u64>());
+        assert!(boxed.allocations >= 2_000 * 16);
+        assert!(arena.allocations * 100 < boxed.allocations, "{} vs {}", arena.allocations, boxed.allocations);
+    }
+}
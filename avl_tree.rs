@@ -0,0 +1,439 @@
+/*
+Balanced binary search tree (AVL) as a recursive enum
+
+`List` in 1.rs is the classic recursive enum: every Cons owns the rest of the
+list through a Box. A binary search tree is the same idea with TWO children:
+
+    enum List { Cons(T, Box<List>), Nil }
+    enum Tree { Node(Box<Node>), Empty }   where Node = { key, value, left: Tree, right: Tree }
+
+📌 Search-tree rule: every key in `left` is smaller than the node's key, every
+key in `right` is bigger. Lookups go left or right at each node → O(height).
+
+📌 AVL rule: the heights of `left` and `right` differ by at most 1 at every
+node. After an insert or remove, a node that breaks the rule is fixed with one
+or two rotations, which keeps height ≈ 1.44·log2(n). So:
+
+Operation	Cost
+insert / remove / get	O(log n)
+range(a..b)	O(log n + items returned)
+iter / Display	O(n), in key order
+
+A rotation just re-hangs Boxes, no data is copied:
+
+        y                x
+       / \   rotate    /   \
+      x   C  ─right→  A     y
+     / \                   / \
+    A   B                 B   C
+
+The tests check the invariants (order, heights, balance, len) after every step
+of a long random sequence of inserts and removes, comparing against BTreeMap.
+
+$ rustc -O avl_tree.rs
+$ ./avl_tree
+$ rustc -O --test avl_tree.rs && ./avl_tree
+*/
+
+#[cfg(test)]
+mod testing;
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+enum Tree<K, V> {
+    Node(Box<Node<K, V>>),
+    Empty,
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    height: u8,
+    left: Tree<K, V>,
+    right: Tree<K, V>,
+}
+
+pub struct AvlMap<K, V> {
+    root: Tree<K, V>,
+    len: usize,
+}
+
+impl<K, V> Node<K, V> {
+    fn update_height(&mut self) {
+        self.height = 1 + self.left.height().max(self.right.height());
+    }
+
+    fn balance_factor(&self) -> i16 {
+        self.left.height() as i16 - self.right.height() as i16
+    }
+}
+
+impl<K, V> Tree<K, V> {
+    fn height(&self) -> u8 {
+        match self {
+            Tree::Node(node) => node.height,
+            Tree::Empty => 0,
+        }
+    }
+
+    fn take(&mut self) -> Tree<K, V> {
+        std::mem::replace(self, Tree::Empty)
+    }
+
+    fn rotate_right(&mut self) {
+        if let Tree::Node(mut root) = self.take() {
+            if let Tree::Node(mut pivot) = root.left.take() {
+                root.left = pivot.right.take();
+                root.update_height();
+                pivot.right = Tree::Node(root);
+                pivot.update_height();
+                *self = Tree::Node(pivot);
+            }
+        }
+    }
+
+    fn rotate_left(&mut self) {
+        if let Tree::Node(mut root) = self.take() {
+            if let Tree::Node(mut pivot) = root.right.take() {
+                root.right = pivot.left.take();
+                root.update_height();
+                pivot.left = Tree::Node(root);
+                pivot.update_height();
+                *self = Tree::Node(pivot);
+            }
+        }
+    }
+
+    // Restores the AVL rule at this node, assuming both subtrees already obey it.
+    fn rebalance(&mut self) {
+        let Tree::Node(node) = self else { return };
+        node.update_height();
+        match node.balance_factor() {
+            2 => {
+                if let Tree::Node(left) = &node.left {
+                    if left.balance_factor() < 0 {
+                        node.left.rotate_left();
+                    }
+                }
+                self.rotate_right();
+            }
+            -2 => {
+                if let Tree::Node(right) = &node.right {
+                    if right.balance_factor() > 0 {
+                        node.right.rotate_right();
+                    }
+                }
+                self.rotate_left();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<K: Ord, V> Tree<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old = match self {
+            Tree::Empty => {
+                *self = Tree::Node(Box::new(Node { key, value, height: 1, left: Tree::Empty, right: Tree::Empty }));
+                return None;
+            }
+            Tree::Node(node) => match key.cmp(&node.key) {
+                Ordering::Less => node.left.insert(key, value),
+                Ordering::Greater => node.right.insert(key, value),
+                Ordering::Equal => return Some(std::mem::replace(&mut node.value, value)),
+            },
+        };
+        self.rebalance();
+        old
+    }
+
+    fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let removed = match self {
+            Tree::Empty => return None,
+            Tree::Node(node) => match key.cmp(node.key.borrow()) {
+                Ordering::Less => node.left.remove(key),
+                Ordering::Greater => node.right.remove(key),
+                Ordering::Equal => {
+                    let Tree::Node(mut node) = self.take() else { unreachable!() };
+                    *self = match (node.left.take(), node.right.take()) {
+                        (left, Tree::Empty) => left,
+                        (Tree::Empty, right) => right,
+                        // Two children: the smallest key on the right takes this node's place.
+                        (left, mut right) => {
+                            let mut successor = right.remove_min().expect("right subtree is not empty");
+                            successor.left = left;
+                            successor.right = right;
+                            Tree::Node(successor)
+                        }
+                    };
+                    Some(node.value)
+                }
+            },
+        };
+        self.rebalance();
+        removed
+    }
+
+    // Unlinks and returns the leftmost node, rebalancing on the way back up.
+    fn remove_min(&mut self) -> Option<Box<Node<K, V>>> {
+        let Tree::Node(node) = self else { return None };
+        if let Tree::Node(_) = node.left {
+            let min = node.left.remove_min();
+            self.rebalance();
+            return min;
+        }
+        let Tree::Node(mut min) = self.take() else { unreachable!() };
+        *self = min.right.take();
+        Some(min)
+    }
+
+    fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let mut tree = self;
+        while let Tree::Node(node) = tree {
+            tree = match key.cmp(node.key.borrow()) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+}
+
+#[allow(dead_code)]
+impl<K: Ord, V> AvlMap<K, V> {
+    pub fn new() -> Self {
+        AvlMap { root: Tree::Empty, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn height(&self) -> u8 {
+        self.root.height()
+    }
+
+    // Returns the previous value if the key was already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old = self.root.insert(key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let removed = self.root.remove(key);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.root.get(key)
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    // In-order iteration over the entries whose keys fall inside `bounds`.
+    pub fn range<R: RangeBounds<K>>(&self, bounds: R) -> Range<'_, K, V, R> {
+        let mut range = Range { stack: Vec::new(), bounds };
+        range.seek(&self.root);
+        range
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V, std::ops::RangeFull> {
+        self.range(..)
+    }
+
+    // Panics with a description if the tree breaks the search-tree or AVL rules.
+    fn check_invariants(&self) {
+        fn check<K: Ord, V>(tree: &Tree<K, V>, lower: Option<&K>, upper: Option<&K>) -> (u8, usize) {
+            let Tree::Node(node) = tree else { return (0, 0) };
+            assert!(lower.is_none_or(|low| *low < node.key), "key not greater than its left ancestors");
+            assert!(upper.is_none_or(|up| node.key < *up), "key not smaller than its right ancestors");
+            let (left_height, left_len) = check(&node.left, lower, Some(&node.key));
+            let (right_height, right_len) = check(&node.right, Some(&node.key), upper);
+            assert_eq!(node.height, 1 + left_height.max(right_height), "stale height");
+            assert!(left_height.abs_diff(right_height) <= 1, "unbalanced node");
+            (node.height, 1 + left_len + right_len)
+        }
+        let (_, len) = check(&self.root, None, None);
+        assert_eq!(len, self.len, "len does not match the node count");
+    }
+}
+
+impl<K: Ord, V> Default for AvlMap<K, V> {
+    fn default() -> Self {
+        AvlMap::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for AvlMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = AvlMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for AvlMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+// In key order: {1: one, 2: two}
+impl<K: Ord + fmt::Display, V: fmt::Display> fmt::Display for AvlMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("{")?;
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}: {}", key, value)?;
+        }
+        f.write_str("}")
+    }
+}
+
+// In-order walk with an explicit stack of the nodes still to visit.
+pub struct Range<'a, K, V, R> {
+    stack: Vec<&'a Node<K, V>>,
+    bounds: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Range<'a, K, V, R> {
+    fn after_start(&self, key: &K) -> bool {
+        match self.bounds.start_bound() {
+            Bound::Included(start) => key >= start,
+            Bound::Excluded(start) => key > start,
+            Bound::Unbounded => true,
+        }
+    }
+
+    fn before_end(&self, key: &K) -> bool {
+        match self.bounds.end_bound() {
+            Bound::Included(end) => key <= end,
+            Bound::Excluded(end) => key < end,
+            Bound::Unbounded => true,
+        }
+    }
+
+    // Pushes the path to the smallest key in `tree` that is not before the start bound.
+    fn seek(&mut self, mut tree: &'a Tree<K, V>) {
+        while let Tree::Node(node) = tree {
+            if self.after_start(&node.key) {
+                self.stack.push(node);
+                tree = &node.left;
+            } else {
+                tree = &node.right;
+            }
+        }
+    }
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if !self.before_end(&node.key) {
+            self.stack.clear();
+            return None;
+        }
+        self.seek(&node.right);
+        Some((&node.key, &node.value))
+    }
+}
+
+// ---------- 1. ORDERED MAP ----------
+fn map_example() {
+    let mut map: AvlMap<u32, &str> = [(3, "three"), (1, "one"), (4, "four"), (5, "five"), (9, "nine")]
+        .into_iter()
+        .collect();
+    map.insert(2, "two");
+    assert_eq!(map.insert(4, "FOUR"), Some("four"));
+    println!("map:      {}", map);
+    println!("get(4):   {:?}", map.get(&4));
+    let in_range: Vec<u32> = map.range(2..5).map(|(k, _)| *k).collect();
+    println!("2..5:     {:?}", in_range);
+    assert_eq!(in_range, vec![2, 3, 4]);
+    assert_eq!(map.remove(&3), Some("three"));
+    println!("remove 3: {}", map);
+    map.check_invariants();
+}
+
+fn main() {
+    map_example();
+}
+
+// ---------- 2. INVARIANT CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::{next_random, SEED};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn random_ops_check() {
+        let mut map = AvlMap::new();
+        let mut model = BTreeMap::new();
+        let mut seed = SEED;
+
+        for step in 0..20_000 {
+            let key = next_random(&mut seed) % 500;
+            if next_random(&mut seed).is_multiple_of(3) {
+                assert_eq!(map.remove(&key), model.remove(&key), "remove {} at step {}", key, step);
+            } else {
+                assert_eq!(map.insert(key, step), model.insert(key, step), "insert {} at step {}", key, step);
+            }
+            map.check_invariants();
+            assert_eq!(map.len(), model.len());
+
+            let (low, high) = (key.saturating_sub(20), key + 20);
+            assert!(map.range(low..=high).eq(model.range(low..=high)), "range at step {}", step);
+        }
+        assert!(map.iter().eq(model.iter()));
+        println!("random check: 20000 inserts/removes, invariants held at every step");
+    }
+
+    #[test]
+    fn sorted_insert_check() {
+        let n = 1_000_000u32;
+        let map: AvlMap<u32, ()> = (0..n).map(|k| (k, ())).collect();
+        map.check_invariants();
+        // AVL height bound: h < 1.44 * log2(n + 2).
+        let bound = 1.44 * ((n + 2) as f64).log2();
+        assert!((map.height() as f64) < bound);
+        println!("sorted insert: {} keys, height {} (bound {:.1})", n, map.height(), bound);
+    }
+}
@@ -0,0 +1,693 @@
+/*
+BigInt — arbitrary-precision integers for `Operation`
+
+i32 (and every other primitive) overflows; BigInt doesn't. It is a sign plus
+a magnitude stored as base-2³² digits ("limbs"), least significant first:
+
+    -(2^32 + 5)  →  BigInt { negative: true, magnitude: [5, 1] }
+
+Operation	Algorithm
++ -	limb by limb with carry / borrow	O(n)
+*	schoolbook	O(n·m)
+/ %	Knuth's algorithm D, truncating like i32 (-7 / 2 == -3, -7 % 2 == -1)	O(n·m)
+pow	square and multiply	O(log y) multiplications
+& | ^	on the two's complement form, like i32 (-1 & x == x)
+<< >>	limb and bit shifts; >> floors like i32 (-5 >> 1 == -3)
+
+📌 Parsing: "123", "-0xff", "0b1010_1010" (FromStr), or from_str_radix(s, 2..=36).
+📌 Printing: Display (decimal, honours width / sign / fill), {:x} {:X} {:o} {:b}
+   with `#` for the prefix, and to_str_radix(2..=36) for any base.
+⚠️ "No overflow" still needs a memory limit: a result over MAX_BITS bits is
+   reported as OpError::Overflow in every Mode, so pow(3, 1000000000) fails
+   fast instead of allocating gigabytes.
+*/
+
+// Shared by several programs; each one uses a different subset.
+#![allow(dead_code)]
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops;
+use std::str::FromStr;
+
+use crate::operation::{Mode, Number, OpError, Operation};
+
+// Results bigger than this many bits are reported as overflow.
+pub const MAX_BITS: u64 = 1 << 18;
+
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,      // never true for zero
+    magnitude: Vec<u32>, // little-endian limbs, no trailing (most significant) zeros
+}
+
+// ---------- 1. MAGNITUDES ----------
+// Unsigned helpers on limb slices; the signed operations are built from these.
+fn trim(mut limbs: Vec<u32>) -> Vec<u32> {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+    limbs
+}
+
+fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, limb) in long.iter().enumerate() {
+        let total = *limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    if carry != 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+// a - b, for a >= b.
+fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, limb) in a.iter().enumerate() {
+        let mut total = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = (total < 0) as i64;
+        total += borrow << 32;
+        difference.push(total as u32);
+    }
+    debug_assert_eq!(borrow, 0, "sub_mag needs a >= b");
+    trim(difference)
+}
+
+fn mul_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.iter().enumerate() {
+            let total = *x as u64 * *y as u64 + product[i + j] as u64 + carry;
+            product[i + j] = total as u32;
+            carry = total >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    trim(product)
+}
+
+fn shl_mag(a: &[u32], bits: u64) -> Vec<u32> {
+    if a.is_empty() {
+        return Vec::new();
+    }
+    let (limbs, bits) = ((bits / 32) as usize, (bits % 32) as u32);
+    let mut shifted = vec![0u32; limbs];
+    if bits == 0 {
+        shifted.extend_from_slice(a);
+    } else {
+        let mut carry = 0;
+        for limb in a {
+            shifted.push((limb << bits) | carry);
+            carry = limb >> (32 - bits);
+        }
+        shifted.push(carry);
+    }
+    trim(shifted)
+}
+
+fn shr_mag(a: &[u32], bits: u64) -> Vec<u32> {
+    let (limbs, bits) = ((bits / 32) as usize, (bits % 32) as u32);
+    if limbs >= a.len() {
+        return Vec::new();
+    }
+    let a = &a[limbs..];
+    if bits == 0 {
+        return a.to_vec();
+    }
+    let shifted = (0..a.len()).map(|i| (a[i] >> bits) | a.get(i + 1).map_or(0, |next| next << (32 - bits))).collect();
+    trim(shifted)
+}
+
+// Division by one limb: (quotient, remainder).
+fn divmod_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) | a[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    (trim(quotient), remainder as u32)
+}
+
+// Knuth, The Art of Computer Programming vol. 2, 4.3.1, algorithm D.
+// Long division where each quotient limb is estimated from the top two limbs
+// and corrected at most twice. `b` must not be zero.
+fn divmod_mag(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_mag(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if b.len() == 1 {
+        let (quotient, remainder) = divmod_small(a, b[0]);
+        return (quotient, trim(vec![remainder]));
+    }
+
+    // Normalize: shift so the divisor's top limb has its high bit set.
+    let shift = b.last().expect("divisor is not zero").leading_zeros() as u64;
+    let v = shl_mag(b, shift);
+    let mut u = shl_mag(a, shift);
+    if u.len() == a.len() {
+        u.push(0);
+    }
+    let n = v.len();
+    let m = u.len() - n - 1;
+    let base = 1u64 << 32;
+
+    let mut quotient = vec![0u32; m + 1];
+    for j in (0..=m).rev() {
+        let top = ((u[j + n] as u64) << 32) | u[j + n - 1] as u64;
+        let mut q = top / v[n - 1] as u64;
+        let mut r = top % v[n - 1] as u64;
+        while q >= base || q * v[n - 2] as u64 > ((r << 32) | u[j + n - 2] as u64) {
+            q -= 1;
+            r += v[n - 1] as u64;
+            if r >= base {
+                break;
+            }
+        }
+
+        // u[j..=j + n] -= q * v
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let product = q * v[i] as u64 + carry;
+            carry = product >> 32;
+            let total = u[i + j] as i64 - borrow - (product & 0xffff_ffff) as i64;
+            u[i + j] = total as u32;
+            borrow = (total < 0) as i64;
+        }
+        let total = u[j + n] as i64 - borrow - carry as i64;
+        u[j + n] = total as u32;
+
+        // q was one too big (rare): add v back.
+        if total < 0 {
+            q -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = u[i + j] as u64 + v[i] as u64 + carry;
+                u[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            u[j + n] = u[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = q as u32;
+    }
+    (trim(quotient), shr_mag(&trim(u[..n].to_vec()), shift))
+}
+
+// ---------- 2. BIGINT ----------
+impl BigInt {
+    pub fn zero() -> Self {
+        BigInt::default()
+    }
+
+    pub fn one() -> Self {
+        BigInt::from(1)
+    }
+
+    fn from_parts(negative: bool, magnitude: Vec<u32>) -> Self {
+        let magnitude = trim(magnitude);
+        BigInt { negative: negative && !magnitude.is_empty(), magnitude }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.magnitude.first().is_some_and(|limb| limb % 2 == 1)
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::from_parts(false, self.magnitude.clone())
+    }
+
+    // Number of bits in the magnitude; 0 for zero.
+    pub fn bits(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(top) => self.magnitude.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    // The value as an i128 / u64, if it fits.
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.bits() > 127 {
+            return None;
+        }
+        let magnitude = self.magnitude.iter().rev().fold(0i128, |acc, limb| (acc << 32) | *limb as i128);
+        Some(if self.negative { -magnitude } else { magnitude })
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        self.to_i128().and_then(|value| u64::try_from(value).ok())
+    }
+
+    // Truncating division, like i32: the quotient rounds toward zero and the
+    // remainder has the sign of `self`. None when `divisor` is zero.
+    pub fn div_rem(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = divmod_mag(&self.magnitude, &divisor.magnitude);
+        Some((
+            BigInt::from_parts(self.negative != divisor.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+
+    pub fn pow(&self, mut exponent: u64) -> BigInt {
+        let mut base = self.clone();
+        let mut result = BigInt::one();
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = &result * &base;
+            }
+            exponent /= 2;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let (mut a, mut b) = (self.abs(), other.abs());
+        while !b.is_zero() {
+            let remainder = a.div_rem(&b).expect("b is not zero").1;
+            a = b;
+            b = remainder;
+        }
+        a
+    }
+
+    fn shl_bits(&self, bits: u64) -> BigInt {
+        BigInt::from_parts(self.negative, shl_mag(&self.magnitude, bits))
+    }
+
+    // Floors like i32's >>: -5 >> 1 == -3.
+    fn shr_bits(&self, bits: u64) -> BigInt {
+        if !self.negative {
+            return BigInt::from_parts(false, shr_mag(&self.magnitude, bits));
+        }
+        // -x >> n == -((x - 1) >> n) - 1
+        let x_minus_one = sub_mag(&self.magnitude, &[1]);
+        let shifted = shr_mag(&x_minus_one, bits);
+        BigInt::from_parts(true, add_mag(&shifted, &[1]))
+    }
+
+    // Two's complement in `len` limbs (len must leave room for the sign bit).
+    fn to_twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut limbs = self.magnitude.clone();
+        limbs.resize(len, 0);
+        if self.negative {
+            // -x == !x + 1
+            let mut carry = 1u64;
+            for limb in &mut limbs {
+                let total = (!*limb) as u64 + carry;
+                *limb = total as u32;
+                carry = total >> 32;
+            }
+        }
+        limbs
+    }
+
+    fn from_twos_complement(mut limbs: Vec<u32>) -> BigInt {
+        let negative = limbs.last().is_some_and(|top| top >> 31 == 1);
+        if negative {
+            let mut carry = 1u64;
+            for limb in &mut limbs {
+                let total = (!*limb) as u64 + carry;
+                *limb = total as u32;
+                carry = total >> 32;
+            }
+        }
+        BigInt::from_parts(negative, limbs)
+    }
+
+    fn bitwise(&self, other: &BigInt, f: impl Fn(u32, u32) -> u32) -> BigInt {
+        let len = self.magnitude.len().max(other.magnitude.len()) + 1;
+        let (a, b) = (self.to_twos_complement(len), other.to_twos_complement(len));
+        BigInt::from_twos_complement(a.iter().zip(&b).map(|(x, y)| f(*x, *y)).collect())
+    }
+}
+
+macro_rules! bigint_from {
+    ($($t:ident)*) => {$(
+        impl From<$t> for BigInt {
+            fn from(value: $t) -> Self {
+                let negative = (value as i128) < 0;
+                let mut magnitude = (value as i128).unsigned_abs();
+                let mut limbs = Vec::new();
+                while magnitude != 0 {
+                    limbs.push(magnitude as u32);
+                    magnitude >>= 32;
+                }
+                BigInt::from_parts(negative, limbs)
+            }
+        }
+    )*};
+}
+
+bigint_from!(i8 i16 i32 i64 isize u8 u16 u32 u64 usize);
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_mag(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_mag(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// ---------- 3. OPERATORS ----------
+impl ops::Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl ops::Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_mag(&self.magnitude, &other.magnitude));
+        }
+        match cmp_mag(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_parts(other.negative, sub_mag(&other.magnitude, &self.magnitude)),
+            _ => BigInt::from_parts(self.negative, sub_mag(&self.magnitude, &other.magnitude)),
+        }
+    }
+}
+
+impl ops::Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl ops::Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(self.negative != other.negative, mul_mag(&self.magnitude, &other.magnitude))
+    }
+}
+
+// Panics on division by zero, like the primitive integers.
+impl ops::Div for &BigInt {
+    type Output = BigInt;
+
+    fn div(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).expect("attempt to divide by zero").0
+    }
+}
+
+impl ops::Rem for &BigInt {
+    type Output = BigInt;
+
+    fn rem(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).expect("attempt to calculate the remainder with a divisor of zero").1
+    }
+}
+
+// The same operators on owned values.
+macro_rules! owned_operator {
+    ($($trait:ident $method:ident)*) => {$(
+        impl ops::$trait for BigInt {
+            type Output = BigInt;
+
+            fn $method(self, other: BigInt) -> BigInt {
+                ops::$trait::$method(&self, &other)
+            }
+        }
+    )*};
+}
+
+owned_operator!(Add add Sub sub Mul mul Div div Rem rem);
+
+impl ops::Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        -&self
+    }
+}
+
+// ---------- 4. OPERATION ----------
+// Results never overflow, so Mode has nothing to choose: the only overflow is
+// going over MAX_BITS, which is an error in every mode.
+impl Number for BigInt {
+    const NAME: &'static str = "bigint";
+
+    fn from_digits(digits: &str, radix: u32) -> Option<Self> {
+        BigInt::from_str_radix(digits, radix).ok()
+    }
+
+    fn run(op: Operation, x: Self, y: Self, _: Mode) -> Result<Self, OpError> {
+        use Operation::*;
+
+        let too_big = |bits: u64| if bits > MAX_BITS { Err(OpError::Overflow) } else { Ok(()) };
+        let value = match op {
+            Add => &x + &y,
+            Subtract => &x - &y,
+            Multiply => {
+                too_big(x.bits() + y.bits())?;
+                &x * &y
+            }
+            Divide => x.div_rem(&y).ok_or(OpError::DivideByZero)?.0,
+            Remainder => x.div_rem(&y).ok_or(OpError::DivideByZero)?.1,
+            Pow => {
+                if y.is_negative() {
+                    return Err(OpError::NegativeExponent);
+                }
+                // 0, 1 and -1 stay small whatever the exponent.
+                if x.bits() <= 1 {
+                    let value = if y.is_zero() { BigInt::one() } else { x.clone() };
+                    let even = !y.is_odd() && !y.is_zero();
+                    return Ok(if x.is_negative() && even { -value } else { value });
+                }
+                let exponent = y.to_u64().ok_or(OpError::Overflow)?;
+                too_big((x.bits() - 1).saturating_mul(exponent))?;
+                x.pow(exponent)
+            }
+            Min => x.min(y),
+            Max => x.max(y),
+            BitAnd => x.bitwise(&y, |a, b| a & b),
+            BitOr => x.bitwise(&y, |a, b| a | b),
+            BitXor => x.bitwise(&y, |a, b| a ^ b),
+            // Shift amounts can't be negative; Shr by more than the size of x gives 0 or -1.
+            Shl if y.is_negative() => return Err(OpError::NegativeShift),
+            Shl => {
+                let amount = y.to_u64().ok_or(OpError::Overflow)?;
+                too_big(x.bits().saturating_add(amount))?;
+                x.shl_bits(amount)
+            }
+            Shr if y.is_negative() => return Err(OpError::NegativeShift),
+            Shr => x.shr_bits(y.to_u64().unwrap_or(u64::MAX).min(x.bits() + 1)),
+        };
+        too_big(value.bits())?;
+        Ok(value)
+    }
+}
+
+// ---------- 5. PARSING AND PRINTING ----------
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseBigIntError {
+    pub input: String,
+}
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid integer `{}`", self.input)
+    }
+}
+
+impl std::error::Error for ParseBigIntError {}
+
+impl BigInt {
+    // An optional sign, then digits in `radix` (2..=36); `_` separators are allowed.
+    pub fn from_str_radix(text: &str, radix: u32) -> Result<BigInt, ParseBigIntError> {
+        assert!((2..=36).contains(&radix), "radix must be in 2..=36");
+        let error = || ParseBigIntError { input: text.to_string() };
+        let (negative, digits) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        if !digits.chars().any(|c| c != '_') || digits.starts_with('_') {
+            return Err(error());
+        }
+
+        // Work in chunks: the largest power of `radix` that fits in a limb.
+        let (_, chunk_len) = chunk(radix);
+        let mut magnitude: Vec<u32> = Vec::new();
+        let (mut value, mut len) = (0u64, 0);
+        let flush = |magnitude: &mut Vec<u32>, value: u64, len: u32| {
+            let scale = (radix as u64).pow(len);
+            let mut carry = value;
+            for limb in magnitude.iter_mut() {
+                let total = *limb as u64 * scale + carry;
+                *limb = total as u32;
+                carry = total >> 32;
+            }
+            if carry != 0 {
+                magnitude.push(carry as u32);
+            }
+        };
+        for c in digits.chars().filter(|c| *c != '_') {
+            let digit = c.to_digit(radix).ok_or_else(error)?;
+            value = value * radix as u64 + digit as u64;
+            len += 1;
+            if len == chunk_len {
+                flush(&mut magnitude, value, len);
+                (value, len) = (0, 0);
+            }
+        }
+        if len > 0 {
+            flush(&mut magnitude, value, len);
+        }
+        Ok(BigInt::from_parts(negative, magnitude))
+    }
+
+    // Digits in `radix` (2..=36), lowercase, with a leading `-` for negatives.
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        let mut text = self.digits(radix);
+        if self.negative {
+            text.insert(0, '-');
+        }
+        text
+    }
+
+    // The magnitude's digits, most significant first.
+    fn digits(&self, radix: u32) -> String {
+        assert!((2..=36).contains(&radix), "radix must be in 2..=36");
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        let (chunk_base, chunk_len) = chunk(radix);
+        let mut chunks = Vec::new();
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = divmod_small(&magnitude, chunk_base);
+            chunks.push(remainder);
+            magnitude = quotient;
+        }
+
+        let mut text = String::new();
+        for (i, chunk) in chunks.iter().rev().enumerate() {
+            let mut digits = Vec::new();
+            let mut chunk = *chunk;
+            while chunk > 0 {
+                digits.push(std::char::from_digit(chunk % radix, radix).expect("digit < radix"));
+                chunk /= radix;
+            }
+            // Every chunk but the first is zero-padded to its full width.
+            if i > 0 {
+                digits.resize(chunk_len as usize, '0');
+            }
+            text.extend(digits.iter().rev());
+        }
+        text
+    }
+}
+
+// The largest power of `radix` that fits in a u32, and its exponent.
+fn chunk(radix: u32) -> (u32, u32) {
+    let (mut base, mut len) = (radix, 1);
+    while let Some(next) = base.checked_mul(radix) {
+        base = next;
+        len += 1;
+    }
+    (base, len)
+}
+
+// "-42", "0xff", "-0b1010", "1_000_000"
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (sign, rest) = match text.as_bytes().first() {
+            Some(b'-' | b'+') => text.split_at(1),
+            _ => ("", text),
+        };
+        let (radix, digits) = match rest.get(..2) {
+            Some("0x" | "0X") => (16, &rest[2..]),
+            Some("0o" | "0O") => (8, &rest[2..]),
+            Some("0b" | "0B") => (2, &rest[2..]),
+            _ => (10, rest),
+        };
+        if digits.starts_with(['-', '+']) {
+            return Err(ParseBigIntError { input: text.to_string() });
+        }
+        BigInt::from_str_radix(&format!("{}{}", sign, digits), radix)
+            .map_err(|_| ParseBigIntError { input: text.to_string() })
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(!self.negative, "", &self.digits(10))
+    }
+}
+
+impl fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::LowerHex for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(!self.negative, "0x", &self.digits(16))
+    }
+}
+
+impl fmt::UpperHex for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(!self.negative, "0x", &self.digits(16).to_uppercase())
+    }
+}
+
+impl fmt::Octal for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(!self.negative, "0o", &self.digits(8))
+    }
+}
+
+impl fmt::Binary for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(!self.negative, "0b", &self.digits(2))
+    }
+}
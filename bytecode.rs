@@ -0,0 +1,184 @@
+/*
+Bytecode for `Operation` programs — emitter, disassembler and stack VM
+
+The last step of compile.rs's pipeline turns the three-address IR (ir.rs) into
+a flat byte array, and a small stack machine runs it:
+
+IR                              bytecode
+_0 = Multiply(const 2, _1);     PUSH 2   LOAD 1   MULTIPLY   STORE 0
+print(_0);                      LOAD 0   PRINT
+
+Opcode	Bytes	Stack effect
+PUSH n	01 + i32 (little-endian)	→ n
+LOAD s	02 + u32 slot	→ slots[s]
+STORE s	03 + u32 slot	v →     (slots[s] = v)
+PRINT	04	v →     (v is an output of the program)
+ADD …	10 + index in Operation::ALL	x y → x op y
+
+Every IR local gets one slot. Runtime errors (division by zero, overflow, …)
+are mapped back to the source through a span table keyed by code offset, the
+same way debug info maps machine code back to lines.
+*/
+
+// Shared by several programs; each one uses a different subset.
+#![allow(dead_code)]
+
+use std::fmt::Write;
+
+use crate::expression::{operation_error, Error, Span};
+use crate::ir::{Inst, Operand, Program, Rvalue};
+use crate::operation::{Mode, Operation};
+
+const PUSH: u8 = 0x01;
+const LOAD: u8 = 0x02;
+const STORE: u8 = 0x03;
+const PRINT: u8 = 0x04;
+const OPERATION: u8 = 0x10; // 0x10 ..= 0x1c, one per Operation
+
+pub struct Chunk {
+    pub code: Vec<u8>,
+    // Slot names for the disassembly; None for temporaries.
+    pub slots: Vec<Option<String>>,
+    // (offset of an operation opcode, span, right operand span), sorted by offset.
+    spans: Vec<(usize, Span, Span)>,
+}
+
+// ---------- 1. EMITTER ----------
+pub fn emit(program: &Program) -> Chunk {
+    let mut chunk = Chunk {
+        code: Vec::new(),
+        slots: program.locals.iter().map(|decl| decl.name.clone()).collect(),
+        spans: Vec::new(),
+    };
+    for inst in &program.insts {
+        match inst {
+            Inst::Assign { dest, value, span, right_span } => {
+                match value {
+                    Rvalue::Use(operand) => chunk.operand(operand),
+                    Rvalue::Binary(op, x, y) => {
+                        chunk.operand(x);
+                        chunk.operand(y);
+                        chunk.spans.push((chunk.code.len(), *span, *right_span));
+                        chunk.code.push(OPERATION + opcode_index(*op));
+                    }
+                }
+                chunk.code.push(STORE);
+                chunk.code.extend(dest.0.to_le_bytes());
+            }
+            Inst::Print { value, .. } => {
+                chunk.operand(value);
+                chunk.code.push(PRINT);
+            }
+        }
+    }
+    chunk
+}
+
+fn opcode_index(op: Operation) -> u8 {
+    Operation::ALL.iter().position(|candidate| *candidate == op).expect("ALL lists every operation") as u8
+}
+
+impl Chunk {
+    fn operand(&mut self, operand: &Operand) {
+        match operand {
+            Operand::Const(n) => {
+                self.code.push(PUSH);
+                self.code.extend(n.to_le_bytes());
+            }
+            Operand::Local(local) => {
+                self.code.push(LOAD);
+                self.code.extend(local.0.to_le_bytes());
+            }
+        }
+    }
+}
+
+// ---------- 2. DECODING ----------
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Instr {
+    Push(i32),
+    Load(u32),
+    Store(u32),
+    Print,
+    Op(Operation),
+}
+
+// The instruction at `pc` and the offset of the next one.
+fn decode(code: &[u8], pc: usize) -> (Instr, usize) {
+    let word = || <[u8; 4]>::try_from(&code[pc + 1..pc + 5]).expect("truncated bytecode");
+    match code[pc] {
+        PUSH => (Instr::Push(i32::from_le_bytes(word())), pc + 5),
+        LOAD => (Instr::Load(u32::from_le_bytes(word())), pc + 5),
+        STORE => (Instr::Store(u32::from_le_bytes(word())), pc + 5),
+        PRINT => (Instr::Print, pc + 1),
+        byte => match Operation::ALL.get(byte.wrapping_sub(OPERATION) as usize) {
+            Some(op) => (Instr::Op(*op), pc + 1),
+            None => panic!("invalid opcode {:#04x} at {:04x}", byte, pc),
+        },
+    }
+}
+
+impl Chunk {
+    // One line per instruction: offset, raw bytes, mnemonic and the slot's variable name.
+    pub fn disassemble(&self) -> String {
+        let mut text = String::new();
+        let mut pc = 0;
+        while pc < self.code.len() {
+            let (instr, next) = decode(&self.code, pc);
+            let bytes: Vec<String> = self.code[pc..next].iter().map(|byte| format!("{:02x}", byte)).collect();
+            let (mnemonic, slot) = match instr {
+                Instr::Push(n) => (format!("PUSH {}", n), None),
+                Instr::Load(slot) => (format!("LOAD {}", slot), Some(slot)),
+                Instr::Store(slot) => (format!("STORE {}", slot), Some(slot)),
+                Instr::Print => ("PRINT".to_string(), None),
+                Instr::Op(op) => (format!("{:?}", op).to_uppercase(), None),
+            };
+            let name = slot.and_then(|slot| self.slots[slot as usize].as_deref());
+            let line = match name {
+                Some(name) => format!("{:04x}  {:<16}{:<12}; {}", pc, bytes.join(" "), mnemonic, name),
+                None => format!("{:04x}  {:<16}{}", pc, bytes.join(" "), mnemonic),
+            };
+            writeln!(text, "{}", line.trim_end()).expect("writing to a String never fails");
+            pc = next;
+        }
+        text
+    }
+
+    // ---------- 3. VM ----------
+    // Runs the program and returns the printed values.
+    pub fn run(&self, mode: Mode) -> Result<Vec<i32>, Error> {
+        let mut stack: Vec<i32> = Vec::new();
+        let mut slots = vec![0; self.slots.len()];
+        let mut output = Vec::new();
+        let mut pc = 0;
+        while pc < self.code.len() {
+            let (instr, next) = decode(&self.code, pc);
+            match instr {
+                Instr::Push(n) => stack.push(n),
+                Instr::Load(slot) => stack.push(slots[slot as usize]),
+                Instr::Store(slot) => slots[slot as usize] = pop(&mut stack),
+                Instr::Print => output.push(pop(&mut stack)),
+                Instr::Op(op) => {
+                    let y = pop(&mut stack);
+                    let x = pop(&mut stack);
+                    let value = op.run_with(x, y, mode).map_err(|err| {
+                        let (span, right_span) = self.spans_at(pc);
+                        operation_error(err, span, right_span)
+                    })?;
+                    stack.push(value);
+                }
+            }
+            pc = next;
+        }
+        Ok(output)
+    }
+
+    fn spans_at(&self, pc: usize) -> (Span, Span) {
+        let i = self.spans.binary_search_by_key(&pc, |(offset, _, _)| *offset).expect("every operation has a span");
+        (self.spans[i].1, self.spans[i].2)
+    }
+}
+
+fn pop(stack: &mut Vec<i32>) -> i32 {
+    stack.pop().expect("stack underflow: the emitter never produces this")
+}
@@ -0,0 +1,615 @@
+/*
+Calculator REPL with `let` bindings
+
+Expressions are the ones from evaluate.rs (expression.rs + operation.rs). On
+top of them the REPL keeps variables, with exactly the rules that
+`variable_bindings` (1.rs) and Variable_Binging.rs demonstrate:
+
+calc> let x = 5;
+calc> x = 6;
+error: cannot assign twice to immutable variable `x`
+  |  x = 6;
+  |  ^^^^^
+calc> let mut y = 10; y = 20; y
+20
+calc> let z = 30; let z = z + 1; z          // shadowing: a new `z`, not a mutation
+31
+calc> let a;
+calc> a + 1
+error: used binding `a` isn't initialized
+  |  a + 1
+  |  ^
+calc> a = 50;                                // declare first, assign once later
+calc> {
+calc{> let a = 100; a                       // inner scope shadows the outer `a`
+100
+calc{> }
+calc> a
+50
+
+Rule	Error when broken
+let x = …	immutable: a second assignment is "cannot assign twice"
+let mut x = …	x = … any number of times
+let x = x * 2	shadowing creates a new binding; the old value is untouched
+{ … }	bindings made inside are gone after `}`; assignments to outer `mut` bindings stay
+let x;	must be assigned before use: "used binding isn't initialized"
+
+📌 Line editing (when stdin is a terminal): ← → Home End, Backspace / Delete,
+↑ ↓ for history, Ctrl-A / Ctrl-E, Ctrl-K / Ctrl-U / Ctrl-W to cut, Ctrl-L to
+clear, Ctrl-C to drop the line, Ctrl-D on an empty line to quit. The terminal is
+put in raw mode with `stty` while a line is being edited, and history is saved
+to ~/.calculator_history.
+
+📌 Commands: :vars  :mode [checked|wrapping|saturating]  :history  :help  :quit
+
+$ rustc -O calculator.rs
+$ ./calculator
+$ printf 'let x = 2;\nx * 21\n' | ./calculator      // not a terminal: plain line input
+42
+$ rustc --test calculator.rs && ./calculator      // the tests
+*/
+
+mod expression;
+mod operation;
+
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+use expression::{parse_statements, Error, Span, Stmt};
+use operation::Mode;
+
+// ---------- BINDINGS ----------
+#[derive(Debug, Clone, PartialEq)]
+struct Binding {
+    name: String,
+    mutable: bool,
+    value: Option<i32>, // None: declared with `let x;` and not assigned yet
+}
+
+// scopes[0] is the top level; each `{` pushes a scope and `}` pops it.
+// Within a scope, later bindings shadow earlier ones with the same name.
+struct Env {
+    scopes: Vec<Vec<Binding>>,
+    mode: Mode,
+}
+
+impl Env {
+    fn new() -> Self {
+        Env { scopes: vec![Vec::new()], mode: Mode::Checked }
+    }
+
+    // Number of open `{` blocks.
+    fn depth(&self) -> usize {
+        self.scopes.len() - 1
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().flat_map(|scope| scope.iter().rev()).find(|binding| binding.name == name)
+    }
+
+    fn lookup_mut(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes.iter_mut().rev().flat_map(|scope| scope.iter_mut().rev()).find(|binding| binding.name == name)
+    }
+
+    fn get(&self, name: &str, span: Span) -> Result<i32, Error> {
+        match self.lookup(name) {
+            Some(Binding { value: Some(value), .. }) => Ok(*value),
+            Some(_) => Err(Error::new(format!("used binding `{}` isn't initialized", name), span)),
+            None => Err(Error::new(format!("cannot find value `{}` in this scope", name), span)),
+        }
+    }
+
+    // The bindings a name lookup can currently reach, oldest first.
+    fn visible(&self) -> Vec<&Binding> {
+        let mut visible: Vec<&Binding> = Vec::new();
+        for binding in self.scopes.iter().rev().flat_map(|scope| scope.iter().rev()) {
+            if visible.iter().all(|seen| seen.name != binding.name) {
+                visible.push(binding);
+            }
+        }
+        visible.reverse();
+        visible
+    }
+
+    // Runs one statement; expression statements produce a value to print.
+    fn execute(&mut self, stmt: &Stmt) -> Result<Option<i32>, Error> {
+        let mode = self.mode;
+        match stmt {
+            Stmt::Let { name, mutable, value, .. } => {
+                // Evaluated before the new binding exists, so `let x = x * 2` reads the old `x`.
+                let value = match value {
+                    Some(expr) => Some(expr.eval_in(mode, &|name, span| self.get(name, span))?),
+                    None => None,
+                };
+                let scope = self.scopes.last_mut().expect("the top-level scope is never popped");
+                scope.push(Binding { name: name.clone(), mutable: *mutable, value });
+                Ok(None)
+            }
+            Stmt::Assign { name, value, span } => {
+                let value = value.eval_in(mode, &|name, span| self.get(name, span))?;
+                let binding = self
+                    .lookup_mut(name)
+                    .ok_or_else(|| Error::new(format!("cannot find value `{}` in this scope", name), *span))?;
+                if !binding.mutable && binding.value.is_some() {
+                    return Err(Error::new(format!("cannot assign twice to immutable variable `{}`", name), *span));
+                }
+                binding.value = Some(value);
+                Ok(None)
+            }
+            Stmt::Expr(expr) => Ok(Some(expr.eval_in(mode, &|name, span| self.get(name, span))?)),
+            Stmt::BlockStart(_) => {
+                self.scopes.push(Vec::new());
+                Ok(None)
+            }
+            Stmt::BlockEnd(span) => {
+                if self.depth() == 0 {
+                    return Err(Error::new("unexpected closing delimiter `}`", *span));
+                }
+                self.scopes.pop();
+                Ok(None)
+            }
+        }
+    }
+
+    // Runs every statement on the line, stopping at the first error.
+    // Statements before the error keep their effect, like lines typed earlier.
+    fn run_line(&mut self, line: &str) -> Result<Vec<i32>, Error> {
+        let mut values = Vec::new();
+        for stmt in parse_statements(line)? {
+            values.extend(self.execute(&stmt)?);
+        }
+        Ok(values)
+    }
+}
+
+// ---------- LINE EDITOR ----------
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    KillToEnd,   // Ctrl-K
+    KillToStart, // Ctrl-U
+    KillWord,    // Ctrl-W
+    ClearScreen, // Ctrl-L
+    Interrupt,   // Ctrl-C
+    EndOfFile,   // Ctrl-D
+    Ignored,
+}
+
+fn read_byte(input: &mut impl Read) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    match input.read(&mut byte)? {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    }
+}
+
+// Decodes one key press from raw terminal input. None at end of input.
+fn read_key(input: &mut impl Read) -> io::Result<Option<Key>> {
+    let Some(byte) = read_byte(input)? else {
+        return Ok(None);
+    };
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        0x7f | 0x08 => Key::Backspace,
+        0x01 => Key::Home,
+        0x05 => Key::End,
+        0x02 => Key::Left,
+        0x06 => Key::Right,
+        0x10 => Key::Up,
+        0x0e => Key::Down,
+        0x0b => Key::KillToEnd,
+        0x15 => Key::KillToStart,
+        0x17 => Key::KillWord,
+        0x0c => Key::ClearScreen,
+        0x03 => Key::Interrupt,
+        0x04 => Key::EndOfFile,
+        0x1b => read_escape(input)?,
+        byte if byte < 0x20 => Key::Ignored,
+        byte => {
+            // UTF-8: the leading byte says how many continuation bytes follow.
+            let len = match byte {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            };
+            let mut bytes = vec![byte];
+            for _ in 1..len {
+                bytes.extend(read_byte(input)?);
+            }
+            match std::str::from_utf8(&bytes).ok().and_then(|text| text.chars().next()) {
+                Some(c) => Key::Char(c),
+                None => Key::Ignored,
+            }
+        }
+    };
+    Ok(Some(key))
+}
+
+// ESC [ A, ESC O H, ESC [ 3 ~, ...
+fn read_escape(input: &mut impl Read) -> io::Result<Key> {
+    if !matches!(read_byte(input)?, Some(b'[' | b'O')) {
+        return Ok(Key::Ignored);
+    }
+    let mut number = 0;
+    loop {
+        let key = match read_byte(input)? {
+            Some(digit @ b'0'..=b'9') => {
+                number = number * 10 + u32::from(digit - b'0');
+                continue;
+            }
+            Some(b';') => continue,
+            Some(b'A') => Key::Up,
+            Some(b'B') => Key::Down,
+            Some(b'C') => Key::Right,
+            Some(b'D') => Key::Left,
+            Some(b'H') => Key::Home,
+            Some(b'F') => Key::End,
+            Some(b'~') => match number {
+                1 | 7 => Key::Home,
+                4 | 8 => Key::End,
+                3 => Key::Delete,
+                _ => Key::Ignored,
+            },
+            _ => Key::Ignored,
+        };
+        return Ok(key);
+    }
+}
+
+struct LineEditor {
+    history: Vec<String>,
+}
+
+impl LineEditor {
+    const HISTORY_LIMIT: usize = 1000;
+
+    fn new() -> Self {
+        LineEditor { history: Vec::new() }
+    }
+
+    // Edits one line read key by key from `input`, echoing to `output`.
+    // Returns None at end of input (or Ctrl-D on an empty line).
+    fn edit(&mut self, prompt: &str, input: &mut impl Read, output: &mut impl Write) -> io::Result<Option<String>> {
+        let mut line: Vec<char> = Vec::new();
+        let mut cursor = 0;
+        // history.len() means "the line being typed"; `draft` keeps it while browsing.
+        let mut history_pos = self.history.len();
+        let mut draft: Vec<char> = Vec::new();
+
+        write!(output, "{}", prompt)?;
+        output.flush()?;
+        loop {
+            let Some(key) = read_key(input)? else {
+                return Ok(None);
+            };
+            match key {
+                Key::Char(c) => {
+                    line.insert(cursor, c);
+                    cursor += 1;
+                }
+                Key::Enter => {
+                    writeln!(output)?;
+                    let line: String = line.into_iter().collect();
+                    self.add_history(&line);
+                    return Ok(Some(line));
+                }
+                Key::Backspace if cursor > 0 => {
+                    cursor -= 1;
+                    line.remove(cursor);
+                }
+                Key::Delete if cursor < line.len() => {
+                    line.remove(cursor);
+                }
+                Key::EndOfFile if line.is_empty() => {
+                    writeln!(output)?;
+                    return Ok(None);
+                }
+                Key::EndOfFile if cursor < line.len() => {
+                    line.remove(cursor);
+                }
+                Key::Left => cursor = cursor.saturating_sub(1),
+                Key::Right => cursor = (cursor + 1).min(line.len()),
+                Key::Home => cursor = 0,
+                Key::End => cursor = line.len(),
+                Key::Up | Key::Down => {
+                    let target = match key {
+                        Key::Up => history_pos.checked_sub(1),
+                        _ => Some(history_pos + 1).filter(|pos| *pos <= self.history.len()),
+                    };
+                    if let Some(target) = target {
+                        if history_pos == self.history.len() {
+                            draft = line.clone();
+                        }
+                        history_pos = target;
+                        line = match self.history.get(target) {
+                            Some(entry) => entry.chars().collect(),
+                            None => draft.clone(),
+                        };
+                        cursor = line.len();
+                    }
+                }
+                Key::KillToEnd => line.truncate(cursor),
+                Key::KillToStart => {
+                    line.drain(..cursor);
+                    cursor = 0;
+                }
+                Key::KillWord => {
+                    let mut start = cursor;
+                    while start > 0 && line[start - 1] == ' ' {
+                        start -= 1;
+                    }
+                    while start > 0 && line[start - 1] != ' ' {
+                        start -= 1;
+                    }
+                    line.drain(start..cursor);
+                    cursor = start;
+                }
+                Key::ClearScreen => write!(output, "\x1b[2J\x1b[H")?,
+                Key::Interrupt => {
+                    writeln!(output, "^C")?;
+                    return Ok(Some(String::new()));
+                }
+                _ => {}
+            }
+
+            // Redraw: back to column 0, prompt + line, clear the rest, then put the cursor back.
+            let text: String = line.iter().collect();
+            write!(output, "\r{}{}\x1b[K", prompt, text)?;
+            if cursor < line.len() {
+                write!(output, "\x1b[{}D", line.len() - cursor)?;
+            }
+            output.flush()?;
+        }
+    }
+
+    fn add_history(&mut self, line: &str) {
+        if line.trim().is_empty() || self.history.last().map(String::as_str) == Some(line) {
+            return;
+        }
+        self.history.push(line.to_string());
+        if self.history.len() > Self::HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+    }
+
+    fn load_history(&mut self, path: &str) {
+        if let Ok(text) = fs::read_to_string(path) {
+            for line in text.lines() {
+                self.add_history(line);
+            }
+        }
+    }
+
+    fn save_history(&self, path: &str) -> io::Result<()> {
+        let mut text = self.history.join("\n");
+        text.push('\n');
+        fs::write(path, text)
+    }
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Character-at-a-time input without echo while it lives; the old settings come back on drop.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> Option<RawMode> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "-isig", "min", "1"])?;
+        Some(RawMode { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        stty(&[&self.saved]);
+    }
+}
+
+// ---------- REPL ----------
+const HELP: &str = "\
+let x = expr;      immutable binding (shadows any earlier `x`)
+let mut x = expr;  mutable binding
+let x;             declare now, assign once later
+x = expr;          assign (needs `mut`, or a declared-but-unassigned `x`)
+{  }               open / close a block scope
+expr               print the value; operators: + - * / % & | ^ << >> pow min max
+:vars  :mode [checked|wrapping|saturating]  :history  :help  :quit";
+
+fn prompt(env: &Env) -> String {
+    format!("calc{}> ", "{".repeat(env.depth()))
+}
+
+// Handles a `:command`. Returns false for :quit.
+fn command(env: &mut Env, editor: &LineEditor, line: &str) -> bool {
+    let mut words = line.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some(":quit" | ":q"), _) => return false,
+        (Some(":help"), _) => println!("{}", HELP),
+        (Some(":history"), _) => {
+            for (i, entry) in editor.history.iter().enumerate() {
+                println!("{:4}  {}", i + 1, entry);
+            }
+        }
+        (Some(":vars"), _) => {
+            for binding in env.visible() {
+                let keyword = if binding.mutable { "let mut" } else { "let" };
+                match binding.value {
+                    Some(value) => println!("{} {} = {};", keyword, binding.name, value),
+                    None => println!("{} {}; // not assigned yet", keyword, binding.name),
+                }
+            }
+        }
+        (Some(":mode"), None) => println!("{:?}", env.mode),
+        (Some(":mode"), Some(mode)) => match mode {
+            "checked" => env.mode = Mode::Checked,
+            "wrapping" => env.mode = Mode::Wrapping,
+            "saturating" => env.mode = Mode::Saturating,
+            _ => println!("unknown mode `{}` (checked, wrapping, saturating)", mode),
+        },
+        _ => println!("unknown command `{}`, try :help", line),
+    }
+    true
+}
+
+// Returns false when the user asked to quit.
+fn handle_line(env: &mut Env, editor: &LineEditor, line: &str) -> bool {
+    let line = line.trim_end();
+    if line.trim_start().starts_with(':') {
+        return command(env, editor, line.trim_start());
+    }
+    match env.run_line(line) {
+        Ok(values) => {
+            for value in values {
+                println!("{}", value);
+            }
+        }
+        Err(err) => println!("{}", err.render(line)),
+    }
+    true
+}
+
+fn main() {
+    let mut env = Env::new();
+    let mut editor = LineEditor::new();
+    let stdin = io::stdin();
+
+    if !stdin.is_terminal() {
+        for line in stdin.lock().lines() {
+            let line = line.expect("failed to read stdin");
+            if !handle_line(&mut env, &editor, &line) {
+                break;
+            }
+        }
+        return;
+    }
+
+    let history_path = std::env::var("HOME").map(|home| format!("{}/.calculator_history", home)).ok();
+    if let Some(path) = &history_path {
+        editor.load_history(path);
+    }
+    println!("calculator — :help for help, Ctrl-D to quit");
+    loop {
+        let prompt = prompt(&env);
+        let line = {
+            let _raw = RawMode::enable();
+            editor.edit(&prompt, &mut stdin.lock(), &mut io::stdout())
+        };
+        match line {
+            Ok(Some(line)) => {
+                if !handle_line(&mut env, &editor, &line) {
+                    break;
+                }
+            }
+            Ok(None) => break,
+            Err(err) => {
+                eprintln!("input error: {}", err);
+                break;
+            }
+        }
+    }
+    if let Some(path) = &history_path {
+        if let Err(err) = editor.save_history(path) {
+            eprintln!("could not save history to {}: {}", path, err);
+        }
+    }
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ---------- 1. BINDINGS ----------
+    // The same steps as `variable_bindings` in 1.rs.
+    #[test]
+    fn bindings_check() {
+        let mut env = Env::new();
+        let run = |env: &mut Env, line: &str| env.run_line(line).unwrap_or_else(|err| panic!("{}", err.render(line)));
+
+        assert_eq!(run(&mut env, "let x = 5; x"), [5]);
+        assert_eq!(run(&mut env, "let mut y = 10; y = 20; y"), [20]);
+        assert_eq!(run(&mut env, "let z = 30; let z = z + 1; z"), [31]);
+        assert_eq!(run(&mut env, "let a; a = 50;"), []);
+        assert_eq!(run(&mut env, "{ let a = 100; a }"), [100]);
+        assert_eq!(run(&mut env, "a"), [50]);
+
+        // Blocks may span lines; assignments to an outer `mut` survive the block, shadows don't.
+        run(&mut env, "{");
+        assert_eq!(env.depth(), 1);
+        run(&mut env, "y = y + 1; let x = 0;");
+        run(&mut env, "}");
+        assert_eq!(run(&mut env, "x; y"), [5, 21]);
+
+        let visible: Vec<(&str, Option<i32>)> =
+            env.visible().iter().map(|binding| (binding.name.as_str(), binding.value)).collect();
+        assert_eq!(visible, [("x", Some(5)), ("y", Some(21)), ("z", Some(31)), ("a", Some(50))]);
+
+        env.mode = Mode::Wrapping;
+        assert_eq!(run(&mut env, "let big = 2147483647; big + 1"), [i32::MIN]);
+    }
+
+    #[test]
+    fn binding_errors_check() {
+        let mut env = Env::new();
+        env.run_line("let x = 5;").unwrap();
+        let mut error = |line: &str| env.run_line(line).unwrap_err();
+
+        assert_eq!(error("x = 6").message, "cannot assign twice to immutable variable `x`");
+        assert_eq!(error("let a; a + 1").message, "used binding `a` isn't initialized");
+        assert_eq!(error("a = 1; a = 2").message, "cannot assign twice to immutable variable `a`");
+        assert_eq!(error("nope + 1").span, Span::new(0, 4));
+        assert_eq!(error("nope = 1").message, "cannot find value `nope` in this scope");
+        assert_eq!(error("{ let inner = 1; } inner").message, "cannot find value `inner` in this scope");
+        assert_eq!(error("}").message, "unexpected closing delimiter `}`");
+        assert_eq!(error("let = 1").message, "expected a variable name, found `=`");
+        assert_eq!(error("let pow = 1").message, "expected a variable name, found `pow`");
+        assert_eq!(error("x + 1 = 2").message, "invalid left-hand side of assignment");
+        assert_eq!(error("let b = 1 let c = 2").message, "expected `;`, found `let`");
+        assert_eq!(error("10 / (x - 5)").span, Span::new(5, 12));
+    }
+
+    // ---------- 2. LINE EDITOR ----------
+    #[test]
+    fn line_editor_check() {
+        let mut editor = LineEditor::new();
+        let mut type_keys = |keys: &str| editor.edit("> ", &mut keys.as_bytes(), &mut io::sink()).unwrap();
+
+        assert_eq!(type_keys("1 + 2\r"), Some("1 + 2".to_string()));
+        assert_eq!(type_keys("13\x1b[D2\r"), Some("123".to_string())); // ← then insert
+        assert_eq!(type_keys("abc\x01\x1b[3~\x05\x7fX\n"), Some("bX".to_string())); // Home, Delete, End, Backspace
+        assert_eq!(type_keys("let x = 1\x17y\r"), Some("let x = y".to_string())); // Ctrl-W
+        assert_eq!(type_keys("abcdef\x1b[D\x1b[D\x0b\r"), Some("abcd".to_string())); // Ctrl-K
+        assert_eq!(type_keys("abc\x1b[D\x15\r"), Some("c".to_string())); // Ctrl-U
+        assert_eq!(type_keys("é+ü\x7f\r"), Some("é+".to_string()));
+        assert_eq!(type_keys("typo\x03"), Some(String::new())); // Ctrl-C drops the line
+        assert_eq!(type_keys("\x04"), None);
+        assert_eq!(type_keys("partial"), None); // end of input
+
+        // ↑ walks back through history, ↓ returns to the line being typed.
+        assert_eq!(type_keys("\x1b[A\r"), Some("é+".to_string()));
+        assert_eq!(type_keys("\x1b[A\x1b[A\x1b[A\r"), Some("abcd".to_string()));
+        assert_eq!(type_keys("draft\x1b[A\x1b[B!\r"), Some("draft!".to_string()));
+        assert_eq!(type_keys("\x10\x10\x10\x0e\r"), Some("abcd".to_string())); // Ctrl-P / Ctrl-N
+
+        // Consecutive duplicates and blank lines are not stored twice.
+        let history = editor.history.clone();
+        assert_eq!(history, ["1 + 2", "123", "bX", "let x = y", "abcd", "c", "é+", "abcd", "draft!", "abcd"]);
+    }
+}
@@ -0,0 +1,361 @@
+/*
+Compiler pipeline for calculator programs: source → tokens → AST → IR → bytecode → VM
+
+architecture.rs describes what rustc does with a .rs file:
+
+  │ Lexing & Parsing     │ → AST
+  │ Name & Type Checking │
+  │ HIR → MIR            │
+  → Code Generation
+
+This program does the same, at toy scale, for the language of calculator.rs
+(expressions over `Operation`, `let` / `let mut`, shadowing, `{ }` scopes):
+
+Stage	Module	--emit=
+Lexer	expression.rs (tokenize)	tokens
+Parser	expression.rs (parse_statements)	ast
+Name resolution + lowering	ir.rs	ir
+Optimization (-O, --passes=)	optimize.rs	ir
+Code generation	bytecode.rs (emit)	bytecode
+Execution	bytecode.rs (Chunk::run)	(default)
+
+$ rustc -O compile.rs
+$ ./compile -e 'let x = 3 + 2 * 4; x - 1'
+10
+$ ./compile --emit=ir -e 'let x = 3 + 2 * 4; x - 1'
+debug x => _2;
+_0 = Multiply(const 2, const 4);
+_1 = Add(const 3, _0);
+_2 = _1;
+_3 = Subtract(_2, const 1);
+print(_3);
+$ ./compile -O --emit=ir -e 'let x = 3 + 2 * 4; x - 1'
+print(const 10);
+$ ./compile --passes=simplify,dce --emit=ir -e '…'   // just some passes: fold, simplify, cse, dce
+$ ./compile --emit=bytecode program.calc      // or a file; stdin when neither is given
+$ ./compile --mode=wrapping -e '2147483647 + 1'
+-2147483648
+
+✅ Errors found while lowering (unknown name, use before assignment, assigning
+   twice to an immutable binding) stop the build, like rustc's analysis phase.
+⚠️ Runtime errors (division by zero, overflow) come from the VM and still point
+   at the source, through the span table the emitter writes.
+
+The tests check the stages against each other (and the VM against expression.rs):
+
+$ rustc --test compile.rs && ./compile
+*/
+
+mod bytecode;
+mod expression;
+mod ir;
+mod operation;
+mod optimize;
+#[cfg(test)]
+mod testing;
+
+use std::fmt::Write;
+use std::io::Read;
+
+use expression::{parse_statements, tokenize, Error, Expr, ExprKind, Stmt};
+use operation::Mode;
+use optimize::Pass;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Emit {
+    Tokens,
+    Ast,
+    Ir,
+    Bytecode,
+    Run,
+}
+
+// Runs the pipeline up to the stage `emit` asks for and returns what to print.
+fn compile(source: &str, emit: Emit, mode: Mode, passes: &[Pass]) -> Result<String, Error> {
+    let mut out = String::new();
+    if emit == Emit::Tokens {
+        for token in tokenize(source)? {
+            writeln!(out, "{:>4}..{:<4} {:?}", token.span.start, token.span.end, token.kind).unwrap();
+        }
+        return Ok(out);
+    }
+
+    let statements = parse_statements(source)?;
+    if emit == Emit::Ast {
+        for stmt in &statements {
+            write_stmt(&mut out, stmt);
+        }
+        return Ok(out);
+    }
+
+    let mut program = ir::lower(&statements)?;
+    optimize::optimize(&mut program, passes);
+    if emit == Emit::Ir {
+        return Ok(program.to_string());
+    }
+
+    let chunk = bytecode::emit(&program);
+    if emit == Emit::Bytecode {
+        return Ok(chunk.disassemble());
+    }
+
+    for value in chunk.run(mode)? {
+        writeln!(out, "{}", value).unwrap();
+    }
+    Ok(out)
+}
+
+// ---------- AST DUMP ----------
+fn write_stmt(out: &mut String, stmt: &Stmt) {
+    match stmt {
+        Stmt::Let { name, mutable, value, .. } => {
+            let keyword = if *mutable { "let mut" } else { "let" };
+            match value {
+                Some(value) => {
+                    writeln!(out, "{} {} =", keyword, name).unwrap();
+                    write_expr(out, value, 1);
+                }
+                None => writeln!(out, "{} {}", keyword, name).unwrap(),
+            }
+        }
+        Stmt::Assign { name, value, .. } => {
+            writeln!(out, "{} =", name).unwrap();
+            write_expr(out, value, 1);
+        }
+        Stmt::Expr(expr) => {
+            writeln!(out, "print").unwrap();
+            write_expr(out, expr, 1);
+        }
+        Stmt::BlockStart(_) => writeln!(out, "{{").unwrap(),
+        Stmt::BlockEnd(_) => writeln!(out, "}}").unwrap(),
+    }
+}
+
+// One node per line, children indented, each with its byte span.
+fn write_expr(out: &mut String, expr: &Expr, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let span = format!("{}..{}", expr.span.start, expr.span.end);
+    match &expr.kind {
+        ExprKind::Number(n) => writeln!(out, "{}{}  {}", indent, n, span).unwrap(),
+        ExprKind::Variable(name) => writeln!(out, "{}{}  {}", indent, name, span).unwrap(),
+        ExprKind::Negate(operand) => {
+            writeln!(out, "{}Negate  {}", indent, span).unwrap();
+            write_expr(out, operand, depth + 1);
+        }
+        ExprKind::Binary(op, left, right) => {
+            writeln!(out, "{}{:?}  {}", indent, op, span).unwrap();
+            write_expr(out, left, depth + 1);
+            write_expr(out, right, depth + 1);
+        }
+    }
+}
+
+// ---------- COMMAND LINE ----------
+const USAGE: &str = "usage: compile [--emit=tokens|ast|ir|bytecode] [--mode=checked|wrapping|saturating] \
+                     [-O | --passes=fold,simplify,cse,dce] (-e SOURCE | FILE)";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+fn main() {
+    let mut emit = Emit::Run;
+    let mut mode = Mode::Checked;
+    let mut passes = Vec::new();
+    let mut source = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--emit=tokens" => emit = Emit::Tokens,
+            "--emit=ast" => emit = Emit::Ast,
+            "--emit=ir" => emit = Emit::Ir,
+            "--emit=bytecode" => emit = Emit::Bytecode,
+            "--mode=checked" => mode = Mode::Checked,
+            "--mode=wrapping" => mode = Mode::Wrapping,
+            "--mode=saturating" => mode = Mode::Saturating,
+            "-O" => passes = Pass::ALL.to_vec(),
+            "-e" => source = args.next(),
+            list if list.starts_with("--passes=") => {
+                passes = list["--passes=".len()..].split(',').map(|name| Pass::from_name(name).unwrap_or_else(|| usage())).collect();
+            }
+            path if !path.starts_with('-') => {
+                let text = std::fs::read_to_string(path).unwrap_or_else(|err| {
+                    eprintln!("cannot read {}: {}", path, err);
+                    std::process::exit(2);
+                });
+                source = Some(text);
+            }
+            _ => usage(),
+        }
+    }
+    let source = source.unwrap_or_else(|| {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).expect("failed to read stdin");
+        text
+    });
+
+    match compile(&source, emit, mode, &passes) {
+        Ok(out) => print!("{}", out),
+        Err(err) => {
+            eprintln!("{}", err.render(source.trim_end()));
+            std::process::exit(1);
+        }
+    }
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expression::parse;
+    use testing::{random_expression, SEED};
+
+    // ---------- 1. SNAPSHOTS ----------
+    #[test]
+    fn snapshot_check() {
+        let source = "let x = 3 + 2 * 4; x - 1";
+
+        let ast = compile(source, Emit::Ast, Mode::Checked, &[]).unwrap();
+        assert_eq!(
+            ast,
+            "let x =\n  Add  8..17\n    3  8..9\n    Multiply  12..17\n      2  12..13\n      4  16..17\n\
+             print\n  Subtract  19..24\n    x  19..20\n    1  23..24\n"
+        );
+
+        let ir = compile(source, Emit::Ir, Mode::Checked, &[]).unwrap();
+        assert_eq!(
+            ir,
+            "debug x => _2;\n\
+             _0 = Multiply(const 2, const 4);\n\
+             _1 = Add(const 3, _0);\n\
+             _2 = _1;\n\
+             _3 = Subtract(_2, const 1);\n\
+             print(_3);\n"
+        );
+
+        let bytecode = compile(source, Emit::Bytecode, Mode::Checked, &[]).unwrap();
+        let mnemonics: Vec<&str> = bytecode.lines().map(|line| line[22..].trim()).collect();
+        assert_eq!(
+            mnemonics,
+            [
+                "PUSH 2", "PUSH 4", "MULTIPLY", "STORE 0", "PUSH 3", "LOAD 0", "ADD", "STORE 1", "LOAD 1", "STORE 2     ; x",
+                "LOAD 2      ; x", "PUSH 1", "SUBTRACT", "STORE 3", "LOAD 3", "PRINT",
+            ]
+        );
+        assert!(bytecode.starts_with("0000  01 02 00 00 00  PUSH 2\n"));
+
+        assert_eq!(compile(source, Emit::Run, Mode::Checked, &[]).unwrap(), "10\n");
+        assert_eq!(compile(source, Emit::Tokens, Mode::Checked, &[]).unwrap().lines().count(), 13);
+    }
+
+    // ---------- 2. BINDINGS ----------
+    fn run(source: &str) -> Vec<i32> {
+        let statements = parse_statements(source).unwrap();
+        bytecode::emit(&ir::lower(&statements).unwrap()).run(Mode::Checked).unwrap()
+    }
+
+    // `variable_bindings` from 1.rs, compiled.
+    #[test]
+    fn bindings_check() {
+        let program = "
+            let x = 5;
+            let mut y = 10;
+            y = 20;
+            let z = 30;
+            let z = z + 1;
+            let a;
+            a = 50;
+            {
+                let a = 100;
+                a;
+                y = y + a;
+            }
+            a; x; y; z
+        ";
+        assert_eq!(run(program), [100, 50, 5, 120, 31]);
+    }
+
+    #[test]
+    fn static_errors_check() {
+        let error = |source: &str| {
+            let statements = parse_statements(source).unwrap();
+            ir::lower(&statements).unwrap_err()
+        };
+
+        assert_eq!(error("let a; a + 1").message, "used binding `a` isn't initialized");
+        assert_eq!(error("let x = 1; x = 2").message, "cannot assign twice to immutable variable `x`");
+        assert_eq!(error("y").message, "cannot find value `y` in this scope");
+        assert_eq!(error("{ let y = 1; } y").message, "cannot find value `y` in this scope");
+        assert_eq!(error("}").message, "unexpected closing delimiter `}`");
+        assert_eq!(error("1; {\n2").span.start, 3);
+
+        let rendered = error("let a = 1;\nlet b;\na + b").render("let a = 1;\nlet b;\na + b");
+        assert_eq!(rendered, "error: used binding `b` isn't initialized\n  --> 3:5\n  |  a + b\n  |      ^");
+    }
+
+    // ---------- 3. DIFFERENTIAL CHECK ----------
+    // The VM must agree with the tree-walking evaluator (expression.rs) on every
+    // value and on every error, including the span it points at.
+    #[test]
+    fn differential_check() {
+        let mut state = SEED;
+        for _ in 0..3000 {
+            let source = random_expression(&mut state, 4, &[]);
+            let expr = parse(&source).unwrap();
+            let chunk = bytecode::emit(&ir::lower(&parse_statements(&source).unwrap()).unwrap());
+            for mode in [Mode::Checked, Mode::Wrapping, Mode::Saturating] {
+                let expected = expr.eval_with(mode).map(|value| vec![value]);
+                assert_eq!(chunk.run(mode), expected, "{} in {:?} mode", source, mode);
+            }
+        }
+    }
+
+    // ---------- 4. OPTIMIZER ----------
+    // The IR each pass produces is checked in optimize.rs; here, that an
+    // optimized program still stops with the error the Mode asks for.
+    #[test]
+    fn optimized_errors_check() {
+        let unknown = "let mut n = 2147483647; n = n * 3 - n * 3; n";
+        assert_eq!(compile(unknown, Emit::Run, Mode::Checked, &Pass::ALL).unwrap_err().message, "arithmetic overflow");
+        assert_eq!(compile(unknown, Emit::Run, Mode::Wrapping, &Pass::ALL).unwrap(), "0\n");
+    }
+
+    // A random program using bindings, shadowing, `mut` and a block, so that every pass has work to do.
+    fn random_program(state: &mut u64) -> String {
+        let mut expression = |names: &[&str]| random_expression(state, 3, names);
+        let all = ["a", "b", "c"];
+        format!(
+            "let a = {}; let mut b = {}; let c; b = {}; c = {}; {{ let a = {}; b = {}; {} }} {}; {}",
+            expression(&[]),
+            expression(&["a"]),
+            expression(&["a", "b"]),
+            expression(&["a", "b"]),
+            expression(&all),
+            expression(&all),
+            expression(&all),
+            expression(&all),
+            expression(&all),
+        )
+    }
+
+    // Every combination of passes must print the same values, or stop with the same error, in every Mode.
+    #[test]
+    fn optimizer_differential_check() {
+        let mut state = SEED;
+        for _ in 0..400 {
+            let source = random_program(&mut state);
+            let statements = parse_statements(&source).unwrap();
+            let unoptimized = bytecode::emit(&ir::lower(&statements).unwrap());
+            for subset in 1..1 << Pass::ALL.len() {
+                let passes: Vec<Pass> = (0..Pass::ALL.len()).filter(|i| subset & (1 << i) != 0).map(|i| Pass::ALL[i]).collect();
+                let mut program = ir::lower(&statements).unwrap();
+                optimize::optimize(&mut program, &passes);
+                let optimized = bytecode::emit(&program);
+                for mode in [Mode::Checked, Mode::Wrapping, Mode::Saturating] {
+                    assert_eq!(optimized.run(mode), unoptimized.run(mode), "{} with {:?} in {:?} mode", source, passes, mode);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,375 @@
+/*
+Conformance matrix: every Operation × every primitive number type × every Mode
+
+operation.rs makes `Operation::run` generic over the `Number` trait, which is
+implemented for all the types Primitives.rs lists:
+
+Signed	i8 i16 i32 i64 i128 isize
+Unsigned	u8 u16 u32 u64 u128 usize
+Float	f32 f64
+
+For each type, each of the 13 operations runs on every pair of edge-case
+samples (MIN, MAX, 0, ±1, BITS, ±inf, NaN, …) in all three modes, and checks:
+
+✅ the modes agree: whatever Checked returns apart from Overflow, Wrapping and
+   Saturating return too; on Overflow, Wrapping and Saturating give a value,
+   for every operation, shifts by BITS or more included
+✅ Saturating overflows land on MIN or MAX
+✅ NaN never comes out of `run`, and bit operations on floats are Unsupported
+✅ integer types up to 64 bits match an exact model computed in i128, which
+   doesn't reuse the operators under test: x << y is x · 2^y, x >> y is ⌊x / 2^y⌋
+✅ f32 matches f64 rounded to f32 (exact for + - * / % min max)
+
+$ rustc -O conformance.rs && ./conformance
+type	+	-	*	/	%	pow	min	max	&	|	^	<<	>>
+i8	✓	✓	✓	✓	✓	✓	✓	✓	✓	✓	✓	✓	✓
+…
+f64	✓	✓	✓	✓	✓	✓	✓	✓	—	—	—	—	—
+(✓ every case passed, — Unsupported for this type)
+$ rustc --test conformance.rs && ./conformance   // the matrix, plus spot checks for what the models can't cover
+*/
+
+mod operation;
+
+use operation::{Mode, Number, OpError, Operation};
+
+const MODES: [Mode; 3] = [Mode::Checked, Mode::Wrapping, Mode::Saturating];
+
+// The primitive types are Copy and bounded, unlike BigInt and Rational.
+trait Conformance: Number + Copy {
+    const MIN: Self;
+    const MAX: Self;
+
+    // Edge cases for this type, MIN and MAX included.
+    fn samples() -> Vec<Self>;
+
+    // What `run` must return, from an independent model; None when there is none.
+    fn reference(op: Operation, x: Self, y: Self, mode: Mode) -> Option<Result<Self, OpError>>;
+
+    fn is_nan(self) -> bool {
+        false
+    }
+}
+
+// ---------- INTEGER MODEL ----------
+// Exact arithmetic in i128: enough for every type up to 64 bits. Results that
+// don't fit in i128 at all (u64::MAX * u64::MAX, 3^u64::MAX) are `None` with a
+// known sign, and wrapping i128 arithmetic still gets their low bits right.
+fn integer_reference(op: Operation, x: i128, y: i128, mode: Mode, min: i128, max: i128) -> Result<i128, OpError> {
+    use Operation::*;
+
+    let bits = if min < 0 { (max + 1).trailing_zeros() + 1 } else { (max + 1).trailing_zeros() };
+    // Two's complement truncation to `bits` bits.
+    let truncate = |value: i128| {
+        let low = value & ((1 << bits) - 1);
+        if low > max {
+            low - (1 << bits)
+        } else {
+            low
+        }
+    };
+
+    if matches!(op, Divide | Remainder) && y == 0 {
+        return Err(OpError::DivideByZero);
+    }
+    if op == Pow && y < 0 {
+        return Err(OpError::NegativeExponent);
+    }
+    if matches!(op, Shl | Shr) && y < 0 {
+        return Err(OpError::NegativeShift);
+    }
+    let (exact, wrapped, negative) = match op {
+        Add => (Some(x + y), x + y, x + y < 0),
+        Subtract => (Some(x - y), x - y, x - y < 0),
+        Multiply => (x.checked_mul(y), x.wrapping_mul(y), (x < 0) != (y < 0)),
+        Divide => (Some(x / y), x / y, x / y < 0),
+        Remainder => (Some(x % y), x % y, x % y < 0),
+        Pow => {
+            let (mut base, mut exponent, mut wrapped) = (x, y, 1i128);
+            while exponent != 0 {
+                if exponent % 2 == 1 {
+                    wrapped = wrapped.wrapping_mul(base);
+                }
+                base = base.wrapping_mul(base);
+                exponent /= 2;
+            }
+            let exact = match u32::try_from(y) {
+                Ok(exponent) => x.checked_pow(exponent),
+                Err(_) if (-1..=1).contains(&x) => Some(x.pow(2 + (y % 2) as u32)),
+                Err(_) => None,
+            };
+            (exact, wrapped, x < 0 && y % 2 == 1)
+        }
+        Min => (Some(x.min(y)), x.min(y), false),
+        Max => (Some(x.max(y)), x.max(y), false),
+        BitAnd => (Some(x & y), x & y, false),
+        BitOr => (Some(x | y), x | y, false),
+        BitXor => (Some(x ^ y), x ^ y, false),
+        // Shifts as the arithmetic they stand for, x · 2^y and ⌊x / 2^y⌋, without
+        // the shift operators under test. 2^y doesn't fit in i128 from y = 127 on.
+        Shl => {
+            let power = u32::try_from(y).ok().and_then(|y| 2i128.checked_pow(y));
+            let exact = if x == 0 { Some(0) } else { power.and_then(|power| x.checked_mul(power)) };
+            // Modulo 2^bits, x · 2^y is 0 once y >= bits.
+            let wrapped = if y < bits as i128 { x.wrapping_mul(2i128.pow(y as u32)) } else { 0 };
+            (exact, wrapped, x < 0)
+        }
+        Shr => {
+            let power = u32::try_from(y).ok().and_then(|y| 2i128.checked_pow(y));
+            let floor = power.map_or(if x < 0 { -1 } else { 0 }, |power| x.div_euclid(power));
+            (Some(floor), floor, floor < 0)
+        }
+    };
+
+    match exact {
+        Some(value) if (min..=max).contains(&value) => Ok(value),
+        _ => match mode {
+            Mode::Checked => Err(OpError::Overflow),
+            Mode::Wrapping => Ok(truncate(wrapped)),
+            Mode::Saturating if negative => Ok(min),
+            Mode::Saturating => Ok(max),
+        },
+    }
+}
+
+macro_rules! integer_conformance {
+    ($($t:ident)*) => {$(
+        impl Conformance for $t {
+            const MIN: Self = $t::MIN;
+            const MAX: Self = $t::MAX;
+
+            fn samples() -> Vec<Self> {
+                let (min, max, bits) = ($t::MIN as i128, $t::MAX as i128, $t::BITS as i128);
+                let candidates = [min, min + 1, -3, -2, -1, 0, 1, 2, 3, 7, bits - 1, bits, bits + 1, max / 2, max - 1, max];
+                let mut samples: Vec<Self> = candidates.iter().filter_map(|n| $t::try_from(*n).ok()).collect();
+                samples.sort();
+                samples.dedup();
+                samples
+            }
+
+            fn reference(op: Operation, x: Self, y: Self, mode: Mode) -> Option<Result<Self, OpError>> {
+                if $t::BITS > 64 {
+                    return None;
+                }
+                let result = integer_reference(op, x as i128, y as i128, mode, $t::MIN as i128, $t::MAX as i128);
+                Some(result.map(|value| $t::try_from(value).expect("the model stays in range")))
+            }
+        }
+    )*};
+}
+
+integer_conformance!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+// ---------- FLOAT MODEL ----------
+fn float_samples() -> Vec<f64> {
+    vec![
+        f64::NEG_INFINITY, f64::MIN, -1e300, -2.5, -1.0, -0.5, -0.0, 0.0, f64::MIN_POSITIVE, 0.5, 1.0, 2.0, 3.0,
+        1e300, f64::MAX, f64::INFINITY, f64::NAN,
+    ]
+}
+
+impl Conformance for f64 {
+    const MIN: Self = f64::MIN;
+    const MAX: Self = f64::MAX;
+
+    fn samples() -> Vec<Self> {
+        float_samples()
+    }
+
+    fn reference(_: Operation, _: Self, _: Self, _: Mode) -> Option<Result<Self, OpError>> {
+        None
+    }
+
+    fn is_nan(self) -> bool {
+        self.is_nan()
+    }
+}
+
+impl Conformance for f32 {
+    const MIN: Self = f32::MIN;
+    const MAX: Self = f32::MAX;
+
+    fn samples() -> Vec<Self> {
+        let mut samples: Vec<f32> = float_samples().into_iter().map(|x| x as f32).collect();
+        samples.extend([f32::MIN, f32::MAX, 3e38]);
+        samples
+    }
+
+    // + - * / % min max on f32 values are exact in f64, and rounding that to f32
+    // gives the correctly rounded f32 result. pow is not correctly rounded, so it has no model.
+    fn reference(op: Operation, x: Self, y: Self, mode: Mode) -> Option<Result<Self, OpError>> {
+        if op == Operation::Pow {
+            return None;
+        }
+        let result = op.run_with(f64::from(x), f64::from(y), mode).map(|value| value as f32);
+        // An f64 result that is finite but beyond f32::MAX is an f32 overflow.
+        Some(match result {
+            Ok(value) if value.is_infinite() && x.is_finite() && y.is_finite() => match mode {
+                Mode::Checked => Err(OpError::Overflow),
+                Mode::Wrapping => Ok(value),
+                Mode::Saturating if value > 0.0 => Ok(f32::MAX),
+                Mode::Saturating => Ok(f32::MIN),
+            },
+            other => other,
+        })
+    }
+
+    fn is_nan(self) -> bool {
+        self.is_nan()
+    }
+}
+
+// ---------- MATRIX ----------
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cell {
+    Passed,
+    Unsupported,
+}
+
+// Runs one (type, operation) cell of the matrix. Panics with the failing case.
+fn check_cell<T: Conformance>(op: Operation) -> Cell {
+    let samples = T::samples();
+    let mut unsupported = 0;
+    let mut cases = 0;
+    for &x in &samples {
+        for &y in &samples {
+            let results = MODES.map(|mode| op.run_with(x, y, mode));
+            let case = || format!("{}: {:?}({:?}, {:?}) → {:?}", T::NAME, op, x, y, results);
+            let [checked, wrapping, saturating] = results;
+            cases += 1;
+
+            // Modes only differ on overflow.
+            match checked {
+                Err(OpError::Overflow) => {
+                    assert!(wrapping.is_ok(), "{}", case());
+                    let value = saturating.unwrap_or_else(|err| panic!("{}: {}", case(), err));
+                    assert!(value == T::MIN || value == T::MAX, "{}", case());
+                }
+                other => {
+                    assert_eq!(wrapping, other, "{}", case());
+                    assert_eq!(saturating, other, "{}", case());
+                }
+            }
+
+            for result in results {
+                assert!(!matches!(result, Ok(value) if value.is_nan()), "{}", case());
+            }
+            if checked == Err(OpError::Unsupported) {
+                unsupported += 1;
+            }
+
+            for (mode, result) in MODES.into_iter().zip(results) {
+                if let Some(expected) = T::reference(op, x, y, mode) {
+                    assert_eq!(result, expected, "{} in {:?} mode", case(), mode);
+                }
+            }
+        }
+    }
+    match unsupported {
+        0 => Cell::Passed,
+        n if n == cases => Cell::Unsupported,
+        _ => panic!("{}: {:?} is unsupported for only some operands", T::NAME, op),
+    }
+}
+
+fn matrix_row<T: Conformance>() -> Vec<Cell> {
+    Operation::ALL.iter().map(|op| check_cell::<T>(*op)).collect()
+}
+
+// One row per type, named after it.
+macro_rules! matrix {
+    ($($t:ident)*) => {
+        vec![$((stringify!($t), matrix_row::<$t>())),*]
+    };
+}
+
+// The whole matrix, in the order Primitives.rs lists the types.
+fn matrix() -> Vec<(&'static str, Vec<Cell>)> {
+    matrix!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize f32 f64)
+}
+
+fn print_row(name: &str, cells: &[Cell]) {
+    let marks: Vec<&str> = cells
+        .iter()
+        .map(|cell| match cell {
+            Cell::Passed => "✓",
+            Cell::Unsupported => "—",
+        })
+        .collect();
+    println!("{}\t{}", name, marks.join("\t"));
+}
+
+fn main() {
+    let names: Vec<&str> = Operation::ALL.iter().map(|op| op.symbol()).collect();
+    println!("type\t{}", names.join("\t"));
+    for (name, cells) in matrix() {
+        print_row(name, &cells);
+    }
+    println!("(✓ every case passed, — Unsupported for this type)");
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // check_cell panics on the first failing case; this checks which cells are Unsupported.
+    #[test]
+    fn matrix_check() {
+        use Operation::*;
+        let rows = matrix();
+        assert_eq!(rows.len(), 14);
+        for (name, cells) in rows {
+            for (op, cell) in Operation::ALL.into_iter().zip(cells) {
+                let float_only_bits = name.starts_with('f') && matches!(op, BitAnd | BitOr | BitXor | Shl | Shr);
+                let expected = if float_only_bits { Cell::Unsupported } else { Cell::Passed };
+                assert_eq!(cell, expected, "{} {:?}", name, op);
+            }
+        }
+    }
+
+    // Spot checks, mostly for the 128-bit types that the i128 model can't cover.
+    #[test]
+    fn spot_check() {
+        assert_eq!(Operation::Add.run(u128::MAX, 1), Err(OpError::Overflow));
+        assert_eq!(Operation::Add.run_with(u128::MAX, 1, Mode::Wrapping), Ok(0));
+        assert_eq!(Operation::Multiply.run_with(i128::MIN, -1, Mode::Saturating), Ok(i128::MAX));
+        assert_eq!(Operation::Pow.run(2i128, 126), Ok(1 << 126));
+        assert_eq!(Operation::Pow.run(-1i128, i128::MAX), Ok(-1));
+        assert_eq!(Operation::Pow.run_with(3u128, u128::MAX, Mode::Saturating), Ok(u128::MAX));
+        assert_eq!(Operation::Pow.run_with(-2i64, i64::MAX, Mode::Saturating), Ok(i64::MIN));
+        assert_eq!(Operation::Pow.run_with(2u8, 200, Mode::Wrapping), Ok(0));
+        assert_eq!(Operation::Shl.run(1u128, 127), Ok(1 << 127));
+        assert_eq!(Operation::Shl.run(i128::MIN >> 1, 1), Ok(i128::MIN));
+        assert_eq!(Operation::Shl.run(i128::MAX, 1), Err(OpError::Overflow));
+        assert_eq!(Operation::Shl.run_with(i128::MAX, 1, Mode::Wrapping), Ok(-2));
+        assert_eq!(Operation::Shl.run_with(-3i128, 200, Mode::Saturating), Ok(i128::MIN));
+        assert_eq!(Operation::Shr.run(-5i128, 1_000), Ok(-1));
+        assert_eq!(Operation::Shr.run_with(5u128, -1i128 as u128, Mode::Checked), Ok(0));
+        assert_eq!(Operation::Shr.run_with(5i128, -1, Mode::Wrapping), Err(OpError::NegativeShift));
+        assert_eq!(Operation::Subtract.run(0usize, 1), Err(OpError::Overflow));
+        assert_eq!(Operation::Divide.run(-7i8, 2), Ok(-3));
+        assert_eq!(Operation::Remainder.run(i128::MIN, -1), Ok(0)); // the exact value fits
+        assert_eq!(Operation::Divide.run(i128::MIN, -1), Err(OpError::Overflow));
+
+        assert_eq!(Operation::Divide.run(1.0, 0.0), Err(OpError::DivideByZero));
+        assert_eq!(Operation::Remainder.run(1.0, -0.0), Err(OpError::DivideByZero));
+        assert_eq!(Operation::Multiply.run(f64::MAX, 2.0), Err(OpError::Overflow));
+        assert_eq!(Operation::Multiply.run_with(f64::MAX, 2.0, Mode::Wrapping), Ok(f64::INFINITY));
+        assert_eq!(Operation::Multiply.run_with(f64::MAX, -2.0, Mode::Saturating), Ok(f64::MIN));
+        assert_eq!(Operation::Add.run(f64::INFINITY, 1.0), Ok(f64::INFINITY)); // already infinite: not an overflow
+        assert_eq!(Operation::Subtract.run(f64::INFINITY, f64::INFINITY), Err(OpError::NotANumber));
+        assert_eq!(Operation::Pow.run(-8.0, 1.0 / 3.0), Err(OpError::NotANumber));
+        assert_eq!(Operation::Pow.run(2.0f32, -1.0), Ok(0.5));
+        assert_eq!(Operation::Min.run(f32::NAN, 1.0), Err(OpError::NotANumber));
+        assert_eq!(Operation::Shl.run(1.0, 2.0), Err(OpError::Unsupported));
+
+        // Literals: the evaluator hands every type the digits of an unsigned integer.
+        assert_eq!(u8::from_digits("ff", 16), Some(255));
+        assert_eq!(u8::from_digits("100", 16), None);
+        assert_eq!(i128::from_digits("170141183460469231731687303715884105727", 10), Some(i128::MAX));
+        assert_eq!(f64::from_digits("1010", 2), Some(10.0));
+        assert_eq!(f32::from_digits("16777217", 10), Some(16777216.0)); // rounded to nearest
+        assert_eq!(f64::from_digits(&"9".repeat(400), 10), None);
+    }
+}
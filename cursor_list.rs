@@ -0,0 +1,508 @@
+/*
+Doubly-linked list with a cursor
+
+The cons-list `List` in 1.rs can only grow at the head: every node knows its
+successor, nobody knows its predecessor. To edit in the middle you need each
+node to point both ways:
+
+    None ← [prev | 1 | next] ⇄ [prev | 2 | next] ⇄ [prev | 3 | next] → None
+           ↑ head                                   ↑ tail
+
+A node now has TWO owners-in-spirit (its prev and its next), which Box can't
+express, so the links are raw pointers (NonNull) and the list itself owns every
+node. All the `unsafe` stays inside this file; the public API is safe.
+
+✅ CursorMut — a movable position inside the list
+Operation	Cost	Notes
+move_next / move_prev	O(1)	Walking off either end lands on the "ghost" position
+current	O(1)	None on the ghost
+insert_before / insert_after	O(1)	On the ghost: insert at the back / front
+remove_current	O(1)	Cursor moves to the next element
+splice_before / splice_after	O(1)	Moves a whole other list in, no copying
+
+The ghost sits between the tail and the head, so the cursor can wrap around,
+and its index is always `len`.
+
+🧪 Checking the unsafe code
+The tests are model-based: random cursor edits are mirrored on a Vec, and
+after every step the prev/next links are validated in both directions. Elements
+count their own drops, so leaks and double frees show up as failed asserts.
+Run them under a memory checker too. Miri also catches aliasing violations
+(Stacked Borrows) that a sanitizer can't see; there's no Cargo.toml, so call its
+driver directly with the sysroot that `cargo miri setup` builds (a shorter run):
+
+$ rustc --test cursor_list.rs && ./cursor_list
+$ rustc +nightly -Zsanitizer=address --test cursor_list.rs && ./cursor_list
+$ rustup +nightly component add miri rust-src && cargo +nightly miri setup
+$ export MIRI_SYSROOT=$(cargo +nightly miri setup --print-sysroot)
+$ "$(rustc +nightly --print sysroot)/bin/miri" --sysroot "$MIRI_SYSROOT" --edition 2021 --test cursor_list.rs
+*/
+
+#[cfg(test)]
+mod testing;
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    prev: Link<T>,
+    next: Link<T>,
+}
+
+pub struct DoublyLinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    // Tells the compiler we own `Node<T>`s (for drop checking and variance).
+    _owns: PhantomData<Box<Node<T>>>,
+}
+
+// SAFETY: the list owns its nodes exactly like a Box would, so it is as
+// thread-safe as the elements it holds.
+unsafe impl<T: Send> Send for DoublyLinkedList<T> {}
+unsafe impl<T: Sync> Sync for DoublyLinkedList<T> {}
+
+#[allow(dead_code)]
+impl<T> DoublyLinkedList<T> {
+    pub fn new() -> Self {
+        DoublyLinkedList { head: None, tail: None, len: 0, _owns: PhantomData }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        // SAFETY: None and the current head are adjacent ends of this list.
+        unsafe { self.link(Self::new_node(elem), None, self.head) }
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        // SAFETY: the current tail and None are adjacent ends of this list.
+        unsafe { self.link(Self::new_node(elem), self.tail, None) }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        // SAFETY: `head` is a node of this list.
+        self.head.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        // SAFETY: `tail` is a node of this list.
+        self.tail.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: nodes stay alive while `self` is borrowed.
+        self.head.map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: nodes stay alive while `self` is borrowed.
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { front: self.head, back: self.tail, remaining: self.len, _list: PhantomData }
+    }
+
+    // A cursor on the first element (or on the ghost if the list is empty).
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { cur: self.head, index: 0, list: self }
+    }
+
+    // A cursor on the last element (or on the ghost if the list is empty).
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let index = self.len.saturating_sub(1);
+        CursorMut { cur: self.tail, index, list: self }
+    }
+
+    fn new_node(elem: T) -> NonNull<Node<T>> {
+        NonNull::from(Box::leak(Box::new(Node { elem, prev: None, next: None })))
+    }
+
+    // Links a fresh `node` between `prev` and `next`.
+    // SAFETY: `prev` and `next` must be adjacent in `self` (None = that end of the list)
+    // and `node` must not be in any list.
+    unsafe fn link(&mut self, node: NonNull<Node<T>>, prev: Link<T>, next: Link<T>) {
+        (*node.as_ptr()).prev = prev;
+        (*node.as_ptr()).next = next;
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = Some(node),
+            None => self.head = Some(node),
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(node),
+            None => self.tail = Some(node),
+        }
+        self.len += 1;
+    }
+
+    // Unlinks `node`, frees it and returns its element.
+    // SAFETY: `node` must belong to `self`.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        let node = Box::from_raw(node.as_ptr());
+        match node.prev {
+            Some(prev) => (*prev.as_ptr()).next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => (*next.as_ptr()).prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        self.len -= 1;
+        node.elem
+    }
+
+    // Moves every node of `other` between `prev` and `next`.
+    // SAFETY: `prev` and `next` must be adjacent in `self` (None = that end of the list).
+    unsafe fn splice(&mut self, mut other: Self, prev: Link<T>, next: Link<T>) {
+        let (first, last) = match (other.head.take(), other.tail.take()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return,
+        };
+        (*first.as_ptr()).prev = prev;
+        (*last.as_ptr()).next = next;
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = Some(first),
+            None => self.head = Some(first),
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(last),
+            None => self.tail = Some(last),
+        }
+        self.len += std::mem::replace(&mut other.len, 0);
+    }
+
+    // Walks the links in both directions and panics if they disagree.
+    fn check_links(&self) {
+        let mut count = 0;
+        let mut prev: Link<T> = None;
+        let mut cur = self.head;
+        while let Some(node) = cur {
+            // SAFETY: every reachable node belongs to this list.
+            unsafe {
+                assert_eq!((*node.as_ptr()).prev, prev, "broken prev link at {}", count);
+                prev = cur;
+                cur = (*node.as_ptr()).next;
+            }
+            count += 1;
+        }
+        assert_eq!(self.tail, prev, "tail is not the last node");
+        assert_eq!(self.len, count, "len does not match the node count");
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        DoublyLinkedList::new()
+    }
+}
+
+impl<T> Drop for DoublyLinkedList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = DoublyLinkedList::new();
+        for elem in iter {
+            list.push_back(elem);
+        }
+        list
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    remaining: usize,
+    _list: PhantomData<&'a T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.front.map(|node| {
+            self.remaining -= 1;
+            // SAFETY: the list is borrowed for 'a, so its nodes outlive the reference.
+            unsafe {
+                self.front = (*node.as_ptr()).next;
+                &(*node.as_ptr()).elem
+            }
+        })
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.back.map(|node| {
+            self.remaining -= 1;
+            // SAFETY: as in `next`.
+            unsafe {
+                self.back = (*node.as_ptr()).prev;
+                &(*node.as_ptr()).elem
+            }
+        })
+    }
+}
+
+// ---------- CURSOR ----------
+// `cur == None` is the ghost position between the tail and the head; its index is `len`.
+pub struct CursorMut<'a, T> {
+    list: &'a mut DoublyLinkedList<T>,
+    cur: Link<T>,
+    index: usize,
+}
+
+#[allow(dead_code)]
+impl<T> CursorMut<'_, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.cur.map(|_| self.index)
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: the cursor borrows the list mutably, so nobody else can reach the node.
+        self.cur.map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = self.next_link();
+        // SAFETY: as in `current`.
+        next.map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = self.prev_link();
+        // SAFETY: as in `current`.
+        prev.map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    pub fn move_next(&mut self) {
+        self.cur = self.next_link();
+        self.index = match self.cur {
+            Some(_) if self.index < self.list.len => self.index + 1,
+            Some(_) => 0,
+            None => self.list.len,
+        };
+    }
+
+    pub fn move_prev(&mut self) {
+        let was_ghost = self.cur.is_none();
+        self.cur = self.prev_link();
+        self.index = match self.cur {
+            Some(_) if was_ghost => self.list.len - 1,
+            Some(_) => self.index - 1,
+            None => self.list.len,
+        };
+    }
+
+    // Inserts before the cursor; on the ghost this appends at the back.
+    pub fn insert_before(&mut self, elem: T) {
+        let prev = self.prev_link();
+        // SAFETY: `prev` and `cur` are adjacent (the ghost joins tail and head).
+        unsafe { self.list.link(DoublyLinkedList::new_node(elem), prev, self.cur) }
+        self.index += 1;
+    }
+
+    // Inserts after the cursor; on the ghost this pushes at the front.
+    pub fn insert_after(&mut self, elem: T) {
+        let next = self.next_link();
+        // SAFETY: `cur` and `next` are adjacent.
+        unsafe { self.list.link(DoublyLinkedList::new_node(elem), self.cur, next) }
+        if self.cur.is_none() {
+            self.index += 1;
+        }
+    }
+
+    // Removes the current element and moves to the next one. None on the ghost.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.cur?;
+        self.cur = self.next_link();
+        // SAFETY: `node` is a node of this list.
+        Some(unsafe { self.list.unlink(node) })
+    }
+
+    // Moves all of `other` in before the cursor.
+    pub fn splice_before(&mut self, other: DoublyLinkedList<T>) {
+        let added = other.len;
+        let prev = self.prev_link();
+        // SAFETY: `prev` and `cur` are adjacent.
+        unsafe { self.list.splice(other, prev, self.cur) }
+        self.index += added;
+    }
+
+    // Moves all of `other` in after the cursor.
+    pub fn splice_after(&mut self, other: DoublyLinkedList<T>) {
+        let added = other.len;
+        let next = self.next_link();
+        // SAFETY: `cur` and `next` are adjacent.
+        unsafe { self.list.splice(other, self.cur, next) }
+        if self.cur.is_none() {
+            self.index += added;
+        }
+    }
+
+    fn next_link(&self) -> Link<T> {
+        match self.cur {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head,
+        }
+    }
+
+    fn prev_link(&self) -> Link<T> {
+        match self.cur {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail,
+        }
+    }
+}
+
+// ---------- 1. CURSOR EDITING ----------
+fn cursor_example() {
+    let mut list: DoublyLinkedList<i32> = (1..=5).collect();
+    println!("start:          {:?}", list);
+
+    let mut cursor = list.cursor_front_mut();
+    cursor.move_next(); // at 2
+    cursor.insert_before(10);
+    cursor.insert_after(20);
+    assert_eq!(cursor.index(), Some(2));
+    *cursor.current().unwrap() *= 100;
+    cursor.move_next(); // at 20
+    cursor.move_next(); // at 3
+    assert_eq!(cursor.remove_current(), Some(3));
+    cursor.splice_before((7..=8).collect());
+    println!("after edits:    {:?}", list);
+    assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 10, 200, 20, 7, 8, 4, 5]);
+
+    // Walking off the end lands on the ghost, and the next step wraps to the front.
+    let mut cursor = list.cursor_back_mut();
+    cursor.move_next();
+    assert_eq!(cursor.current(), None);
+    cursor.insert_before(99); // ghost: append
+    cursor.move_next();
+    assert_eq!(cursor.current(), Some(&mut 1));
+    println!("wrapped around: {:?}", list);
+    println!("backwards:      {:?}", list.iter().rev().collect::<Vec<_>>());
+    list.check_links();
+}
+
+fn main() {
+    cursor_example();
+}
+
+// ---------- 2. MODEL-BASED CHECK ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::{next_random, SEED};
+
+    // Each element bumps a shared counter when dropped, to catch leaks and double frees.
+    struct Tracked(u32, std::rc::Rc<std::cell::Cell<usize>>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() + 1);
+        }
+    }
+
+    #[test]
+    fn model_check() {
+        let drops = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut created = 0;
+        let mut model: Vec<u32> = Vec::new();
+        let mut list = DoublyLinkedList::new();
+        let mut seed = SEED;
+        let mut pos = 0; // model index of the cursor; model.len() is the ghost
+        // Miri interprets every pointer access, so it gets a shorter run.
+        let steps = if cfg!(miri) { 300 } else { 3_000 };
+
+        for step in 0..steps {
+            let mut cursor = list.cursor_front_mut();
+            for _ in 0..pos {
+                cursor.move_next();
+            }
+            let len = model.len();
+            match next_random(&mut seed) % 8 {
+                0 => {
+                    cursor.move_next();
+                    pos = if pos == len { 0 } else { pos + 1 };
+                }
+                1 => {
+                    cursor.move_prev();
+                    pos = if pos == 0 { len } else { pos - 1 };
+                }
+                2 => {
+                    cursor.insert_before(Tracked(step, drops.clone()));
+                    model.insert(pos, step);
+                    pos += 1;
+                    created += 1;
+                }
+                3 => {
+                    cursor.insert_after(Tracked(step, drops.clone()));
+                    if pos == len {
+                        model.insert(0, step);
+                        pos += 1;
+                    } else {
+                        model.insert(pos + 1, step);
+                    }
+                    created += 1;
+                }
+                4 | 5 => {
+                    let removed = cursor.remove_current().map(|t| t.0);
+                    assert_eq!(removed, if pos < len { Some(model.remove(pos)) } else { None });
+                }
+                _ => {
+                    let extra: Vec<u32> = (0..next_random(&mut seed) % 4).map(|i| step * 10 + i as u32).collect();
+                    created += extra.len();
+                    let other = extra.iter().map(|&v| Tracked(v, drops.clone())).collect();
+                    if step % 2 == 0 {
+                        cursor.splice_before(other);
+                        model.splice(pos..pos, extra.iter().copied());
+                        pos += extra.len();
+                    } else {
+                        cursor.splice_after(other);
+                        let at = if pos == len { 0 } else { pos + 1 };
+                        model.splice(at..at, extra.iter().copied());
+                        if pos == len {
+                            pos += extra.len();
+                        }
+                    }
+                }
+            }
+            assert_eq!(cursor.index(), if pos < model.len() { Some(pos) } else { None });
+            list.check_links();
+            assert!(list.iter().map(|t| t.0).eq(model.iter().copied()), "diverged at step {}", step);
+            assert_eq!(drops.get(), created - model.len());
+        }
+
+        drop(list);
+        assert_eq!(drops.get(), created);
+    }
+}
@@ -193,19 +193,23 @@ Unit structs are lightweight and compile to zero bytes
 
 --------------------
 
-enum VeryVerboseEnumOfThingsToDoWithNumbers {
-    Add,
-    Subtract,
-}
+mod operation; // operation.rs, next to this file
 
-impl VeryVerboseEnumOfThingsToDoWithNumbers {
-    fn run(&self, x: i32, y: i32) -> i32 {
-        match self {
-            Self::Add => x + y,
-            Self::Subtract => x - y,
-        }
-    }
-}
+// Add, Subtract, Multiply, Divide, Remainder, Pow, Min, Max, BitAnd, BitOr, BitXor, Shl, Shr
+use operation::Operation as VeryVerboseEnumOfThingsToDoWithNumbers;
+use operation::{Mode, OpError};
+
+type Operations = VeryVerboseEnumOfThingsToDoWithNumbers;
+
+let op = Operations::Add;
+assert_eq!(op.run(2, 3), Ok(5));
+assert_eq!(op.run(i32::MAX, 1), Err(OpError::Overflow)); // no panic, in any build
+assert_eq!(op.run_with(i32::MAX, 1, Mode::Wrapping), Ok(i32::MIN));
+assert_eq!(Operations::Divide.run(1, 0), Err(OpError::DivideByZero));
+assert_eq!(Operations::Max.run(2.5, -1.0), Ok(2.5)); // any primitive number type
+
+`run` returns a Result instead of panicking on overflow in debug builds, and
+the Checked / Wrapping / Saturating modes are in `Mode` — see operation.rs.
 
 ---------------
 
@@ -0,0 +1,199 @@
+/*
+Symbolic derivatives — symbolic.rs on the command line
+
+$ rustc -O derive.rs
+$ ./derive "x * x + 3 * x" "pow(x, 3) / (x + 1)" "1 / x"
+d/dx x * x + 3 * x = 2 * x + 3
+d/dx pow(x, 3) / (x + 1) = (3 * pow(x, 2) * (x + 1) - pow(x, 3)) / pow(x + 1, 2)
+d/dx 1 / x = -1 / pow(x, 2)
+
+$ ./derive --var=t "pow(t, 2) * a"          // other names are constants
+d/dt pow(t, 2) * a = 2 * t * a
+
+$ ./derive "min(x, 1)"
+error: cannot differentiate `min`
+  |  min(x, 1)
+  |  ^^^^^^^^^
+
+✅ Every derivative in the tests below is compared with finite differences
+   (f(x + h) - f(x - h)) / 2h at several points, on hand-written and on random
+   expressions, and simplification must never change a value:
+
+$ rustc --test derive.rs && ./derive
+*/
+
+mod expression;
+mod operation;
+mod symbolic;
+#[cfg(test)]
+mod testing;
+
+use expression::{parse, Error};
+use symbolic::differentiate;
+
+fn derive(source: &str, var: &str) -> Result<String, Error> {
+    Ok(differentiate(&parse(source)?, var)?.to_string())
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let mut var = "x".to_string();
+    if let Some(name) = args.first().and_then(|arg| arg.strip_prefix("--var=")) {
+        var = name.to_string();
+        args.remove(0);
+    }
+    let mut ok = true;
+    for source in &args {
+        match derive(source, &var) {
+            Ok(result) => println!("d/d{} {} = {}", var, source, result),
+            Err(err) => {
+                println!("{}", err.render(source));
+                ok = false;
+            }
+        }
+    }
+    if !ok {
+        std::process::exit(1);
+    }
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expression::Expr;
+    use operation::Operation;
+    use symbolic::{derivative, eval_at, finite_difference, simplify};
+    use testing::{next_random, SEED};
+
+    #[test]
+    fn rules_check() {
+        let cases = [
+            ("7", "0"),
+            ("x", "1"),
+            ("y", "0"),
+            ("-x", "-1"),
+            ("x + y", "1"),
+            ("x - 5", "1"),
+            ("3 * x", "3"),
+            ("x * x + 3 * x", "2 * x + 3"),
+            ("x * y", "y"),
+            ("pow(x, 3)", "3 * pow(x, 2)"),
+            ("pow(x, 2) * 5", "10 * x"),
+            ("pow(2 * x + 1, 3)", "6 * pow(2 * x + 1, 2)"),
+            ("1 / x", "-1 / pow(x, 2)"),
+            ("x / 4", "1 / 4"),
+            ("x / (x + 1)", "1 / pow(x + 1, 2)"),
+            ("pow(x, 0 - 1)", "-pow(x, -2)"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(derive(source, "x"), Ok(expected.to_string()), "d/dx {}", source);
+        }
+        assert_eq!(derive("pow(t, 2) * a", "t"), Ok("2 * t * a".to_string()));
+    }
+
+    #[test]
+    fn simplify_check() {
+        let simplified = |source: &str| simplify(&parse(source).unwrap()).to_string();
+        assert_eq!(simplified("(1 * x + x * 1) + (0 * x + 3 * 1)"), "2 * x + 3");
+        assert_eq!(simplified("x + 0 - 0 * y"), "x");
+        assert_eq!(simplified("2 * (3 * x) * 1"), "6 * x");
+        assert_eq!(simplified("x * 2"), "2 * x");
+        assert_eq!(simplified("pow(pow(x, 2), 3)"), "pow(x, 6)");
+        assert_eq!(simplified("pow(x, 2) * x"), "pow(x, 3)");
+        assert_eq!(simplified("x - -y"), "x + y");
+        assert_eq!(simplified("--x"), "x");
+        assert_eq!(simplified("-(x - 1)"), "1 - x");
+        assert_eq!(simplified("6 / 4"), "3 / 2");
+        assert_eq!(simplified("6 / -3"), "-2");
+        assert_eq!(simplified("(x + 1) - x"), "1");
+        assert_eq!(simplified("pow(3, 39)"), "4052555153018976267");
+        assert_eq!(simplified("pow(3, 40)"), "pow(3, 40)"); // doesn't fit in i64: left alone
+        assert_eq!(simplified("min(x, x) + 0"), "min(x, x)");
+    }
+
+    #[test]
+    fn errors_check() {
+        let err = derive("min(x, 1)", "x").unwrap_err();
+        assert_eq!((err.message.as_str(), err.span.start, err.span.end), ("cannot differentiate `min`", 0, 9));
+        let err = derive("pow(2, x)", "x").unwrap_err();
+        assert_eq!(err.message, "cannot differentiate `pow` with an exponent that depends on `x`");
+        assert_eq!((err.span.start, err.span.end), (7, 8));
+        assert_eq!(derive("x % 2", "x").unwrap_err().message, "cannot differentiate `%`");
+        // Operations on constants are fine: they are constants.
+        assert_eq!(derive("x + min(2, 3) + (7 % 4)", "x"), Ok("1".to_string()));
+    }
+
+    // f' must agree with (f(x + h) - f(x - h)) / 2h wherever both exist.
+    fn agrees(f: &Expr, df: &Expr, x: f64) -> bool {
+        let (Ok(exact), Some(estimate)) = (eval_at(df, "x", x), finite_difference(f, "x", x)) else {
+            return true; // undefined here (division by zero, overflow): nothing to compare
+        };
+        (exact - estimate).abs() <= 1e-4 * (1.0 + exact.abs().max(estimate.abs()))
+    }
+
+    const POINTS: [f64; 7] = [-3.7, -1.3, -0.4, 0.6, 1.9, 2.6, 5.1];
+
+    #[test]
+    fn finite_difference_check() {
+        let sources = [
+            "x * x + 3 * x",
+            "pow(x, 3) / (x + 1)",
+            "1 / x",
+            "(x - 1) * (x + 2) * (x - 3)",
+            "pow(x * x + 1, 0 - 2)",
+            "x / (x * x + 1) - 7 * pow(x, 4)",
+            "-(2 * x - 5) / (3 - x)",
+        ];
+        for source in sources {
+            let f = parse(source).unwrap();
+            let df = differentiate(&f, "x").unwrap();
+            for x in POINTS {
+                assert!(agrees(&f, &df, x), "d/dx {} = {} at x = {}", source, df, x);
+            }
+        }
+    }
+
+    // Not testing::random_expression: only operations with a derivative, small exponents, and `x`.
+    fn random_expression(state: &mut u64, depth: u32) -> String {
+        const OPERANDS: [&str; 6] = ["x", "x", "1", "2", "3", "7"];
+        const OPERATIONS: [Operation; 5] =
+            [Operation::Add, Operation::Subtract, Operation::Multiply, Operation::Divide, Operation::Pow];
+        let pick = next_random(state);
+        if depth == 0 || pick.is_multiple_of(4) {
+            return OPERANDS[(pick / 4 % OPERANDS.len() as u64) as usize].to_string();
+        }
+        let op = OPERATIONS[(pick / 4 % OPERATIONS.len() as u64) as usize];
+        let x = random_expression(state, depth - 1);
+        match op {
+            Operation::Pow => format!("pow({}, {})", x, pick / 64 % 4),
+            _ if pick.is_multiple_of(7) => format!("-({} {} {})", x, op, random_expression(state, depth - 1)),
+            _ => format!("({} {} {})", x, op, random_expression(state, depth - 1)),
+        }
+    }
+
+    // On random expressions: the simplified derivative has the raw derivative's
+    // values, and both match finite differences.
+    #[test]
+    fn random_check() {
+        let mut state = SEED;
+        for _ in 0..2000 {
+            let source = random_expression(&mut state, 4);
+            let f = parse(&source).unwrap();
+            let raw = derivative(&f, "x").unwrap();
+            let df = simplify(&raw);
+            for x in POINTS {
+                // Near a pole the estimate is meaningless; skip where f is huge.
+                if eval_at(&f, "x", x).is_ok_and(|value| value.abs() > 1e6) {
+                    continue;
+                }
+                assert!(agrees(&f, &df, x), "d/dx {} = {} at x = {}", source, df, x);
+                if let (Ok(a), Ok(b)) = (eval_at(&raw, "x", x), eval_at(&df, "x", x)) {
+                    assert!((a - b).abs() <= 1e-9 * (1.0 + a.abs()), "{} ≠ {} at x = {}", raw, df, x);
+                }
+            }
+            let simplified = simplify(&f);
+            assert_eq!(simplify(&simplified).to_string(), simplified.to_string(), "simplify is idempotent");
+        }
+    }
+}
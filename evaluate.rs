@@ -0,0 +1,216 @@
+/*
+Evaluate infix expressions built from `Operation`
+
+expression.rs parses text into an AST of Operation nodes; operation.rs does the
+arithmetic. This program wires them together:
+
+$ rustc -O evaluate.rs
+$ ./evaluate "3 + 2 * (4 - 1) % 5" "10 / (3 - 3)"
+3 + 2 * (4 - 1) % 5 = 4
+error: division by zero
+  |  10 / (3 - 3)
+  |       ^^^^^^^
+
+$ echo "1 << 4 | 1" | ./evaluate          // no arguments: one expression per stdin line
+1 << 4 | 1 = 17
+
+$ ./evaluate --type=bigint "pow(3, 100)"  // any Number: i8..u128, f32, f64, bigint, rational
+pow(3, 100) = 515377520732011331036461129765621272702107522001
+$ ./evaluate --type=rational "1 / 3 + 1 / 6"
+1 / 3 + 1 / 6 = 1/2
+
+✅ Precedence follows Primitives.rs: 2 + 3 * 4 == 14, 1 | 2 & 3 == 3
+✅ Left-associative: 8 - 3 - 2 == 3, 64 / 4 / 2 == 8
+✅ pow / min / max use call syntax: pow(2, 10) == 1024
+⚠️ Errors point at the part of the input that caused them.
+
+$ rustc --test evaluate.rs && ./evaluate  // the tests
+*/
+
+mod bigint;
+mod expression;
+mod operation;
+mod rational;
+
+use std::io::{self, BufRead};
+
+use bigint::BigInt;
+use expression::{parse, Error};
+use operation::Number;
+use rational::Rational;
+
+fn evaluate_as<T: Number>(source: &str) -> Result<T, Error> {
+    parse(source)?.eval()
+}
+
+// Evaluates in the type named by --type=, printing the result.
+type Evaluator = fn(&str) -> Result<String, Error>;
+
+fn evaluator(type_name: &str) -> Option<Evaluator> {
+    fn as_text<T: Number>(source: &str) -> Result<String, Error> {
+        evaluate_as::<T>(source).map(|value| value.to_string())
+    }
+    Some(match type_name {
+        "i8" => as_text::<i8>,
+        "i16" => as_text::<i16>,
+        "i32" => as_text::<i32>,
+        "i64" => as_text::<i64>,
+        "i128" => as_text::<i128>,
+        "isize" => as_text::<isize>,
+        "u8" => as_text::<u8>,
+        "u16" => as_text::<u16>,
+        "u32" => as_text::<u32>,
+        "u64" => as_text::<u64>,
+        "u128" => as_text::<u128>,
+        "usize" => as_text::<usize>,
+        "f32" => as_text::<f32>,
+        "f64" => as_text::<f64>,
+        "bigint" => as_text::<BigInt>,
+        "rational" => as_text::<Rational>,
+        _ => return None,
+    })
+}
+
+fn print_result(source: &str, evaluate: Evaluator) -> bool {
+    match evaluate(source) {
+        Ok(value) => {
+            println!("{} = {}", source, value);
+            true
+        }
+        Err(err) => {
+            println!("{}", err.render(source));
+            false
+        }
+    }
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let mut evaluate: Evaluator = evaluator("i32").expect("i32 is a Number");
+    if let Some(type_name) = args.first().and_then(|arg| arg.strip_prefix("--type=")) {
+        evaluate = evaluator(type_name).unwrap_or_else(|| {
+            eprintln!("unknown type `{}` (expected i8..i128, u8..u128, isize, usize, f32, f64, bigint or rational)", type_name);
+            std::process::exit(2);
+        });
+        args.remove(0);
+    }
+
+    let mut ok = true;
+    if args.is_empty() {
+        for line in io::stdin().lock().lines() {
+            let line = line.expect("failed to read stdin");
+            if !line.trim().is_empty() {
+                ok &= print_result(&line, evaluate);
+            }
+        }
+    } else {
+        for source in &args {
+            ok &= print_result(source, evaluate);
+        }
+    }
+    if !ok {
+        std::process::exit(1);
+    }
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expression::{Expr, Literal, Span};
+    use operation::{Mode, Operation};
+
+    fn evaluate(source: &str) -> Result<i32, Error> {
+        evaluate_as(source)
+    }
+
+    #[test]
+    fn precedence_check() {
+        let cases = [
+            ("3 + 2 * (4 - 1) % 5", 4),
+            ("2 + 3 * 4", 14),
+            ("(2 + 3) * 4", 20),
+            ("8 - 3 - 2", 3),
+            ("64 / 4 / 2", 8),
+            ("1 | 2 & 3", 3),
+            ("6 ^ 3 & 5", 7),
+            ("1 << 2 + 1", 8),
+            ("-2 * -3", 6),
+            ("--5", 5),
+            ("-(2 + 3)", -5),
+            ("pow(2, 10) - max(1, min(7, 3))", 1021),
+            ("0xff & 0b1111_0000 | 0o7", 247),
+            ("-2147483647 - 1", i32::MIN),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(evaluate(source), Ok(expected), "{}", source);
+        }
+    }
+
+    #[test]
+    fn error_span_check() {
+        let span_of = |source: &str| evaluate(source).unwrap_err().span;
+
+        assert_eq!(span_of("10 / (3 - 3)"), Span::new(5, 12)); // the divisor
+        assert_eq!(span_of("pow(2, 0 - 1)"), Span::new(7, 12)); // the exponent
+        assert_eq!(span_of("1 + 2147483647 * 2"), Span::new(4, 18)); // the overflowing operation
+        assert_eq!(span_of("1 + $"), Span::new(4, 5));
+        assert_eq!(span_of("(1 + 2"), Span::new(6, 6)); // missing `)` at end of input
+        assert_eq!(span_of("1 2"), Span::new(2, 3));
+        assert_eq!(span_of("foo(1)"), Span::new(0, 3));
+        assert_eq!(span_of("99999999999"), Span::new(0, 11));
+        assert_eq!(span_of(&"(".repeat(1000)).start, 256);
+
+        let rendered = evaluate("10 / (3 - 3)").unwrap_err().render("10 / (3 - 3)");
+        assert_eq!(rendered, "error: division by zero\n  |  10 / (3 - 3)\n  |       ^^^^^^^");
+    }
+
+    // Every operator counts towards the nesting limit, so a long flat chain is rejected
+    // instead of overflowing the stack; trees built in code are evaluated, printed and
+    // dropped without recursion, however deep.
+    #[test]
+    fn deep_tree_check() {
+        let chain = |terms: usize| vec!["1"; terms].join("+");
+        assert_eq!(evaluate(&chain(200)), Ok(200));
+        let err = evaluate(&chain(200_000)).unwrap_err();
+        assert_eq!(err.message, "expression nested too deeply");
+        assert_eq!(err.span, Span::new(510, 511));
+
+        let one = || Expr::number(Literal::parse("1").unwrap(), Span::new(0, 1));
+        let mut sum = one();
+        let mut negated = one();
+        for _ in 0..1_000_000 {
+            sum = Expr::binary(Operation::Add, sum, one(), Span::new(0, 1));
+            negated = Expr::negate(negated, Span::new(0, 1));
+        }
+        assert_eq!(sum.eval(), Ok(1_000_001));
+        assert_eq!(negated.eval(), Ok(1));
+        assert_eq!(sum.to_string().len(), 4_000_001);
+        assert!(negated.to_string().ends_with("--1"));
+        drop((sum, negated));
+    }
+
+    #[test]
+    fn mode_check() {
+        let expr = parse("2147483647 + 1").unwrap();
+        assert_eq!(expr.eval_with::<i32>(Mode::Checked).unwrap_err().message, "arithmetic overflow");
+        assert_eq!(expr.eval_with(Mode::Wrapping), Ok(i32::MIN));
+        assert_eq!(expr.eval_with(Mode::Saturating), Ok(i32::MAX));
+    }
+
+    #[test]
+    fn type_check() {
+        let run = |type_name: &str, source: &str| evaluator(type_name).unwrap()(source);
+        assert_eq!(run("u8", "200 + 55"), Ok("255".to_string()));
+        assert_eq!(run("u8", "200 + 56").unwrap_err().message, "arithmetic overflow");
+        assert_eq!(run("u8", "256").unwrap_err().message, "literal `256` does not fit in u8");
+        assert_eq!(run("i64", "2147483647 + 1"), Ok("2147483648".to_string()));
+        assert_eq!(run("f64", "7 / 2"), Ok("3.5".to_string()));
+        assert_eq!(run("f64", "1 & 1").unwrap_err().message, "operation not supported for this type");
+        assert_eq!(run("bigint", "pow(3, 100)"), Ok("515377520732011331036461129765621272702107522001".to_string()));
+        assert_eq!(run("bigint", "-0xffff_ffff_ffff_ffff_ffff >> 64"), Ok("-65536".to_string()));
+        assert_eq!(run("rational", "1 / 3 + 1 / 6"), Ok("1/2".to_string()));
+        assert_eq!(run("rational", "pow(2, 0 - 3) * 3"), Ok("3/8".to_string()));
+        assert!(evaluator("i256").is_none());
+    }
+}
@@ -0,0 +1,346 @@
+/*
+Record a WebEvent session to an NDJSON log, and replay it
+
+$ rustc -O event_replay.rs
+$ ./event_replay record session.ndjson       // one event per stdin line, stamped as it arrives
+load
+key a
+click 10 20
+^D
+$ cat session.ndjson
+{"t_us":41,"type":"PageLoad"}
+{"t_us":1250340,"type":"KeyPress","key":"a"}
+{"t_us":1874002,"type":"Click","x":10,"y":20}
+$ ./event_replay replay session.ndjson --speed=2
+Page loaded
+Key pressed: a
+Clicked at: 10, 20
+3 events in 0.937 s
+
+Input line	Event
+load	WebEvent::PageLoad
+key C	WebEvent::KeyPress('C'); `key space` for ' '
+click X Y	WebEvent::Click { x: X, y: Y }
+
+--speed=original (default)	the gaps between events as recorded
+--speed=N	N times faster (0.5: half speed)
+--speed=fastest	no waiting at all
+
+📌 Replay feeds the same Dispatcher handlers as live input (`inspect` here), so a
+   log attached to a bug report reproduces the session event for event.
+📌 The format and the Recorder / replay code are in web_event.rs.
+
+$ rustc --test event_replay.rs && ./event_replay   // the tests
+*/
+
+mod web_event;
+
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::time::Instant;
+
+use web_event::{inspect, parse_log, replay, Dispatcher, Recorder, Speed, WebEvent};
+
+// ---------- 1. COMMANDS ----------
+fn parse_event(line: &str) -> Option<WebEvent> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["load"] => Some(WebEvent::PageLoad),
+        ["key", "space"] => Some(WebEvent::KeyPress(' ')),
+        ["key", key] => {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(WebEvent::KeyPress(c)),
+                _ => None,
+            }
+        }
+        ["click", x, y] => Some(WebEvent::Click { x: x.parse().ok()?, y: y.parse().ok()? }),
+        _ => None,
+    }
+}
+
+fn parse_speed(text: &str) -> Option<Speed> {
+    match text {
+        "original" => Some(Speed::Original),
+        "fastest" => Some(Speed::Fastest),
+        factor => factor.parse().ok().and_then(Speed::scaled),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("usage: event_replay record LOG                         events from stdin: load | key C | click X Y");
+    eprintln!("       event_replay replay LOG [--speed=original|N|fastest]");
+    std::process::exit(2);
+}
+
+fn record(path: &str) -> io::Result<()> {
+    let mut recorder = Recorder::new(File::create(path)?);
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_event(&line) {
+            Some(event) => recorder.record(event)?,
+            None => eprintln!("ignored `{}`: expected load, key C or click X Y", line.trim()),
+        }
+    }
+    Ok(())
+}
+
+fn replay_file(path: &str, speed: Speed) -> Result<(), Box<dyn std::error::Error>> {
+    let events = parse_log(&std::fs::read_to_string(path)?)?;
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.on_any(inspect);
+    let start = Instant::now();
+    replay(&events, speed, &mut dispatcher);
+    println!("{} events in {:.3} s", events.len(), start.elapsed().as_secs_f64());
+    Ok(())
+}
+
+// ---------- 2. MAIN ----------
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        [] => {}
+        ["record", path] => {
+            if let Err(err) = record(path) {
+                eprintln!("error: {}", err);
+                std::process::exit(1);
+            }
+        }
+        ["replay", path, options @ ..] => {
+            let mut speed = Speed::Original;
+            for option in options {
+                match option.strip_prefix("--speed=").and_then(parse_speed) {
+                    Some(parsed) => speed = parsed,
+                    None => usage(),
+                }
+            }
+            if let Err(err) = replay_file(path, speed) {
+                eprintln!("error: {}: {}", path, err);
+                std::process::exit(1);
+            }
+        }
+        _ => usage(),
+    }
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use web_event::{replay_with, Button, Clock, EventKind, Key, KeyEvent, Modifiers, MouseAction, MouseEvent, Propagation, Recorded};
+
+    fn at_ms(ms: u64, event: WebEvent) -> Recorded {
+        Recorded { at: Duration::from_millis(ms), event }
+    }
+
+    #[test]
+    fn json_check() {
+        let events = [
+            at_ms(0, WebEvent::PageLoad),
+            at_ms(1250, WebEvent::KeyPress('a')),
+            at_ms(1874, WebEvent::Click { x: 10, y: -20 }),
+        ];
+        let lines: Vec<String> = events.iter().map(|recorded| recorded.to_json()).collect();
+        assert_eq!(
+            lines,
+            [
+                r#"{"t_us":0,"type":"PageLoad"}"#,
+                r#"{"t_us":1250000,"type":"KeyPress","key":"a"}"#,
+                r#"{"t_us":1874000,"type":"Click","x":10,"y":-20}"#,
+            ]
+        );
+        for (line, event) in lines.iter().zip(&events) {
+            assert_eq!(Recorded::from_json(line).as_ref(), Ok(event));
+        }
+
+        // Keys that need escaping, and keys outside ASCII.
+        for key in ['"', '\\', '\n', '\u{1b}', 'é', '😀', ' '] {
+            let recorded = at_ms(5, WebEvent::KeyPress(key));
+            assert_eq!(Recorded::from_json(&recorded.to_json()), Ok(recorded));
+        }
+        assert_eq!(at_ms(0, WebEvent::KeyPress('\u{1b}')).to_json(), r#"{"t_us":0,"type":"KeyPress","key":"\u001b"}"#);
+
+        // The richer variants, with and without modifiers.
+        let events = [
+            at_ms(1, KeyEvent::new(Key::Up, Modifiers::CTRL | Modifiers::SHIFT).into()),
+            at_ms(2, KeyEvent::release(Key::F(5), Modifiers::NONE).into()),
+            at_ms(3, KeyEvent::new(Key::Char('x'), Modifiers::ALT).into()),
+            at_ms(4, MouseEvent { action: MouseAction::Drag(Button::Right), x: 4, y: 7, modifiers: Modifiers::NONE }.into()),
+            at_ms(5, MouseEvent { action: MouseAction::Move, x: 0, y: 0, modifiers: Modifiers::META }.into()),
+            at_ms(6, WebEvent::Scroll { x: 1, y: 2, dx: 0, dy: -3, modifiers: Modifiers::CTRL }),
+            at_ms(7, WebEvent::Resize { width: 80, height: 24 }),
+            at_ms(8, WebEvent::Paste("two\nlines \"quoted\"".to_string())),
+            at_ms(9, WebEvent::FocusIn),
+            at_ms(10, WebEvent::FocusOut),
+        ];
+        assert_eq!(events[0].to_json(), r#"{"t_us":1000,"type":"Key","key":"Up","modifiers":"Ctrl+Shift"}"#);
+        assert_eq!(events[3].to_json(), r#"{"t_us":4000,"type":"Mouse","action":"Drag","button":"Right","x":4,"y":7}"#);
+        for recorded in &events {
+            assert_eq!(Recorded::from_json(&recorded.to_json()).as_ref(), Ok(recorded));
+        }
+        // A plain press is written in its original form.
+        assert_eq!(at_ms(0, KeyEvent::from('a').into()).to_json(), r#"{"t_us":0,"type":"KeyPress","key":"a"}"#);
+
+        // Other writers: any field order, spaces, \u escapes, surrogate pairs.
+        let line = r#" { "key" : "\u00e9", "type": "KeyPress", "t_us": 7 } "#;
+        assert_eq!(Recorded::from_json(line), Ok(Recorded { at: Duration::from_micros(7), event: WebEvent::KeyPress('é') }));
+        let line = r#"{"t_us":0,"type":"KeyPress","key":"\ud83d\ude00"}"#;
+        assert_eq!(Recorded::from_json(line), Ok(at_ms(0, WebEvent::KeyPress('😀'))));
+        let line = r#"{"t_us":0,"type":"Paste","text":"\uD83D\uDE00 ok"}"#;
+        assert_eq!(Recorded::from_json(line), Ok(at_ms(0, WebEvent::Paste("😀 ok".to_string()))));
+    }
+
+    #[test]
+    fn parse_errors_check() {
+        let error = |line: &str| Recorded::from_json(line).unwrap_err().to_string();
+        assert_eq!(error(r#"{"t_us":0}"#), "missing field `type` at byte 10");
+        assert_eq!(error(r#"{"type":"PageLoad"}"#), "missing field `t_us` at byte 19");
+        assert_eq!(error(r#"{"t_us":0,"type":"Hover"}"#), "unknown event type `Hover` at byte 17");
+        assert_eq!(error(r#"{"t_us":0,"type":"Key","key":"Up","modifiers":"Ctrl+Hyper"}"#), "unknown modifiers `Ctrl+Hyper` at byte 46");
+        assert_eq!(error(r#"{"t_us":0,"type":"Mouse","action":"Press","x":1,"y":2}"#), "missing field `button` at byte 54");
+        assert_eq!(error(r#"{"t_us":0,"type":"KeyPress","key":"ab"}"#), "`key` must be one character at byte 34");
+        assert_eq!(error(r#"{"t_us":0,"type":"KeyPress"}"#), "missing field `key` at byte 28");
+        assert_eq!(error(r#"{"t_us":0,"type":"Click","x":1}"#), "missing field `y` at byte 31");
+        assert_eq!(error(r#"{"t_us":0,"type":"Click","x":"1","y":2}"#), "`x` must be an integer at byte 29");
+        assert_eq!(error(r#"{"t_us":-1,"type":"PageLoad"}"#), "`t_us` must be a time in microseconds at byte 8");
+        assert_eq!(error(r#"{"t_us":0,"type":"PageLoad","x":1}"#), "unknown field `x` at byte 32");
+        assert_eq!(error(r#"{"t_us":0,"t_us":1,"type":"PageLoad"}"#), "duplicate field `t_us` at byte 17");
+        assert_eq!(error(r#"{"t_us":0 "type":"PageLoad"}"#), "expected `,` or `}` at byte 10");
+        assert_eq!(error(r#"{"t_us":0,"type":"PageLoad"} x"#), "unexpected input after `}` at byte 29");
+        assert_eq!(error(r#"{"t_us":0,"type":"KeyPress","key":"\q"}"#), "expected a valid escape at byte 35");
+        // \u takes exactly four hex digits, and a surrogate only as half of a pair.
+        for key in [r#"\u+abc"#, r#"\u12"#, r#"\ud83d"#, r#"\ude00"#, r#"\ud83d\u0041"#, r#"\ud83dx"#] {
+            let line = format!(r#"{{"t_us":0,"type":"KeyPress","key":"{}"}}"#, key);
+            assert_eq!(error(&line), "bad \\u escape at byte 35", "{}", key);
+        }
+        assert_eq!(error("[]"), "expected `{` at byte 0");
+    }
+
+    #[test]
+    fn log_check() {
+        // What the Recorder writes is what parse_log reads.
+        let mut recorder = Recorder::new(Vec::new());
+        recorder.record_at(Duration::from_millis(3), WebEvent::PageLoad).unwrap();
+        recorder.record_at(Duration::from_millis(3), WebEvent::KeyPress('x')).unwrap();
+        recorder.record_at(Duration::from_millis(9), WebEvent::Click { x: 1, y: 2 }).unwrap();
+        let out_of_order = recorder.record_at(Duration::from_millis(8), WebEvent::PageLoad);
+        assert_eq!(out_of_order.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        let text = String::from_utf8(recorder.into_inner()).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        let events = parse_log(&text).unwrap();
+        assert_eq!(events[2], at_ms(9, WebEvent::Click { x: 1, y: 2 }));
+
+        // Live stamps come from Instant, so they never decrease.
+        let mut recorder = Recorder::new(Vec::new());
+        for c in "live".chars() {
+            recorder.record(WebEvent::KeyPress(c)).unwrap();
+        }
+        let live = parse_log(&String::from_utf8(recorder.into_inner()).unwrap()).unwrap();
+        assert!(live.windows(2).all(|pair| pair[0].at <= pair[1].at));
+
+        // Errors carry the line number; blank lines count but are skipped.
+        let log = "{\"t_us\":5,\"type\":\"PageLoad\"}\n\n{\"t_us\":4,\"type\":\"PageLoad\"}\n";
+        assert_eq!(parse_log(log).unwrap_err().to_string(), "line 3: time goes backwards at byte 0");
+        let log = "{\"t_us\":5,\"type\":\"PageLoad\"}\n{\"t_us\":6,\"type\":\"Keypress\"}\n";
+        assert_eq!(parse_log(log).unwrap_err().to_string(), "line 2: unknown event type `Keypress` at byte 17");
+        assert_eq!(parse_log("\n  \n"), Ok(Vec::new()));
+
+        assert_eq!(parse_event("click -3 4"), Some(WebEvent::Click { x: -3, y: 4 }));
+        assert_eq!(parse_event("key space"), Some(WebEvent::KeyPress(' ')));
+        assert_eq!(parse_event("key ab"), None);
+        assert_eq!(parse_speed("0.5"), Speed::scaled(0.5));
+        assert_eq!(parse_speed("0"), None);
+    }
+
+    // Replay's clock in tests: sleeping just moves the time forward, so the checks
+    // see every wait exactly, however busy the machine running them is.
+    #[derive(Default)]
+    struct FakeClock {
+        now: Duration,
+        sleeps: Vec<Duration>,
+    }
+
+    impl Clock for FakeClock {
+        fn elapsed(&self) -> Duration {
+            self.now
+        }
+
+        fn sleep(&mut self, duration: Duration) {
+            self.now += duration;
+            self.sleeps.push(duration);
+        }
+    }
+
+    #[test]
+    fn replay_check() {
+        let scaled = |factor| Speed::scaled(factor).unwrap();
+        assert_eq!(Speed::Original.deadline(Duration::from_secs(3)), Duration::from_secs(3));
+        assert_eq!(scaled(2.0).deadline(Duration::from_secs(3)), Duration::from_millis(1500));
+        assert_eq!(scaled(0.5).deadline(Duration::from_secs(3)), Duration::from_secs(6));
+        assert_eq!(Speed::Fastest.deadline(Duration::from_secs(3)), Duration::ZERO);
+        // Factors that would never finish are rejected where the Speed is made, not only by the CLI.
+        for factor in [0.0, -0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(Speed::scaled(factor), None, "{}", factor);
+        }
+        // --speed=1e-300 is a valid factor; its deadlines saturate instead of panicking.
+        let crawl = parse_speed("1e-300").unwrap();
+        assert_eq!(crawl.deadline(Duration::from_secs(3)), Duration::MAX);
+        assert_eq!(crawl.deadline(Duration::ZERO), Duration::ZERO);
+        assert_eq!(scaled(1e300).deadline(Duration::MAX), Duration::ZERO);
+
+        let events = parse_log(
+            "{\"t_us\":0,\"type\":\"PageLoad\"}\n\
+             {\"t_us\":10000,\"type\":\"KeyPress\",\"key\":\"\\u001b\"}\n\
+             {\"t_us\":20000,\"type\":\"KeyPress\",\"key\":\"q\"}\n\
+             {\"t_us\":40000,\"type\":\"Click\",\"x\":3,\"y\":4}\n",
+        )
+        .unwrap();
+
+        // Every speed gives the handlers the same events in the same order;
+        // Escape is consumed by the first KeyPress handler, as it was live.
+        let run = |speed: Speed| {
+            let seen = Rc::new(RefCell::new(Vec::new()));
+            let mut dispatcher = Dispatcher::new();
+            let log = Rc::clone(&seen);
+            dispatcher.on(EventKind::KeyPress, move |event| {
+                log.borrow_mut().push(format!("{:?}", event));
+                match event {
+                    WebEvent::KeyPress('\u{1b}') => Propagation::Stop,
+                    _ => Propagation::Continue,
+                }
+            });
+            let log = Rc::clone(&seen);
+            dispatcher.on_any(move |event| {
+                log.borrow_mut().push(format!("any {:?}", event.kind()));
+                Propagation::Continue
+            });
+            let mut clock = FakeClock::default();
+            let calls = replay_with(&events, speed, &mut dispatcher, &mut clock);
+            let seen = seen.borrow().clone();
+            (calls, seen, clock.sleeps)
+        };
+        let ms = Duration::from_millis;
+        let (calls, fastest, sleeps) = run(Speed::Fastest);
+        assert_eq!(calls, 5);
+        assert_eq!(fastest, ["any PageLoad", "KeyPress('\\u{1b}')", "KeyPress('q')", "any KeyPress", "any Click"]);
+        assert_eq!(sleeps, []);
+
+        let (_, original, sleeps) = run(Speed::Original);
+        assert_eq!(original, fastest);
+        assert_eq!(sleeps, [ms(10), ms(10), ms(20)]);
+        let (_, doubled, sleeps) = run(scaled(2.0));
+        assert_eq!(doubled, fastest);
+        assert_eq!(sleeps, [ms(5), ms(5), ms(10)]);
+
+        // Deadlines count from the start: time spent between events is not waited again.
+        let mut clock = FakeClock { now: ms(15), sleeps: Vec::new() };
+        replay_with(&events, Speed::Original, &mut Dispatcher::new(), &mut clock);
+        assert_eq!(clock.sleeps, [ms(5), ms(20)]);
+    }
+}
@@ -0,0 +1,324 @@
+/*
+Exact arithmetic — BigInt (bigint.rs) and Rational (rational.rs) in action
+
+$ rustc -O exact.rs
+$ ./exact
+2^256 = 115792089237316195423570985008687907853269984665640564039457584007913129639936
+      = 0x10000000000000000000000000000000000000000000000000000000000000000
+30! = 265252859812191058636308480000000
+1/7 = 0.(142857)
+1/3 + 1/6 = 1/2
+0.1 in binary = 0.0(0011)
+
+Both implement `Number`, so Operation::run and the expression evaluator take them
+like any primitive type:
+
+$ ./evaluate --type=bigint "pow(2, 100) + 1"
+pow(2, 100) + 1 = 1267650600228229401496703205377
+$ ./evaluate --type=rational "1 / 3 - 1 / 2"
+1 / 3 - 1 / 2 = -1/6
+
+✅ BigInt is checked against i128 / u128 on random operands, and against
+   algebraic identities (q·d + r == n, ...) on operands hundreds of limbs long.
+✅ Every base 2..=36 round-trips through to_str_radix / from_str_radix.
+⚠️ Size is only limited by bigint::MAX_BITS; beyond it every Mode reports overflow.
+
+$ rustc --test exact.rs && ./exact        // the tests
+*/
+
+mod bigint;
+mod expression;
+mod operation;
+mod rational;
+#[cfg(test)]
+mod testing;
+
+use bigint::BigInt;
+use operation::Operation;
+use rational::Rational;
+
+fn main() {
+    let two_256 = Operation::Pow.run(BigInt::from(2), BigInt::from(256)).unwrap();
+    println!("2^256 = {}", two_256);
+    println!("      = {:#x}", two_256);
+    println!("30! = {}", factorial(30));
+    println!("1/7 = {}", "1/7".parse::<Rational>().unwrap().to_positional(10, 50));
+    println!("1/3 + 1/6 = {}", Operation::Add.run(rational("1/3"), rational("1/6")).unwrap());
+    println!("0.1 in binary = {}", rational("0.1").to_positional(2, 50));
+}
+
+fn factorial(n: u32) -> BigInt {
+    (1..=n).fold(BigInt::one(), |product, k| &product * &BigInt::from(k))
+}
+
+fn rational(text: &str) -> Rational {
+    text.parse().unwrap()
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expression::parse;
+    use operation::{Mode, OpError};
+    use testing::{next_random, SEED};
+
+    // A random BigInt of up to `limbs` 32-bit limbs, either sign.
+    fn random_bigint(state: &mut u64, limbs: usize) -> BigInt {
+        let len = next_random(state) as usize % (limbs + 1);
+        let mut value = BigInt::zero();
+        for _ in 0..len {
+            value = &(&value * &BigInt::from(1u64 << 32)) + &BigInt::from(next_random(state) as u32);
+        }
+        if next_random(state).is_multiple_of(2) {
+            -value
+        } else {
+            value
+        }
+    }
+
+    // BigInt results must be the exact i128 results, which never overflow for i64 operands.
+    #[test]
+    fn bigint_differential_check() {
+        let mut state = SEED;
+        let interesting = [0, 1, -1, 2, -2, 31, 32, 33, 63, 64, i64::MAX, i64::MIN, u32::MAX as i64];
+        for i in 0..20_000 {
+            let mut operand = || match next_random(&mut state) % 4 {
+                0 => interesting[next_random(&mut state) as usize % interesting.len()],
+                1 => next_random(&mut state) as i64 % 100,
+                _ => next_random(&mut state) as i64 >> (next_random(&mut state) % 64),
+            };
+            let (x, y) = (operand(), operand());
+            let op = Operation::ALL[i % Operation::ALL.len()];
+            let (wide_x, wide_y) = (x as i128, y as i128);
+            let expected = match op {
+                Operation::Pow if !(0..=2).contains(&y) => continue,
+                Operation::Shl if !(0..64).contains(&y) => continue,
+                Operation::Shr if y < 0 => continue,
+                Operation::Shr => Ok(wide_x >> y.min(127)),
+                _ => op.run(wide_x, wide_y),
+            };
+            let actual = op.run(BigInt::from(x), BigInt::from(y)).map(|value| value.to_i128().unwrap());
+            assert_eq!(actual, expected, "{:?}({}, {})", op, x, y);
+        }
+
+        // Division by multi-limb divisors, against u128.
+        for _ in 0..20_000 {
+            let x = ((next_random(&mut state) as u128) << 64 | next_random(&mut state) as u128) >> (next_random(&mut state) % 64);
+            let y = ((next_random(&mut state) as u128) << 64 | next_random(&mut state) as u128) >> (next_random(&mut state) % 128);
+            if y == 0 {
+                continue;
+            }
+            let (quotient, remainder) = BigInt::from(x as u64).div_rem(&BigInt::from(y as u64)).unwrap();
+            assert_eq!(quotient.to_i128(), Some((x as u64 / y as u64) as i128));
+            assert_eq!(remainder.to_i128(), Some((x as u64 % y as u64) as i128));
+            let (big_x, big_y) = (u128_to_bigint(x), u128_to_bigint(y));
+            let (quotient, remainder) = big_x.div_rem(&big_y).unwrap();
+            assert_eq!(quotient, u128_to_bigint(x / y), "{} / {}", x, y);
+            assert_eq!(remainder, u128_to_bigint(x % y), "{} % {}", x, y);
+        }
+    }
+
+    fn u128_to_bigint(value: u128) -> BigInt {
+        &(&BigInt::from((value >> 64) as u64) * &BigInt::from(1u64 << 32).pow(2)) + &BigInt::from(value as u64)
+    }
+
+    // Identities that must hold for operands far too big for any primitive.
+    #[test]
+    fn bigint_identity_check() {
+        let mut state = SEED;
+        for _ in 0..2_000 {
+            let (a, b) = (random_bigint(&mut state, 40), random_bigint(&mut state, 20));
+            assert_eq!(&(&a + &b) - &b, a);
+            assert_eq!(&(&a - &b) + &b, a);
+            assert_eq!(&a * &b, &b * &a);
+            if !b.is_zero() {
+                assert_eq!(&(&a * &b) / &b, a);
+                let (quotient, remainder) = a.div_rem(&b).unwrap();
+                assert_eq!(&(&quotient * &b) + &remainder, a);
+                assert!(remainder.abs() < b.abs());
+                assert!(remainder.is_zero() || remainder.is_negative() == a.is_negative());
+            }
+
+            let run = |op: Operation, x: &BigInt, y: &BigInt| op.run(x.clone(), y.clone()).unwrap();
+            let (and, or, xor) = (run(Operation::BitAnd, &a, &b), run(Operation::BitOr, &a, &b), run(Operation::BitXor, &a, &b));
+            assert_eq!(xor, &or - &and);
+            assert_eq!(&and + &or, &a + &b);
+            assert_eq!(run(Operation::BitXor, &xor, &b), a);
+
+            let n = BigInt::from(next_random(&mut state) % 200);
+            let shifted = run(Operation::Shl, &a, &n);
+            assert_eq!(shifted, &a * &BigInt::from(2).pow(n.to_u64().unwrap()));
+            assert_eq!(run(Operation::Shr, &shifted, &n), a);
+            // >> floors, so it matches division only for non-negative values.
+            if !a.is_negative() {
+                assert_eq!(run(Operation::Shr, &a, &n), &a / &BigInt::from(2).pow(n.to_u64().unwrap()));
+            }
+        }
+        assert_eq!(factorial(30).to_string(), "265252859812191058636308480000000");
+        assert_eq!(&factorial(100) / &factorial(98), BigInt::from(9900));
+        assert_eq!(factorial(60).gcd(&BigInt::from(1u64 << 63)), BigInt::from(1u64 << 56));
+    }
+
+    #[test]
+    fn bigint_text_check() {
+        let two_100 = BigInt::from(2).pow(100);
+        assert_eq!(two_100.to_string(), "1267650600228229401496703205376");
+        assert_eq!(BigInt::from(2).pow(1000).to_string().len(), 302);
+        assert_eq!(format!("{:x}", two_100), format!("1{}", "0".repeat(25)));
+        assert_eq!(format!("{:#b}", BigInt::from(-5)), "-0b101");
+        assert_eq!(format!("{:#o}", BigInt::from(8)), "0o10");
+        assert_eq!(format!("{:X}", BigInt::from(255)), "FF");
+        assert_eq!(format!("{:>6}|{:<6}|{:+}", BigInt::from(42), BigInt::from(-7), BigInt::from(3)), "    42|-7    |+3");
+        assert_eq!(format!("{:06}", BigInt::from(-42)), "-00042");
+
+        assert_eq!("-0xff".parse(), Ok(BigInt::from(-255)));
+        assert_eq!("0b1010_1010".parse(), Ok(BigInt::from(170)));
+        assert_eq!("+1_000_000".parse(), Ok(BigInt::from(1_000_000)));
+        assert_eq!("-0".parse(), Ok(BigInt::zero()));
+        for bad in ["", "-", "0x", "12a", "_1", "--1", "0x-1", "1.5"] {
+            assert!(bad.parse::<BigInt>().is_err(), "{:?}", bad);
+        }
+        assert_eq!("12a".parse::<BigInt>().unwrap_err().to_string(), "invalid integer `12a`");
+
+        let mut state = 0x1234_5678_9abc_def1;
+        for radix in 2..=36 {
+            for _ in 0..20 {
+                let value = random_bigint(&mut state, 12);
+                let text = value.to_str_radix(radix);
+                assert_eq!(BigInt::from_str_radix(&text, radix), Ok(value.clone()), "{} in base {}", text, radix);
+                assert_eq!(BigInt::from_str_radix(&text.to_uppercase(), radix), Ok(value));
+            }
+        }
+        assert_eq!(BigInt::from(35).to_str_radix(36), "z");
+        assert_eq!(BigInt::from(-36).to_str_radix(36), "-10");
+        assert_eq!(BigInt::from(i64::MIN).to_str_radix(16), format!("-{:x}", i64::MIN.unsigned_abs()));
+    }
+
+    // Results only "overflow" past MAX_BITS, and then in every mode.
+    #[test]
+    fn bigint_limit_check() {
+        let big = |n: i64| BigInt::from(n);
+        for mode in [Mode::Checked, Mode::Wrapping, Mode::Saturating] {
+            assert_eq!(Operation::Pow.run_with(big(3), big(1_000_000_000), mode), Err(OpError::Overflow));
+            assert_eq!(Operation::Shl.run_with(big(1), big(-1), mode), Err(OpError::NegativeShift));
+            assert_eq!(Operation::Shl.run_with(big(1), big(i64::MAX), mode), Err(OpError::Overflow));
+            assert_eq!(Operation::Add.run_with(big(i64::MAX), big(1), mode), Ok(&big(i64::MAX) + &big(1)));
+        }
+        assert_eq!(Operation::Pow.run(big(-1), big(i64::MAX)), Ok(big(-1)));
+        assert_eq!(Operation::Pow.run(big(-1), big(i64::MAX - 1)), Ok(big(1)));
+        assert_eq!(Operation::Pow.run(big(0), big(0)), Ok(big(1)));
+        assert_eq!(Operation::Pow.run(big(2), big(-1)), Err(OpError::NegativeExponent));
+        assert_eq!(Operation::Divide.run(big(1), big(0)), Err(OpError::DivideByZero));
+        assert_eq!(Operation::Shr.run(big(-5), big(1)), Ok(big(-3)));
+        assert_eq!(Operation::Shr.run(big(-1), big(1_000)), Ok(big(-1)));
+        assert_eq!(Operation::BitAnd.run(big(-1), big(12345)), Ok(big(12345)));
+        let largest = Operation::Shl.run(big(1), big(bigint::MAX_BITS as i64 - 1)).unwrap();
+        assert_eq!(largest.bits(), bigint::MAX_BITS);
+        assert_eq!(Operation::Add.run(largest.clone(), largest), Err(OpError::Overflow));
+    }
+
+    #[test]
+    fn rational_check() {
+        let run = |op: Operation, x: &str, y: &str| op.run(rational(x), rational(y));
+        assert_eq!(Rational::new(BigInt::from(6), BigInt::from(-4)).unwrap().to_string(), "-3/2");
+        assert_eq!(Rational::new(BigInt::from(1), BigInt::zero()), None);
+        assert_eq!(rational("-6/4"), rational("-3/2"));
+        assert_eq!(rational("10/5").to_string(), "2");
+        assert_eq!(run(Operation::Add, "1/3", "1/6"), Ok(rational("1/2")));
+        assert_eq!(run(Operation::Subtract, "1/3", "1/2"), Ok(rational("-1/6")));
+        assert_eq!(run(Operation::Multiply, "2/3", "9/4"), Ok(rational("3/2")));
+        assert_eq!(run(Operation::Divide, "2/3", "4/9"), Ok(rational("3/2")));
+        assert_eq!(run(Operation::Divide, "1", "0"), Err(OpError::DivideByZero));
+        assert_eq!(run(Operation::Remainder, "7/2", "1"), Ok(rational("1/2")));
+        assert_eq!(run(Operation::Remainder, "-7/2", "1"), Ok(rational("-1/2")));
+        assert_eq!(run(Operation::Remainder, "-7", "2"), Ok(rational("-1"))); // like i32
+        assert_eq!(run(Operation::Pow, "2/3", "-2"), Ok(rational("9/4")));
+        assert_eq!(run(Operation::Pow, "-1/2", "3"), Ok(rational("-1/8")));
+        assert_eq!(run(Operation::Pow, "0", "-1"), Err(OpError::DivideByZero));
+        assert_eq!(run(Operation::Pow, "4", "1/2"), Err(OpError::Unsupported));
+        assert_eq!(run(Operation::Pow, "-1", "-99999999999999999999"), Ok(rational("-1")));
+        assert_eq!(run(Operation::Pow, "3/2", "99999999999999999999"), Err(OpError::Overflow));
+        assert_eq!(run(Operation::Min, "1/3", "2/7"), Ok(rational("2/7")));
+        assert_eq!(run(Operation::Max, "-1/3", "-2/7"), Ok(rational("-2/7")));
+        assert_eq!(run(Operation::BitAnd, "1", "1"), Err(OpError::Unsupported));
+        assert_eq!(run(Operation::Shl, "1", "1"), Err(OpError::Unsupported));
+
+        // (a/b + c/d) - c/d == a/b, on random fractions.
+        let mut state = 0xdead_beef_cafe_f00d;
+        for _ in 0..1_000 {
+            let mut fraction = || loop {
+                let denom = random_bigint(&mut state, 3);
+                if let Some(value) = Rational::new(random_bigint(&mut state, 3), denom) {
+                    break value;
+                }
+            };
+            let (x, y) = (fraction(), fraction());
+            assert_eq!(&(&x + &y) - &y, x);
+            assert!(x.denom() > &BigInt::zero() && x.numer().gcd(x.denom()) == BigInt::one());
+            if !y.is_zero() {
+                assert_eq!(&(&x / &y) * &y, x);
+                let remainder = Operation::Remainder.run(x.clone(), y.clone()).unwrap();
+                assert!(remainder.is_zero() || remainder.is_negative() == x.is_negative());
+            }
+            assert_eq!(x.clone().max(y.clone()) == x, x >= y);
+        }
+    }
+
+    #[test]
+    fn rational_text_check() {
+        assert_eq!(rational("1.25"), rational("5/4"));
+        assert_eq!(rational("-0.5"), rational("-1/2"));
+        assert_eq!(rational("0.(3)"), rational("1/3"));
+        assert_eq!(rational("0.1(6)"), rational("1/6"));
+        assert_eq!(rational("3.(142857)"), rational("22/7"));
+        assert_eq!(rational("0x1f/3"), rational("31/3"));
+        assert_eq!(rational("0b0.1"), rational("1/2"));
+        for bad in ["", "1/", "/2", "1/0", "1.", ".5", "1/-2", "0.(", "1.2.3", "--1"] {
+            assert!(bad.parse::<Rational>().is_err(), "{:?}", bad);
+        }
+        assert_eq!("1/0".parse::<Rational>().unwrap_err().to_string(), "invalid fraction `1/0`");
+
+        assert_eq!(format!("[{:>6}]", rational("-3/4")), "[  -3/4]");
+        assert_eq!(rational("255/16").to_str_radix(16), "ff/10");
+        assert_eq!(rational("1/4").to_positional(10, 20), "0.25");
+        assert_eq!(rational("-7/2").to_positional(10, 20), "-3.5");
+        assert_eq!(rational("1/6").to_positional(10, 20), "0.1(6)");
+        assert_eq!(rational("22/7").to_positional(10, 20), "3.(142857)");
+        assert_eq!(rational("1/3").to_positional(3, 20), "0.1");
+        assert_eq!(rational("1/10").to_positional(2, 20), "0.0(0011)");
+        assert_eq!(rational("1/97").to_positional(10, 5), "0.01030…");
+
+        // Every expansion reads back as the same fraction, in every base.
+        let mut state = 0x0123_4567_89ab_cdef;
+        for radix in 2..=36 {
+            for _ in 0..10 {
+                let denom = BigInt::from(next_random(&mut state) % 60 + 1);
+                let value = Rational::new(random_bigint(&mut state, 2), denom).unwrap();
+                let positional = value.to_positional(radix, 100);
+                assert_eq!(Rational::from_str_radix(&positional, radix), Ok(value.clone()), "{} in base {}", positional, radix);
+                assert_eq!(Rational::from_str_radix(&value.to_str_radix(radix), radix), Ok(value));
+            }
+        }
+    }
+
+    // The expression evaluator works on the exact types just like on i32.
+    #[test]
+    fn evaluator_check() {
+        let expr = parse("pow(2, 100) + 1").unwrap();
+        assert_eq!(expr.eval::<BigInt>().unwrap().to_string(), "1267650600228229401496703205377");
+        assert_eq!(expr.eval::<i32>().unwrap_err().message, "arithmetic overflow");
+
+        let big_literal = parse("123456789012345678901234567890 * 0x1_0000_0000").unwrap();
+        assert_eq!(big_literal.eval::<BigInt>(), Ok(&"123456789012345678901234567890".parse().unwrap() * &BigInt::from(1u64 << 32)));
+        let err = big_literal.eval::<i64>().unwrap_err();
+        assert_eq!(err.message, "literal `123456789012345678901234567890` does not fit in i64");
+
+        let exact = parse("1 / 3 - 1 / 2").unwrap();
+        assert_eq!(exact.eval::<Rational>(), Ok(rational("-1/6")));
+        assert_eq!(exact.eval::<i32>(), Ok(0));
+        assert_eq!(parse("-pow(2 / 3, 0 - 2)").unwrap().eval::<Rational>(), Ok(rational("-9/4")));
+        assert_eq!(parse("1 / (2 - 2)").unwrap().eval::<Rational>().unwrap_err().span.start, 4);
+        assert_eq!(parse("1 << 2").unwrap().eval::<Rational>().unwrap_err().message, "operation not supported for this type");
+    }
+}
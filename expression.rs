@@ -0,0 +1,636 @@
+/*
+Infix expressions over `Operation` — lexer, parser, evaluator
+
+"3 + 2 * (4 - 1) % 5"
+        │ lex
+        ▼
+[3] [+] [2] [*] [(] [4] [-] [1] [)] [%] [5]        tokens, each with its byte span
+        │ parse (precedence climbing)
+        ▼
+        Add                                        AST: every operator node is an Operation
+       /   \
+      3    Remainder
+           /      \
+      Multiply     5
+       /   \
+      2   Subtract
+           /  \
+          4    1
+        │ eval
+        ▼
+        4
+
+Precedence and associativity come from `Operation::precedence` (operation.rs),
+which follows the operator table in Primitives.rs. Integer literals accept the
+formats from Primitives.rs too: 42, 1_000, 0xff, 0o77, 0b1010.
+
+The evaluator works on any `Number` (eval::<i32>, eval::<BigInt>, ...). Literals
+keep their digits until then, so `2^100`-sized literals are fine for BigInt and
+only an error ("literal does not fit in i32") for the types they overflow.
+
+On top of expressions sit the statements used by the calculator REPL, with the
+binding syntax from `variable_bindings` (1.rs) and Variable_Binging.rs:
+
+let x = 5;  let mut y = 10;  y = 20;  let a;  a = 50;  { let a = 100; a }
+
+`{` and `}` are statements of their own, so a block may span several REPL lines.
+
+Every error carries the span of the input it is about, and `render` points at it:
+
+error: division by zero
+  |  10 / (3 - 3)
+  |       ^^^^^^^
+*/
+
+#![allow(dead_code)]
+
+use std::fmt;
+
+use crate::operation::{Mode, Number, OpError, Operation};
+
+// Byte range `start..end` in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    // The smallest span covering both.
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Error {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Error { message: message.into(), span }
+    }
+
+    // The message plus the source line with the bad span underlined.
+    // Multi-line sources also get a `--> line:column` pointer.
+    pub fn render(&self, source: &str) -> String {
+        let at = self.span.start.min(source.len());
+        let line_start = source[..at].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[at..].find('\n').map_or(source.len(), |i| at + i);
+        let line = &source[line_start..line_end];
+        let column = source[line_start..at].chars().count();
+        let width = source
+            .get(at..self.span.end.min(line_end))
+            .map_or(1, |text| text.chars().count().max(1));
+        let location = if source.contains('\n') {
+            format!("\n  --> {}:{}", source[..at].matches('\n').count() + 1, column + 1)
+        } else {
+            String::new()
+        };
+        format!("error: {}{}\n  |  {}\n  |  {}{}", self.message, location, line, " ".repeat(column), "^".repeat(width))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at {}..{}", self.message, self.span.start, self.span.end)
+    }
+}
+
+impl std::error::Error for Error {}
+
+// ---------- 1. LEXER ----------
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Number(Literal),
+    Ident(String),
+    Op(Operation),
+    LParen,
+    RParen,
+    Comma,
+    Assign,
+    Semicolon,
+    LBrace,
+    RBrace,
+    Eof,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenKind::Number(n) => write!(f, "{}", n),
+            TokenKind::Ident(name) => write!(f, "{}", name),
+            TokenKind::Op(op) => write!(f, "{}", op),
+            TokenKind::LParen => write!(f, "("),
+            TokenKind::RParen => write!(f, ")"),
+            TokenKind::Comma => write!(f, ","),
+            TokenKind::Assign => write!(f, "="),
+            TokenKind::Semicolon => write!(f, ";"),
+            TokenKind::LBrace => write!(f, "{{"),
+            TokenKind::RBrace => write!(f, "}}"),
+            TokenKind::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+pub fn tokenize(source: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        if c.is_ascii_alphanumeric() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let text = &source[start..end];
+            let span = Span::new(start, end);
+            let kind = if c.is_ascii_digit() {
+                TokenKind::Number(Literal::parse(text).ok_or_else(|| Error::new(format!("invalid number `{}`", text), span))?)
+            } else {
+                TokenKind::Ident(text.to_string())
+            };
+            tokens.push(Token { kind, span });
+            continue;
+        }
+
+        chars.next();
+        let two_char = |next: char, chars: &mut std::iter::Peekable<std::str::CharIndices>| {
+            chars.next_if(|&(_, c)| c == next).is_some()
+        };
+        let (kind, len) = match c {
+            '+' => (TokenKind::Op(Operation::Add), 1),
+            '-' => (TokenKind::Op(Operation::Subtract), 1),
+            '*' => (TokenKind::Op(Operation::Multiply), 1),
+            '/' => (TokenKind::Op(Operation::Divide), 1),
+            '%' => (TokenKind::Op(Operation::Remainder), 1),
+            '&' => (TokenKind::Op(Operation::BitAnd), 1),
+            '|' => (TokenKind::Op(Operation::BitOr), 1),
+            '^' => (TokenKind::Op(Operation::BitXor), 1),
+            '<' if two_char('<', &mut chars) => (TokenKind::Op(Operation::Shl), 2),
+            '>' if two_char('>', &mut chars) => (TokenKind::Op(Operation::Shr), 2),
+            '(' => (TokenKind::LParen, 1),
+            ')' => (TokenKind::RParen, 1),
+            ',' => (TokenKind::Comma, 1),
+            '=' => (TokenKind::Assign, 1),
+            ';' => (TokenKind::Semicolon, 1),
+            '{' => (TokenKind::LBrace, 1),
+            '}' => (TokenKind::RBrace, 1),
+            _ => return Err(Error::new(format!("unexpected character `{}`", c), Span::new(start, start + c.len_utf8()))),
+        };
+        tokens.push(Token { kind, span: Span::new(start, start + len) });
+    }
+
+    tokens.push(Token { kind: TokenKind::Eof, span: Span::new(source.len(), source.len()) });
+    Ok(tokens)
+}
+
+// An integer literal, not yet converted to any type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Literal {
+    pub digits: String, // without prefix or `_`
+    pub radix: u32,
+}
+
+impl Literal {
+    // Decimal, 0x hex, 0o octal or 0b binary, with optional `_` separators.
+    pub fn parse(text: &str) -> Option<Literal> {
+        let digits = text.replace('_', "");
+        let (radix, body) = match digits.get(..2) {
+            Some("0x") => (16, &digits[2..]),
+            Some("0o") => (8, &digits[2..]),
+            Some("0b") => (2, &digits[2..]),
+            _ => (10, &digits[..]),
+        };
+        if body.is_empty() || !body.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+        Some(Literal { digits: body.to_string(), radix })
+    }
+
+    // The value as a `T`, or an error pointing at `span` if it doesn't fit.
+    pub fn value<T: Number>(&self, span: Span) -> Result<T, Error> {
+        T::from_digits(&self.digits, self.radix)
+            .ok_or_else(|| Error::new(format!("literal `{}` does not fit in {}", self, T::NAME), span))
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let prefix = match self.radix {
+            16 => "0x",
+            8 => "0o",
+            2 => "0b",
+            _ => "",
+        };
+        write!(f, "{}{}", prefix, self.digits)
+    }
+}
+
+// ---------- 2. AST ----------
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Number(Literal),
+    Variable(String),
+    // Unary minus: evaluated as `0 - operand`, so `-i32::MIN` overflows like the rest.
+    Negate(Box<Expr>),
+    Binary(Operation, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn number(literal: Literal, span: Span) -> Self {
+        Expr { kind: ExprKind::Number(literal), span }
+    }
+
+    pub fn variable(name: impl Into<String>, span: Span) -> Self {
+        Expr { kind: ExprKind::Variable(name.into()), span }
+    }
+
+    pub fn negate(operand: Expr, span: Span) -> Self {
+        Expr { kind: ExprKind::Negate(Box::new(operand)), span }
+    }
+
+    pub fn binary(op: Operation, left: Expr, right: Expr, span: Span) -> Self {
+        Expr { kind: ExprKind::Binary(op, Box::new(left), Box::new(right)), span }
+    }
+
+    // Expr has a Drop impl, so its fields can't be moved out; this takes the kind instead.
+    pub fn into_kind(mut self) -> ExprKind {
+        std::mem::replace(&mut self.kind, ExprKind::Variable(String::new()))
+    }
+}
+
+// The default drop recurses once per level, and a tree built in code (or a
+// long chain from symbolic.rs) can be deeper than the stack. Children are
+// detached onto a heap stack first, so each Expr drops as a leaf.
+impl Drop for Expr {
+    fn drop(&mut self) {
+        let mut pending = Vec::new();
+        let detach = |kind: &mut ExprKind, pending: &mut Vec<Box<Expr>>| {
+            match std::mem::replace(kind, ExprKind::Variable(String::new())) {
+                ExprKind::Negate(operand) => pending.push(operand),
+                ExprKind::Binary(_, left, right) => pending.extend([left, right]),
+                ExprKind::Number(_) | ExprKind::Variable(_) => {}
+            }
+        };
+        if matches!(self.kind, ExprKind::Negate(_) | ExprKind::Binary(..)) {
+            detach(&mut self.kind, &mut pending);
+        }
+        while let Some(mut expr) = pending.pop() {
+            detach(&mut expr.kind, &mut pending);
+        }
+    }
+}
+
+// ---------- 3. PARSER ----------
+// Deeper nesting than this is rejected instead of risking a stack overflow in
+// the passes that walk the tree (ir.rs, symbolic.rs, rpn.rs, ...). Every
+// operator counts, not just parentheses: 1 + 1 + 1 is a tree three levels deep.
+const MAX_DEPTH: usize = 256;
+
+pub fn parse(source: &str) -> Result<Expr, Error> {
+    let mut parser = Parser { tokens: tokenize(source)?, pos: 0, depth: 0 };
+    let expr = parser.expression(0)?;
+    parser.expect(&TokenKind::Eof, "an operator or end of input")?;
+    Ok(expr)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, kind: &TokenKind, what: &str) -> Result<Token, Error> {
+        let token = self.peek();
+        if &token.kind == kind {
+            return Ok(self.advance());
+        }
+        Err(Error::new(format!("expected {}, found `{}`", what, token.kind), token.span))
+    }
+
+    // Precedence climbing: parse operands joined by operators that bind tighter than `min_precedence`.
+    fn expression(&mut self, min_precedence: u8) -> Result<Expr, Error> {
+        let depth = self.depth;
+        self.deeper(self.peek().span)?;
+        let mut left = self.unary()?;
+        while let TokenKind::Op(op) = self.peek().kind {
+            let precedence = op.precedence().expect("infix tokens always have a precedence");
+            if precedence <= min_precedence {
+                break;
+            }
+            // Each operator puts everything parsed so far one level further down.
+            let operator = self.advance();
+            self.deeper(operator.span)?;
+            // Left-associative: the right side only takes operators that bind tighter.
+            let right = self.expression(precedence)?;
+            let span = left.span.to(right.span);
+            left = Expr::binary(op, left, right, span);
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn deeper(&mut self, span: Span) -> Result<(), Error> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(Error::new("expression nested too deeply", span));
+        }
+        Ok(())
+    }
+
+    fn unary(&mut self) -> Result<Expr, Error> {
+        if self.peek().kind == TokenKind::Op(Operation::Subtract) {
+            let minus = self.advance();
+            self.deeper(minus.span)?;
+            let operand = self.unary()?;
+            self.depth -= 1;
+            let span = minus.span.to(operand.span);
+            return Ok(Expr::negate(operand, span));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        let token = self.advance();
+        match token.kind {
+            TokenKind::Number(literal) => Ok(Expr::number(literal, token.span)),
+            TokenKind::LParen => {
+                let mut inner = self.expression(0)?;
+                let close = self.expect(&TokenKind::RParen, "`)`")?;
+                inner.span = token.span.to(close.span);
+                Ok(inner)
+            }
+            TokenKind::Ident(name) => match Operation::from_symbol(&name) {
+                Some(op) if op.precedence().is_none() => self.call(op, token.span),
+                _ if self.peek().kind == TokenKind::LParen => {
+                    Err(Error::new(format!("cannot find function `{}`", name), token.span))
+                }
+                _ if is_keyword(&name) => {
+                    Err(Error::new(format!("expected an expression, found keyword `{}`", name), token.span))
+                }
+                _ => Ok(Expr::variable(name, token.span)),
+            },
+            other => Err(Error::new(format!("expected a number or `(`, found `{}`", other), token.span)),
+        }
+    }
+
+    // pow(a, b), min(a, b), max(a, b)
+    fn call(&mut self, op: Operation, name_span: Span) -> Result<Expr, Error> {
+        self.expect(&TokenKind::LParen, &format!("`(` after `{}`", op))?;
+        let left = self.expression(0)?;
+        self.expect(&TokenKind::Comma, "`,`")?;
+        let right = self.expression(0)?;
+        let close = self.expect(&TokenKind::RParen, "`)`")?;
+        Ok(Expr::binary(op, left, right, name_span.to(close.span)))
+    }
+}
+
+fn is_keyword(name: &str) -> bool {
+    matches!(name, "let" | "mut")
+}
+
+// ---------- 4. STATEMENTS ----------
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    // let x;  let x = 1;  let mut x = 1;
+    Let { name: String, mutable: bool, value: Option<Expr>, span: Span },
+    // x = 1;
+    Assign { name: String, value: Expr, span: Span },
+    Expr(Expr),
+    BlockStart(Span),
+    BlockEnd(Span),
+}
+
+// Statements are separated by `;`. `{` and `}` need no separator around them.
+pub fn parse_statements(source: &str) -> Result<Vec<Stmt>, Error> {
+    let mut parser = Parser { tokens: tokenize(source)?, pos: 0, depth: 0 };
+    let mut statements = Vec::new();
+    loop {
+        let token = parser.peek().clone();
+        match token.kind {
+            TokenKind::Eof => break,
+            TokenKind::Semicolon => {
+                parser.advance();
+            }
+            TokenKind::LBrace => {
+                parser.advance();
+                statements.push(Stmt::BlockStart(token.span));
+            }
+            TokenKind::RBrace => {
+                parser.advance();
+                statements.push(Stmt::BlockEnd(token.span));
+            }
+            _ => {
+                statements.push(parser.statement()?);
+                let next = parser.peek();
+                if !matches!(next.kind, TokenKind::Semicolon | TokenKind::RBrace | TokenKind::Eof) {
+                    return Err(Error::new(format!("expected `;`, found `{}`", next.kind), next.span));
+                }
+            }
+        }
+    }
+    Ok(statements)
+}
+
+impl Parser {
+    fn statement(&mut self) -> Result<Stmt, Error> {
+        let first = self.peek().clone();
+        match &first.kind {
+            TokenKind::Ident(word) if word == "let" => {
+                self.advance();
+                let mutable = matches!(&self.peek().kind, TokenKind::Ident(word) if word == "mut");
+                if mutable {
+                    self.advance();
+                }
+                let (name, name_span) = self.binding_name()?;
+                let value = match self.peek().kind {
+                    TokenKind::Assign => {
+                        self.advance();
+                        Some(self.expression(0)?)
+                    }
+                    _ => None,
+                };
+                let end = value.as_ref().map_or(name_span, |value| value.span);
+                Ok(Stmt::Let { name, mutable, value, span: first.span.to(end) })
+            }
+            TokenKind::Ident(name) if self.tokens[self.pos + 1].kind == TokenKind::Assign => {
+                let name = name.clone();
+                self.binding_name()?;
+                self.advance();
+                let value = self.expression(0)?;
+                Ok(Stmt::Assign { name, span: first.span.to(value.span), value })
+            }
+            _ => {
+                let expr = self.expression(0)?;
+                if self.peek().kind == TokenKind::Assign {
+                    return Err(Error::new("invalid left-hand side of assignment", expr.span));
+                }
+                Ok(Stmt::Expr(expr))
+            }
+        }
+    }
+
+    fn binding_name(&mut self) -> Result<(String, Span), Error> {
+        let token = self.advance();
+        match token.kind {
+            TokenKind::Ident(name) if !is_keyword(&name) && Operation::from_symbol(&name).is_none() => {
+                Ok((name, token.span))
+            }
+            other => Err(Error::new(format!("expected a variable name, found `{}`", other), token.span)),
+        }
+    }
+}
+
+// ---------- 5. EVALUATOR ----------
+impl Expr {
+    pub fn eval<T: Number>(&self) -> Result<T, Error> {
+        self.eval_with(Mode::Checked)
+    }
+
+    // Evaluate without any variables in scope.
+    pub fn eval_with<T: Number>(&self, mode: Mode) -> Result<T, Error> {
+        self.eval_in(mode, &|name, span| Err(Error::new(format!("cannot find value `{}` in this scope", name), span)))
+    }
+
+    // `lookup` resolves each variable (or explains why it can't, pointing at the span it is given).
+    // Post-order with explicit stacks instead of recursion, so the depth of the tree doesn't matter:
+    // an operator is visited once to schedule its operands (left first) and once to apply it.
+    pub fn eval_in<T: Number>(&self, mode: Mode, lookup: &dyn Fn(&str, Span) -> Result<T, Error>) -> Result<T, Error> {
+        let mut todo = vec![(self, false)];
+        let mut values: Vec<T> = Vec::new();
+        while let Some((expr, operands_done)) = todo.pop() {
+            match (&expr.kind, operands_done) {
+                (ExprKind::Number(literal), _) => values.push(literal.value(expr.span)?),
+                (ExprKind::Variable(name), _) => values.push(lookup(name, expr.span)?),
+                (ExprKind::Negate(operand), false) => todo.extend([(expr, true), (&**operand, false)]),
+                (ExprKind::Binary(_, left, right), false) => todo.extend([(expr, true), (&**right, false), (&**left, false)]),
+                (ExprKind::Negate(_), true) => {
+                    let value = values.pop().expect("the operand was evaluated");
+                    let zero = T::from_digits("0", 10).expect("every type has a zero");
+                    values.push(Operation::Subtract.run_with(zero, value, mode).map_err(|err| expr.error(err, None))?);
+                }
+                (ExprKind::Binary(op, _, right), true) => {
+                    let y = values.pop().expect("the right operand was evaluated");
+                    let x = values.pop().expect("the left operand was evaluated");
+                    values.push(op.run_with(x, y, mode).map_err(|err| expr.error(err, Some(right)))?);
+                }
+            }
+        }
+        Ok(values.pop().expect("the whole expression was evaluated"))
+    }
+
+    fn error(&self, err: OpError, right: Option<&Expr>) -> Error {
+        operation_error(err, self.span, right.map_or(self.span, |right| right.span))
+    }
+}
+
+// Operand errors point at the operand that caused them, overflow at the whole operation.
+pub fn operation_error(err: OpError, span: Span, right_span: Span) -> Error {
+    match err {
+        OpError::DivideByZero | OpError::NegativeExponent | OpError::NegativeShift => Error::new(err.to_string(), right_span),
+        OpError::Overflow | OpError::NotANumber | OpError::Unsupported => Error::new(err.to_string(), span),
+    }
+}
+
+// ---------- 6. PRINTING ----------
+// Infix text with only the parentheses the tree needs, so parsing it again
+// gives the same tree: (1 + 2) + 3 prints as 1 + 2 + 3, 1 + (2 + 3) keeps its
+// parentheses, and ((x)) is just x.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The operator binding an operand, if it is an infix one.
+        let infix = |expr: &Expr| match &expr.kind {
+            ExprKind::Binary(op, _, _) => op.precedence(),
+            _ => None,
+        };
+        // What is left to print, last piece on top; a stack instead of recursion, like eval_in.
+        enum Piece<'a> {
+            Expr(&'a Expr),
+            Text(&'static str),
+            Op(Operation),
+        }
+        fn operand<'a>(pieces: &mut Vec<Piece<'a>>, expr: &'a Expr, parenthesize: bool) {
+            match parenthesize {
+                true => pieces.extend([Piece::Text(")"), Piece::Expr(expr), Piece::Text("(")]),
+                false => pieces.push(Piece::Expr(expr)),
+            }
+        }
+        let mut pieces = vec![Piece::Expr(self)];
+        while let Some(piece) = pieces.pop() {
+            let expr = match piece {
+                Piece::Expr(expr) => expr,
+                Piece::Text(text) => {
+                    f.write_str(text)?;
+                    continue;
+                }
+                Piece::Op(op) => {
+                    write!(f, " {} ", op)?;
+                    continue;
+                }
+            };
+            match &expr.kind {
+                ExprKind::Number(literal) => write!(f, "{}", literal)?,
+                ExprKind::Variable(name) => write!(f, "{}", name)?,
+                // Unary minus binds tighter than every infix operator.
+                ExprKind::Negate(inner) => {
+                    write!(f, "-")?;
+                    operand(&mut pieces, inner, infix(inner).is_some());
+                }
+                ExprKind::Binary(op, left, right) => match op.precedence() {
+                    None => {
+                        write!(f, "{}(", op)?;
+                        pieces.extend([Piece::Text(")"), Piece::Expr(right), Piece::Text(", "), Piece::Expr(left)]);
+                    }
+                    // Left-associative: the right operand needs parentheses at equal precedence too.
+                    Some(precedence) => {
+                        operand(&mut pieces, right, infix(right).is_some_and(|p| p <= precedence));
+                        pieces.push(Piece::Op(*op));
+                        operand(&mut pieces, left, infix(left).is_some_and(|p| p < precedence));
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,713 @@
+/*
+Raw terminal input → WebEvent — an incremental decoder for keys and mouse reports
+
+A terminal in raw mode sends bytes, not events. The same key can arrive as one
+byte or as a whole escape sequence, and a read() can stop in the middle of either:
+
+Bytes	WebEvent
+61	KeyPress('a')
+c3 a9	KeyPress('é')	(UTF-8: the first byte says how many follow)
+01	Key(Ctrl+a)
+1b 61	Key(Alt+a)	ESC before a key is Alt
+1b 5b 41	Key(Up)	ESC [ A, a CSI sequence
+1b 5b 31 3b 35 41	Key(Ctrl+Up)	ESC [ 1 ; 5 A: 1 + the modifier bits
+1b 4f 50	Key(F1)	ESC O P (SS3)
+1b 5b 31 35 7e	Key(F5)	ESC [ 1 5 ~
+1b 5b 3c 30 3b 31 30 3b 35 4d	Click { x: 9, y: 4 }	ESC [ < 0 ; 10 ; 5 M, an SGR mouse report
+1b 5b 3c 36 35 3b 31 3b 31 4d	Scroll { dy: 1, .. }	button 65: the wheel, down
+1b 5b 49, 1b 5b 4f	FocusIn, FocusOut	ESC [ I, ESC [ O
+1b 5b 32 30 30 7e … 1b 5b 32 30 31 7e	Paste("…")	ESC [ 200 ~ text ESC [ 201 ~
+1b	Key(Escape)	only once `flush` says no more bytes are coming
+1b 1b	Key(Escape), …	ESC before ESC is the Escape key (but 1b 1b 5b 41 is Alt+Up)
+
+📌 `feed` takes whatever read() returned and yields every event that is complete;
+   the bytes of an unfinished one wait for the next `feed`. Feeding the input
+   one byte at a time gives the same events as feeding it all at once.
+📌 ESC alone is ambiguous (Escape key, or the start of a sequence?). Call `flush`
+   when no byte has come for a while (here: 100 ms) or at end of input.
+📌 SGR mouse reports (mode 1006) are 1-based; events use 0-based columns (x)
+   and rows (y). The events are built with `.into()`, so a plain key press is
+   still KeyPress(c) and a plain left press is still Click (web_event.rs).
+📌 Resize isn't in the byte stream: the loop below asks `stty size` at startup
+   and after each SIGWINCH, and sends Resize when the size changed.
+📌 Pasted text is scanned once, however many reads it takes; a paste longer
+   than MAX_PASTE (1 MiB) comes as several Paste events.
+⚠️ Terminals don't report key releases. Unknown sequences come out as
+   Decoded::Unknown with their bytes, never dropped silently.
+
+$ rustc -O input_decoder.rs
+$ printf 'a\xc3\xa9\x1b[1;5A\x1b[<0;10;5M' | ./input_decoder
+Key pressed: a
+Key pressed: é
+Key pressed: Ctrl+Up
+Clicked at: 9, 4
+$ ./input_decoder                            // a terminal: press keys, click, Ctrl-C to quit
+$ rustc --test input_decoder.rs && ./input_decoder   // the tests
+*/
+
+mod web_event;
+#[cfg(test)]
+mod testing;
+
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use web_event::{inspect, Button, Dispatcher, Key, KeyEvent, Modifiers, MouseAction, MouseEvent, WebEvent};
+
+// ---------- 1. THE DECODER ----------
+#[derive(Debug, Clone, PartialEq)]
+enum Decoded {
+    Event(WebEvent),
+    Unknown(Vec<u8>),
+}
+
+// A sequence longer than this without its final byte is garbage, not a slow writer.
+const MAX_SEQUENCE: usize = 32;
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+// A paste longer than this comes as several Paste events, so the pending bytes stay bounded.
+const MAX_PASTE: usize = 1 << 20;
+
+// The result of looking at the start of the pending bytes.
+enum Step {
+    Done(Decoded, usize), // and how many bytes it used
+    Incomplete,
+}
+
+#[derive(Default)]
+struct Decoder {
+    pending: Vec<u8>,
+    // Inside a paste (ESC [ 200 ~ already consumed): how many pending bytes are known
+    // not to start ESC [ 201 ~, so each feed only scans the new ones.
+    paste: Option<usize>,
+}
+
+impl Decoder {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    // Every event completed by `bytes`.
+    fn feed(&mut self, bytes: &[u8]) -> Vec<Decoded> {
+        self.pending.extend_from_slice(bytes);
+        let mut decoded = Vec::new();
+        let mut start = 0;
+        while start < self.pending.len() {
+            let rest = &self.pending[start..];
+            if let Some(scanned) = self.paste {
+                match decode_paste(rest, scanned) {
+                    PasteStep::Done(item, len) => {
+                        decoded.push(item);
+                        start += len;
+                        self.paste = None;
+                    }
+                    PasteStep::Part(item, len) => {
+                        decoded.push(item);
+                        start += len;
+                        self.paste = Some(0);
+                    }
+                    PasteStep::Incomplete(scanned) => {
+                        self.paste = Some(scanned);
+                        break;
+                    }
+                }
+            } else if rest.starts_with(PASTE_START) {
+                start += PASTE_START.len();
+                self.paste = Some(0);
+            } else {
+                match decode(rest) {
+                    Step::Done(item, len) => {
+                        decoded.push(item);
+                        start += len;
+                    }
+                    Step::Incomplete => break,
+                }
+            }
+        }
+        self.pending.drain(..start);
+        decoded
+    }
+
+    // No more bytes are coming soon: whatever is pending is complete as it is.
+    fn flush(&mut self) -> Vec<Decoded> {
+        let mut decoded = Vec::new();
+        // A paste that never ends is reported with its start marker, not lost.
+        if self.paste.take().is_some() {
+            decoded.push(Decoded::Unknown([PASTE_START, &std::mem::take(&mut self.pending)].concat()));
+        }
+        loop {
+            let pending = std::mem::take(&mut self.pending);
+            match pending.as_slice() {
+                [] => return decoded,
+                [0x1b] => decoded.push(key(Key::Escape, Modifiers::NONE)),
+                // Escape, then a cut-off sequence: the key, then the rest on its own.
+                [0x1b, rest @ ..] if rest[0] == 0x1b => {
+                    decoded.push(key(Key::Escape, Modifiers::NONE));
+                    decoded.extend(self.feed(rest));
+                }
+                // A cut-off sequence or character.
+                _ => decoded.push(Decoded::Unknown(pending)),
+            }
+        }
+    }
+}
+
+fn key(key: Key, modifiers: Modifiers) -> Decoded {
+    Decoded::Event(KeyEvent::new(key, modifiers).into())
+}
+
+fn decode(bytes: &[u8]) -> Step {
+    let control = |k| Step::Done(key(k, Modifiers::NONE), 1);
+    match bytes[0] {
+        b'\r' | b'\n' => control(Key::Enter),
+        b'\t' => control(Key::Tab),
+        0x7f | 0x08 => control(Key::Backspace),
+        0x1b => decode_escape(bytes),
+        0x00 => Step::Done(key(Key::Char(' '), Modifiers::CTRL), 1),
+        byte @ 0x01..=0x1a => Step::Done(key(Key::Char(char::from(b'a' + byte - 1)), Modifiers::CTRL), 1),
+        byte if byte < 0x20 => Step::Done(Decoded::Unknown(vec![byte]), 1),
+        byte => decode_utf8(bytes, byte),
+    }
+}
+
+fn decode_utf8(bytes: &[u8], first: u8) -> Step {
+    // The leading byte says how many continuation bytes follow.
+    let len = match first {
+        0x00..=0x7f => 1,
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return Step::Done(Decoded::Unknown(vec![first]), 1),
+    };
+    let available = &bytes[..len.min(bytes.len())];
+    // A byte that can't continue the character ends it early: report the lead byte alone.
+    if available[1..].iter().any(|byte| byte & 0xc0 != 0x80) {
+        return Step::Done(Decoded::Unknown(vec![first]), 1);
+    }
+    if available.len() < len {
+        return Step::Incomplete;
+    }
+    match std::str::from_utf8(available).ok().and_then(|text| text.chars().next()) {
+        Some(c) => Step::Done(Decoded::Event(WebEvent::KeyPress(c)), len),
+        None => Step::Done(Decoded::Unknown(vec![first]), 1), // overlong or a surrogate
+    }
+}
+
+// ESC [ … final (CSI), ESC O x (SS3), ESC before a key (Alt); otherwise Escape itself.
+fn decode_escape(bytes: &[u8]) -> Step {
+    match bytes.get(1) {
+        None => Step::Incomplete,
+        Some(b'[') => decode_csi(bytes),
+        Some(b'O') => match bytes.get(2) {
+            None => Step::Incomplete,
+            Some(&last) => {
+                let decoded = ss3_key(last).map_or_else(|| Decoded::Unknown(bytes[..3].to_vec()), |k| key(k, Modifiers::NONE));
+                Step::Done(decoded, 3)
+            }
+        },
+        // ESC ESC: Alt with a sequence (ESC ESC [ A is Alt+Up), otherwise the Escape key.
+        // Never more than one level deep, so a run of ESC bytes can't exhaust the stack.
+        Some(0x1b) => match bytes.get(2) {
+            None => Step::Incomplete,
+            Some(b'[' | b'O') => with_alt(decode_escape(&bytes[1..])),
+            Some(_) => Step::Done(key(Key::Escape, Modifiers::NONE), 1),
+        },
+        Some(_) => with_alt(decode(&bytes[1..])),
+    }
+}
+
+// The key after an ESC, with Alt added; anything else leaves the ESC as the Escape key.
+fn with_alt(step: Step) -> Step {
+    match step {
+        Step::Incomplete => Step::Incomplete,
+        Step::Done(Decoded::Event(event), len) if event.key_event().is_some() => {
+            let pressed = event.key_event().expect("checked");
+            Step::Done(key(pressed.key, pressed.modifiers | Modifiers::ALT), len + 1)
+        }
+        Step::Done(..) => Step::Done(key(Key::Escape, Modifiers::NONE), 1),
+    }
+}
+
+fn ss3_key(last: u8) -> Option<Key> {
+    Some(match last {
+        b'A' => Key::Up,
+        b'B' => Key::Down,
+        b'C' => Key::Right,
+        b'D' => Key::Left,
+        b'H' => Key::Home,
+        b'F' => Key::End,
+        b'P' => Key::F(1),
+        b'Q' => Key::F(2),
+        b'R' => Key::F(3),
+        b'S' => Key::F(4),
+        _ => return None,
+    })
+}
+
+// ESC [ then parameter bytes (0x30-0x3f), intermediate bytes (0x20-0x2f), one final byte (0x40-0x7e).
+fn decode_csi(bytes: &[u8]) -> Step {
+    let Some(end) = bytes.iter().take(MAX_SEQUENCE).skip(2).position(|byte| !(0x20..=0x3f).contains(byte)).map(|i| i + 2) else {
+        if bytes.len() >= MAX_SEQUENCE {
+            return Step::Done(Decoded::Unknown(bytes[..MAX_SEQUENCE].to_vec()), MAX_SEQUENCE);
+        }
+        return Step::Incomplete;
+    };
+    let sequence = &bytes[..=end];
+    if !(0x40..=0x7e).contains(&bytes[end]) {
+        // Not a CSI after all: report what was read, and decode the odd byte normally.
+        return Step::Done(Decoded::Unknown(bytes[..end].to_vec()), end);
+    }
+    // `feed` takes pastes off first; this one follows an ESC, which with_alt keeps as Escape.
+    if sequence == PASTE_START {
+        return Step::Done(Decoded::Unknown(sequence.to_vec()), end + 1);
+    }
+    let params = std::str::from_utf8(&bytes[2..end]).unwrap_or("");
+    let decoded = match (params, bytes[end]) {
+        ("", b'I') => Some(Decoded::Event(WebEvent::FocusIn)),
+        ("", b'O') => Some(Decoded::Event(WebEvent::FocusOut)),
+        (params, last) => match params.strip_prefix('<') {
+            Some(mouse) => sgr_mouse(mouse, last).map(Decoded::Event),
+            None => csi_key(params, last).map(|(k, modifiers)| key(k, modifiers)),
+        },
+    };
+    Step::Done(decoded.unwrap_or_else(|| Decoded::Unknown(sequence.to_vec())), end + 1)
+}
+
+enum PasteStep {
+    Done(Decoded, usize), // the rest of the paste, and the bytes it used with ESC [ 201 ~
+    Part(Decoded, usize), // MAX_PASTE bytes of a longer paste; more follows
+    Incomplete(usize),    // how many bytes are now known not to start the end marker
+}
+
+// Everything up to ESC [ 201 ~ is text, escape sequences included: it was pasted, not typed.
+// `text` starts after ESC [ 200 ~ and its first `scanned` bytes were searched before.
+fn decode_paste(text: &[u8], scanned: usize) -> PasteStep {
+    let paste = |text: &[u8]| Decoded::Event(WebEvent::Paste(String::from_utf8_lossy(text).into_owned()));
+    // Only an end marker that starts within MAX_PASTE bytes counts, so the pieces of a
+    // long paste are the same however the reads split it.
+    let window = &text[..text.len().min(MAX_PASTE + PASTE_END.len())];
+    let end = window[scanned..].windows(PASTE_END.len()).position(|bytes| bytes == PASTE_END);
+    match end.map(|i| scanned + i) {
+        Some(len) => PasteStep::Done(paste(&text[..len]), len + PASTE_END.len()),
+        // Cut at MAX_PASTE, backing up to the start of a character.
+        None if window.len() < text.len() => {
+            let len = (MAX_PASTE - 3..=MAX_PASTE).rev().find(|&len| text[len] & 0xc0 != 0x80).unwrap_or(MAX_PASTE);
+            PasteStep::Part(paste(&text[..len]), len)
+        }
+        None => PasteStep::Incomplete(window.len().saturating_sub(PASTE_END.len() - 1)),
+    }
+}
+
+// The second parameter is 1 + the modifier bits: ESC [ 1 ; 5 A is Ctrl-Up.
+fn csi_key(params: &str, last: u8) -> Option<(Key, Modifiers)> {
+    let mut numbers = params.split(';');
+    let first: u32 = match numbers.next() {
+        Some("") | None => 1,
+        Some(number) => number.parse().ok()?,
+    };
+    let modifiers = match numbers.next() {
+        Some(number) => modifier_bits(number.parse::<u8>().ok()?.checked_sub(1)?),
+        None => Modifiers::NONE,
+    };
+    let key = match last {
+        b'Z' => Key::BackTab,
+        b'~' => match first {
+            1 | 7 => Key::Home,
+            2 => Key::Insert,
+            3 => Key::Delete,
+            4 | 8 => Key::End,
+            5 => Key::PageUp,
+            6 => Key::PageDown,
+            11..=15 => Key::F(first as u8 - 10),
+            17..=21 => Key::F(first as u8 - 11),
+            23..=26 => Key::F(first as u8 - 12),
+            28 | 29 => Key::F(first as u8 - 13),
+            31..=34 => Key::F(first as u8 - 14),
+            _ => return None,
+        },
+        last => ss3_key(last)?,
+    };
+    Some((key, modifiers))
+}
+
+// Shift 1, Alt 2, Ctrl 4, Meta 8: the same bits as Modifiers.
+fn modifier_bits(bits: u8) -> Modifiers {
+    [Modifiers::SHIFT, Modifiers::ALT, Modifiers::CTRL, Modifiers::META]
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| bits & (1 << i) != 0)
+        .fold(Modifiers::NONE, |all, (_, modifier)| all | modifier)
+}
+
+// ESC [ < button ; column ; row (M press, m release).
+fn sgr_mouse(params: &str, last: u8) -> Option<WebEvent> {
+    let numbers: Vec<u32> = params.split(';').map(|n| n.parse().ok()).collect::<Option<_>>()?;
+    let [code, column, row] = numbers[..] else {
+        return None;
+    };
+    if column == 0 || row == 0 {
+        return None;
+    }
+    let (x, y) = (i64::from(column) - 1, i64::from(row) - 1);
+    // 4 is Shift, 8 Alt (Meta to xterm), 16 Ctrl; 32 is motion, 64 the wheel.
+    let modifiers = [(4, Modifiers::SHIFT), (8, Modifiers::ALT), (16, Modifiers::CTRL)]
+        .into_iter()
+        .filter(|(bit, _)| code & bit != 0)
+        .fold(Modifiers::NONE, |all, (_, modifier)| all | modifier);
+    if code & 64 != 0 {
+        let (dx, dy) = match code & 3 {
+            0 => (0, -1),
+            1 => (0, 1),
+            2 => (-1, 0),
+            _ => (1, 0),
+        };
+        return (last == b'M').then_some(WebEvent::Scroll { x, y, dx, dy, modifiers });
+    }
+    let button = match code & 3 {
+        0 => Some(Button::Left),
+        1 => Some(Button::Middle),
+        2 => Some(Button::Right),
+        _ => None,
+    };
+    let action = match (code & 32 != 0, last, button) {
+        (true, b'M', Some(button)) => MouseAction::Drag(button),
+        (true, b'M', None) => MouseAction::Move,
+        (false, b'M', Some(button)) => MouseAction::Press(button),
+        (false, b'm', Some(button)) => MouseAction::Release(button),
+        _ => return None,
+    };
+    Some(MouseEvent { action, x, y, modifiers }.into())
+}
+
+// ---------- 2. MAIN ----------
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// "24 80" from `stty size` is 24 rows of 80 columns.
+fn terminal_size() -> Option<(u32, u32)> {
+    let size = stty(&["size"])?;
+    let (rows, columns) = size.split_once(' ')?;
+    Some((columns.parse().ok()?, rows.parse().ok()?))
+}
+
+// Set by SIGWINCH, the signal a terminal sends when its size changes; the loop
+// then asks `stty size` once, instead of after every read.
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_resize(_signal: i32) {
+    RESIZED.store(true, Ordering::Relaxed);
+}
+
+fn watch_resize() {
+    extern "C" {
+        fn signal(signal: i32, handler: extern "C" fn(i32)) -> usize;
+    }
+    const SIGWINCH: i32 = 28; // the same on Linux and macOS
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe {
+        signal(SIGWINCH, on_resize);
+    }
+}
+
+// Raw input plus mouse, focus and bracketed-paste reports while it lives; read()
+// gives up after 100 ms without input, which is when a lone ESC is flushed.
+// The old settings come back on drop.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> Option<RawMode> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "-isig", "min", "0", "time", "1"])?;
+        print!("\x1b[?1000h\x1b[?1002h\x1b[?1006h\x1b[?1004h\x1b[?2004h");
+        io::stdout().flush().ok()?;
+        Some(RawMode { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        print!("\x1b[?2004l\x1b[?1004l\x1b[?1006l\x1b[?1002l\x1b[?1000l");
+        let _ = io::stdout().flush();
+        stty(&[&self.saved]);
+    }
+}
+
+// Events go through the dispatcher, like live ones would; the rest is printed as decoded.
+fn show(dispatcher: &mut Dispatcher, decoded: Decoded) {
+    match decoded {
+        Decoded::Event(event) => {
+            dispatcher.dispatch(&event);
+        }
+        Decoded::Unknown(bytes) => println!("Unknown input: {:02x?}", bytes),
+    }
+}
+
+fn main() {
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.on_any(inspect);
+    let mut decoder = Decoder::new();
+    let mut stdin = io::stdin().lock();
+    let mut buffer = [0; 256];
+
+    let interactive = io::stdin().is_terminal();
+    let _raw = if interactive { RawMode::enable() } else { None };
+    if interactive {
+        println!("press keys, click, scroll, paste or resize; Ctrl-C to quit");
+    }
+    let mut size = None;
+    if interactive {
+        watch_resize();
+        RESIZED.store(true, Ordering::Relaxed); // the size at startup
+    }
+    loop {
+        let read = match stdin.read(&mut buffer) {
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue, // by SIGWINCH
+            Err(err) => panic!("failed to read stdin: {}", err),
+        };
+        let mut decoded = match read {
+            0 => decoder.flush(),
+            n => decoder.feed(&buffer[..n]),
+        };
+        if RESIZED.swap(false, Ordering::Relaxed) && terminal_size() != size {
+            size = terminal_size();
+            if let Some((width, height)) = size {
+                decoded.push(Decoded::Event(WebEvent::Resize { width, height }));
+            }
+        }
+        for item in decoded {
+            if interactive && item == key(Key::Char('c'), Modifiers::CTRL) {
+                return;
+            }
+            show(&mut dispatcher, item);
+        }
+        // Not a terminal: 0 is the end of the input, not a pause.
+        if read == 0 && !interactive {
+            return;
+        }
+    }
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::{next_random, SEED};
+
+    fn decode_all(bytes: &[u8]) -> Vec<Decoded> {
+        let mut decoder = Decoder::new();
+        let mut decoded = decoder.feed(bytes);
+        decoded.extend(decoder.flush());
+        decoded
+    }
+
+    fn char_key(c: char) -> Decoded {
+        Decoded::Event(WebEvent::KeyPress(c))
+    }
+
+    #[test]
+    fn text_check() {
+        assert_eq!(decode_all(b"hi!"), [char_key('h'), char_key('i'), char_key('!')]);
+        assert_eq!(decode_all("é€😀".as_bytes()), [char_key('é'), char_key('€'), char_key('😀')]);
+        assert_eq!(
+            decode_all(b"\r\t\x7f\x01\x1a\x00"),
+            [
+                key(Key::Enter, Modifiers::NONE),
+                key(Key::Tab, Modifiers::NONE),
+                key(Key::Backspace, Modifiers::NONE),
+                key(Key::Char('a'), Modifiers::CTRL),
+                key(Key::Char('z'), Modifiers::CTRL),
+                key(Key::Char(' '), Modifiers::CTRL),
+            ]
+        );
+        // Invalid UTF-8 is reported byte by byte, and decoding carries on after it.
+        assert_eq!(decode_all(b"\xffa"), [Decoded::Unknown(vec![0xff]), char_key('a')]);
+        assert_eq!(decode_all(b"\xc3a"), [Decoded::Unknown(vec![0xc3]), char_key('a')]);
+        assert_eq!(decode_all(b"\xc0\x80"), [Decoded::Unknown(vec![0xc0]), Decoded::Unknown(vec![0x80])]);
+        assert_eq!(decode_all(b"\xed\xa0\x80"), [Decoded::Unknown(vec![0xed]), Decoded::Unknown(vec![0xa0]), Decoded::Unknown(vec![0x80])]);
+        // Cut off at the end of the input.
+        assert_eq!(decode_all(b"\xe2\x82"), [Decoded::Unknown(vec![0xe2, 0x82])]);
+    }
+
+    #[test]
+    fn escape_check() {
+        let keys = |bytes: &[u8]| -> Vec<String> {
+            decode_all(bytes)
+                .into_iter()
+                .map(|decoded| match decoded {
+                    Decoded::Event(event) => event.key_event().expect("a key").to_string(),
+                    other => panic!("expected a key, got {:?}", other),
+                })
+                .collect()
+        };
+        assert_eq!(keys(b"\x1b[A\x1b[B\x1b[C\x1b[D"), ["Up", "Down", "Right", "Left"]);
+        assert_eq!(keys(b"\x1bOA\x1bOH\x1b[H\x1b[F"), ["Up", "Home", "Home", "End"]);
+        assert_eq!(keys(b"\x1b[2~\x1b[3~\x1b[5~\x1b[6~\x1b[1~\x1b[4~"), ["Insert", "Delete", "PageUp", "PageDown", "Home", "End"]);
+        assert_eq!(keys(b"\x1bOP\x1bOQ\x1bOR\x1bOS"), ["F1", "F2", "F3", "F4"]);
+        let function_keys: Vec<u8> = [11, 12, 13, 14, 15, 17, 18, 19, 20, 21, 23, 24].iter().flat_map(|n| format!("\x1b[{}~", n).into_bytes()).collect();
+        assert_eq!(keys(&function_keys), (1..=12).map(|n| format!("F{}", n)).collect::<Vec<_>>());
+        assert_eq!(keys(b"\x1b[Z"), ["BackTab"]);
+
+        // Modifiers: the second parameter is 1 + Shift 1, Alt 2, Ctrl 4, Meta 8.
+        assert_eq!(keys(b"\x1b[1;5A\x1b[3;2~\x1b[1;4D\x1b[15;9~"), ["Ctrl+Up", "Shift+Delete", "Alt+Shift+Left", "Meta+F5"]);
+        assert_eq!(decode_all(b"\x1b[1;5A"), [key(Key::Up, Modifiers::CTRL)]);
+
+        // Escape on its own; ESC before a key is Alt.
+        assert_eq!(keys(b"\x1b"), ["Escape"]);
+        assert_eq!(keys(b"\x1bx\x1b\x01\x1b\xc3\xa9"), ["Alt+x", "Ctrl+Alt+a", "Alt+é"]);
+        assert_eq!(keys(b"\x1b\x1b[A"), ["Alt+Up"]);
+        assert_eq!(keys(b"\x1b\x1b"), ["Escape", "Escape"]);
+        assert_eq!(keys(b"\x1b\x1bx\x1b\x1b\x1b[A"), ["Escape", "Alt+x", "Escape", "Alt+Up"]);
+
+        // A long run of ESC bytes is as many Escape keys, fed at once or byte by byte.
+        let run = vec![0x1b; 100_000];
+        assert_eq!(decode_all(&run), vec![key(Key::Escape, Modifiers::NONE); 100_000]);
+        let mut decoder = Decoder::new();
+        let mut decoded: Vec<Decoded> = run.chunks(1).flat_map(|byte| decoder.feed(byte)).collect();
+        decoded.extend(decoder.flush());
+        assert_eq!(decoded.len(), 100_000);
+
+        // Unknown sequences keep their bytes; what follows still decodes.
+        assert_eq!(decode_all(b"\x1b[99~a"), [Decoded::Unknown(b"\x1b[99~".to_vec()), char_key('a')]);
+        assert_eq!(decode_all(b"\x1b[?1u"), [Decoded::Unknown(b"\x1b[?1u".to_vec())]);
+        assert_eq!(decode_all(b"\x1b[1;0A"), [Decoded::Unknown(b"\x1b[1;0A".to_vec())]);
+        assert_eq!(decode_all(b"\x1bOz"), [Decoded::Unknown(b"\x1bOz".to_vec())]);
+        assert_eq!(decode_all(b"\x1b[1\xc3\xa9"), [Decoded::Unknown(b"\x1b[1".to_vec()), char_key('é')]);
+        // Cut at MAX_SEQUENCE bytes, wherever the reads happened to stop.
+        let endless: Vec<u8> = [b"\x1b[".as_slice(), &[b'1'; 40]].concat();
+        let decoded = decode_all(&endless);
+        assert_eq!(decoded[0], Decoded::Unknown(endless[..MAX_SEQUENCE].to_vec()));
+        assert_eq!(decoded[1..], vec![char_key('1'); 10][..]);
+        assert_eq!(decode_all(b"\x1b[12"), [Decoded::Unknown(b"\x1b[12".to_vec())]);
+    }
+
+    #[test]
+    fn mouse_check() {
+        let event = |bytes: &[u8]| match &decode_all(bytes)[..] {
+            [Decoded::Event(event)] => event.clone(),
+            other => panic!("expected one event, got {:?}", other),
+        };
+        let mouse = |action, x, y, modifiers| WebEvent::Mouse(MouseEvent { action, x, y, modifiers });
+        assert_eq!(event(b"\x1b[<0;10;5M"), WebEvent::Click { x: 9, y: 4 });
+        assert_eq!(event(b"\x1b[<16;1;1M"), mouse(MouseAction::Press(Button::Left), 0, 0, Modifiers::CTRL));
+        assert_eq!(event(b"\x1b[<0;10;5m"), mouse(MouseAction::Release(Button::Left), 9, 4, Modifiers::NONE));
+        assert_eq!(event(b"\x1b[<2;3;4M"), mouse(MouseAction::Press(Button::Right), 2, 3, Modifiers::NONE));
+        assert_eq!(event(b"\x1b[<5;3;4M"), mouse(MouseAction::Press(Button::Middle), 2, 3, Modifiers::SHIFT));
+        assert_eq!(event(b"\x1b[<32;200;100M"), mouse(MouseAction::Drag(Button::Left), 199, 99, Modifiers::NONE));
+        assert_eq!(event(b"\x1b[<35;7;8M"), mouse(MouseAction::Move, 6, 7, Modifiers::NONE));
+        // Both forms of a click read the same through mouse_event().
+        assert_eq!(event(b"\x1b[<0;10;5M").mouse_event().map(|m| m.action), Some(MouseAction::Press(Button::Left)));
+
+        let scroll = |x, y, dx, dy, modifiers| WebEvent::Scroll { x, y, dx, dy, modifiers };
+        assert_eq!(event(b"\x1b[<64;1;1M"), scroll(0, 0, 0, -1, Modifiers::NONE));
+        assert_eq!(event(b"\x1b[<65;5;6M"), scroll(4, 5, 0, 1, Modifiers::NONE));
+        assert_eq!(event(b"\x1b[<66;1;1M"), scroll(0, 0, -1, 0, Modifiers::NONE));
+        assert_eq!(event(b"\x1b[<81;1;1M"), scroll(0, 0, 0, 1, Modifiers::CTRL));
+        // Malformed reports.
+        for bad in [b"\x1b[<0;0;5M".as_slice(), b"\x1b[<0;10M", b"\x1b[<0;1;5;7M", b"\x1b[<3;1;1m", b"\x1b[<64;1;1m"] {
+            assert_eq!(decode_all(bad), [Decoded::Unknown(bad.to_vec())]);
+        }
+    }
+
+    #[test]
+    fn paste_and_focus_check() {
+        assert_eq!(decode_all(b"\x1b[I\x1b[O"), [Decoded::Event(WebEvent::FocusIn), Decoded::Event(WebEvent::FocusOut)]);
+        // Pasted text stays one event, even when it holds newlines or escape sequences.
+        let pasted = decode_all(b"a\x1b[200~line 1\nline 2 \x1b[A\xc3\xa9\x1b[201~b");
+        assert_eq!(
+            pasted,
+            [char_key('a'), Decoded::Event(WebEvent::Paste("line 1\nline 2 \x1b[Aé".to_string())), char_key('b')]
+        );
+        assert_eq!(decode_all(b"\x1b[200~\x1b[201~"), [Decoded::Event(WebEvent::Paste(String::new()))]);
+        // A paste that never ends is reported, not lost.
+        assert_eq!(decode_all(b"\x1b[200~abc"), [Decoded::Unknown(b"\x1b[200~abc".to_vec())]);
+    }
+
+    // Feeding the same bytes in any chunks gives the same events.
+    #[test]
+    fn split_check() {
+        let input: Vec<u8> = [
+            "a".as_bytes(),
+            "é😀".as_bytes(),
+            b"\x1b[A\x1bOP\x1b[15~\x1b[<0;10;5M\x1b[<0;10;5m\x1b[1;5C\x1b[<65;2;2M",
+            b"\xff\x1b[99~z\x1bx\r\x1b[I\x1b[200~pasted\x1b[201~",
+            &[b"\x1b[".as_slice(), &[b'2'; 40]].concat(),
+        ]
+        .concat();
+        let whole = decode_all(&input);
+        assert_eq!(whole.len(), 28);
+
+        for chunk in 1..=input.len() {
+            let mut decoder = Decoder::new();
+            let mut decoded: Vec<Decoded> = input.chunks(chunk).flat_map(|part| decoder.feed(part)).collect();
+            decoded.extend(decoder.flush());
+            assert_eq!(decoded, whole, "chunks of {}", chunk);
+        }
+
+        // Random cuts.
+        let mut state = SEED;
+        for _ in 0..500 {
+            let mut decoder = Decoder::new();
+            let mut decoded = Vec::new();
+            let mut rest = input.as_slice();
+            while !rest.is_empty() {
+                let cut = 1 + (next_random(&mut state) % 6) as usize;
+                let (part, tail) = rest.split_at(cut.min(rest.len()));
+                decoded.extend(decoder.feed(part));
+                rest = tail;
+            }
+            decoded.extend(decoder.flush());
+            assert_eq!(decoded, whole);
+        }
+
+        // A sequence stays pending until its last byte arrives.
+        let mut decoder = Decoder::new();
+        assert_eq!(decoder.feed(b"\x1b"), []);
+        assert_eq!(decoder.feed(b"[<0;1"), []);
+        assert_eq!(decoder.feed(b"0;5M"), [Decoded::Event(WebEvent::Click { x: 9, y: 4 })]);
+        assert_eq!(decoder.feed(b"\xf0\x9f"), []);
+        assert_eq!(decoder.feed(b"\x98\x80"), [char_key('😀')]);
+        assert_eq!(decoder.feed(b"\x1b[200~half"), []);
+        assert_eq!(decoder.feed(b" done\x1b[201"), []);
+        assert_eq!(decoder.feed(b"~"), [Decoded::Event(WebEvent::Paste("half done".to_string()))]);
+        assert_eq!(decoder.flush(), []);
+    }
+
+    #[test]
+    fn long_paste_check() {
+        // Multi-byte characters, so some cuts would fall inside one.
+        let text = "é€ x\n".repeat(MAX_PASTE / 3);
+        let input = [PASTE_START, text.as_bytes(), PASTE_END, b"!"].concat();
+        let whole = decode_all(&input);
+        let mut pieces = String::new();
+        for decoded in &whole[..whole.len() - 1] {
+            match decoded {
+                Decoded::Event(WebEvent::Paste(piece)) => {
+                    assert!(!piece.is_empty() && piece.len() <= MAX_PASTE);
+                    pieces.push_str(piece);
+                }
+                other => panic!("expected a paste, got {:?}", other),
+            }
+        }
+        assert_eq!(pieces, text);
+        assert_eq!(whole.len(), 1 + text.len().div_ceil(MAX_PASTE));
+        assert_eq!(whole.last(), Some(&char_key('!')));
+
+        // Read by read, the pieces are the same, and the pending bytes stay bounded.
+        for chunk in [1_000, 4_096, 65_536] {
+            let mut decoder = Decoder::new();
+            let mut decoded = Vec::new();
+            for part in input.chunks(chunk) {
+                decoded.extend(decoder.feed(part));
+                assert!(decoder.pending.len() < MAX_PASTE + PASTE_END.len() + chunk);
+            }
+            decoded.extend(decoder.flush());
+            assert_eq!(decoded, whole, "chunks of {}", chunk);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+/*
+Three-address IR for `Operation` programs — the "HIR → MIR" step of the pipeline
+
+architecture.rs: Parsing → AST → Analysis → HIR & MIR → Code Generation.
+compile.rs does the same for calculator programs, and this module is the middle:
+
+AST (expression.rs)            IR (this file)
+let x = 3 + 2 * 4;        →    _0 = Multiply(const 2, const 4);
+                               _1 = Add(const 3, _0);
+                               _2 = _1;                      // x
+x - 1                     →    _3 = Subtract(_2, const 1);
+                               print(_3);
+
+📌 Every instruction does at most ONE operation, on operands that are constants
+   or locals. Nested expressions get a fresh temporary for each inner result.
+📌 Names are resolved here, like rustc's analysis phase: shadowing and `{ }`
+   scopes disappear, because every `let` gets its own local. `let x = x * 2`
+   simply reads one local and writes another.
+📌 The binding rules from `variable_bindings` (1.rs) are checked at compile time:
+   unknown names, use before assignment, assigning twice to an immutable binding.
+
+Locals are printed `_N` like rustc's MIR (`rustc -Z unpretty=mir` on nightly);
+`debug x => _2;` lines at the top say which local holds which variable.
+*/
+
+// Shared by several programs; each one uses a different subset.
+#![allow(dead_code)]
+
+use std::fmt;
+
+use crate::expression::{Error, Expr, ExprKind, Span, Stmt};
+use crate::operation::Operation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Local(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operand {
+    Const(i32),
+    Local(Local),
+}
+
+// The right-hand side of an assignment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rvalue {
+    Use(Operand),
+    Binary(Operation, Operand, Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inst {
+    // dest = value. Runtime errors point at `span` (overflow) or `right_span`
+    // (division by zero, negative exponent), as in the tree-walking evaluator.
+    Assign { dest: Local, value: Rvalue, span: Span, right_span: Span },
+    // The value of an expression statement.
+    Print { value: Operand, span: Span },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalDecl {
+    pub name: Option<String>, // None for temporaries
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Program {
+    pub locals: Vec<LocalDecl>,
+    pub insts: Vec<Inst>,
+}
+
+impl Program {
+    pub fn local_name(&self, local: Local) -> Option<&str> {
+        self.locals[local.0 as usize].name.as_deref()
+    }
+}
+
+// ---------- 1. LOWERING ----------
+struct Variable {
+    name: String,
+    local: Local,
+    mutable: bool,
+    assigned: bool,
+}
+
+struct Lowerer {
+    program: Program,
+    scopes: Vec<Vec<Variable>>,
+    open_blocks: Vec<Span>,
+}
+
+pub fn lower(statements: &[Stmt]) -> Result<Program, Error> {
+    let mut lowerer = Lowerer { program: Program::default(), scopes: vec![Vec::new()], open_blocks: Vec::new() };
+    for stmt in statements {
+        lowerer.statement(stmt)?;
+    }
+    if let Some(open) = lowerer.open_blocks.last() {
+        return Err(Error::new("unclosed delimiter `{`", *open));
+    }
+    Ok(lowerer.program)
+}
+
+impl Lowerer {
+    fn new_local(&mut self, name: Option<String>, span: Span) -> Local {
+        self.program.locals.push(LocalDecl { name, span });
+        Local(self.program.locals.len() as u32 - 1)
+    }
+
+    fn variable(&mut self, name: &str, span: Span) -> Result<&mut Variable, Error> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|variable| variable.name == name)
+            .ok_or_else(|| Error::new(format!("cannot find value `{}` in this scope", name), span))
+    }
+
+    fn statement(&mut self, stmt: &Stmt) -> Result<(), Error> {
+        match stmt {
+            Stmt::Let { name, mutable, value, span } => {
+                // Lowered before the new local exists, so `let x = x * 2` reads the old `x`.
+                let value = value.as_ref().map(|expr| Ok((self.expression(expr)?, expr.span))).transpose()?;
+                let local = self.new_local(Some(name.clone()), *span);
+                if let Some((operand, value_span)) = value {
+                    self.assign(local, Rvalue::Use(operand), value_span, value_span);
+                }
+                let variable = Variable { name: name.clone(), local, mutable: *mutable, assigned: value.is_some() };
+                self.scopes.last_mut().expect("the top-level scope is never popped").push(variable);
+            }
+            Stmt::Assign { name, value, span } => {
+                let operand = self.expression(value)?;
+                let variable = self.variable(name, *span)?;
+                if !variable.mutable && variable.assigned {
+                    return Err(Error::new(format!("cannot assign twice to immutable variable `{}`", name), *span));
+                }
+                variable.assigned = true;
+                let local = variable.local;
+                self.assign(local, Rvalue::Use(operand), value.span, value.span);
+            }
+            Stmt::Expr(expr) => {
+                let value = self.expression(expr)?;
+                self.program.insts.push(Inst::Print { value, span: expr.span });
+            }
+            Stmt::BlockStart(span) => {
+                self.scopes.push(Vec::new());
+                self.open_blocks.push(*span);
+            }
+            Stmt::BlockEnd(span) => {
+                if self.open_blocks.pop().is_none() {
+                    return Err(Error::new("unexpected closing delimiter `}`", *span));
+                }
+                self.scopes.pop();
+            }
+        }
+        Ok(())
+    }
+
+    fn assign(&mut self, dest: Local, value: Rvalue, span: Span, right_span: Span) {
+        self.program.insts.push(Inst::Assign { dest, value, span, right_span });
+    }
+
+    // Emits the instructions computing `expr`, and returns where its value ends up.
+    fn expression(&mut self, expr: &Expr) -> Result<Operand, Error> {
+        match &expr.kind {
+            ExprKind::Number(literal) => Ok(Operand::Const(literal.value(expr.span)?)),
+            ExprKind::Variable(name) => {
+                let variable = self.variable(name, expr.span)?;
+                if !variable.assigned {
+                    return Err(Error::new(format!("used binding `{}` isn't initialized", name), expr.span));
+                }
+                Ok(Operand::Local(variable.local))
+            }
+            ExprKind::Negate(operand) => {
+                let value = self.expression(operand)?;
+                let dest = self.new_local(None, expr.span);
+                let rvalue = Rvalue::Binary(Operation::Subtract, Operand::Const(0), value);
+                self.assign(dest, rvalue, expr.span, operand.span);
+                Ok(Operand::Local(dest))
+            }
+            ExprKind::Binary(op, left, right) => {
+                let (x, y) = (self.expression(left)?, self.expression(right)?);
+                let dest = self.new_local(None, expr.span);
+                self.assign(dest, Rvalue::Binary(*op, x, y), expr.span, right.span);
+                Ok(Operand::Local(dest))
+            }
+        }
+    }
+}
+
+// ---------- 2. PRINTING ----------
+impl fmt::Display for Local {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "_{}", self.0)
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Const(n) => write!(f, "const {}", n),
+            Operand::Local(local) => write!(f, "{}", local),
+        }
+    }
+}
+
+impl fmt::Display for Rvalue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rvalue::Use(operand) => write!(f, "{}", operand),
+            Rvalue::Binary(op, x, y) => write!(f, "{:?}({}, {})", op, x, y),
+        }
+    }
+}
+
+impl fmt::Display for Inst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Inst::Assign { dest, value, .. } => write!(f, "{} = {};", dest, value),
+            Inst::Print { value, .. } => write!(f, "print({});", value),
+        }
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, decl) in self.locals.iter().enumerate() {
+            if let Some(name) = &decl.name {
+                writeln!(f, "debug {} => _{};", name, i)?;
+            }
+        }
+        for inst in &self.insts {
+            writeln!(f, "{}", inst)?;
+        }
+        Ok(())
+    }
+}
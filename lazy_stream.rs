@@ -0,0 +1,319 @@
+/*
+Lazy streams — a List whose tail is computed on demand
+
+`List` in 1.rs is strict: `Cons(head, Box<List>)` means the whole tail already
+exists. A stream keeps the same Cons / Nil shape but delays the tail:
+
+    enum List   { Cons(T, Box<List>), Nil }   // tail built up front
+    enum Node   { Cons(T, Stream),    Nil }   // tail = a thunk, run the first time it's needed
+
+A thunk is a `FnOnce() -> Node` that is run at most once; its result is stored
+(memoized), so forcing the same cell again is free. Because nothing is built
+until asked for, a stream can be infinite:
+
+naturals()	0, 1, 2, 3, ...
+fibonacci()	0, 1, 1, 2, 3, 5, ...
+iterate(x, f)	x, f(x), f(f(x)), ...
+
+✅ take, zip, interleave, filter and map return new lazy streams: they force
+   nothing until their own cells are forced.
+✅ Forcing and dropping walk the stream with loops, not recursion, so reading
+   the millionth element or freeing a million forced cells is stack-safe.
+⚠️ filter on an infinite stream with no further matches never returns (same as
+   Iterator::filter).
+
+$ rustc lazy_stream.rs
+$ ./lazy_stream
+$ rustc -O --test lazy_stream.rs && ./lazy_stream   // the stack-safety tests: millions of cells
+*/
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub enum Node<T> {
+    Cons(T, Stream<T>),
+    Nil,
+}
+
+enum Thunk<T> {
+    Delayed(Box<dyn FnOnce() -> Node<T>>),
+    Forcing,
+    Forced(Node<T>),
+}
+
+// The shared step function behind `unfold`.
+type Step<S, T> = Rc<dyn Fn(&S) -> Option<(T, S)>>;
+
+// A shared, memoized cell: cloning a stream shares its evaluated prefix.
+pub struct Stream<T>(Rc<RefCell<Thunk<T>>>);
+
+impl<T> Clone for Stream<T> {
+    fn clone(&self) -> Self {
+        Stream(Rc::clone(&self.0))
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Clone + 'static> Stream<T> {
+    pub fn lazy(thunk: impl FnOnce() -> Node<T> + 'static) -> Self {
+        Stream(Rc::new(RefCell::new(Thunk::Delayed(Box::new(thunk)))))
+    }
+
+    pub fn nil() -> Self {
+        Stream(Rc::new(RefCell::new(Thunk::Forced(Node::Nil))))
+    }
+
+    pub fn cons(head: T, tail: Stream<T>) -> Self {
+        Stream(Rc::new(RefCell::new(Thunk::Forced(Node::Cons(head, tail)))))
+    }
+
+    // Runs this cell's thunk if it hasn't run yet.
+    fn force(&self) {
+        let state = std::mem::replace(&mut *self.0.borrow_mut(), Thunk::Forcing);
+        let node = match state {
+            Thunk::Delayed(thunk) => thunk(),
+            Thunk::Forced(node) => node,
+            Thunk::Forcing => panic!("stream cell depends on its own value"),
+        };
+        *self.0.borrow_mut() = Thunk::Forced(node);
+    }
+
+    // Forces this cell and splits it into head and tail (None at the end).
+    pub fn uncons(&self) -> Option<(T, Stream<T>)> {
+        self.force();
+        match &*self.0.borrow() {
+            Thunk::Forced(Node::Cons(head, tail)) => Some((head.clone(), tail.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn head(&self) -> Option<T> {
+        self.uncons().map(|(head, _)| head)
+    }
+
+    pub fn tail(&self) -> Option<Stream<T>> {
+        self.uncons().map(|(_, tail)| tail)
+    }
+
+    pub fn is_forced(&self) -> bool {
+        matches!(&*self.0.borrow(), Thunk::Forced(_))
+    }
+
+    // Forces the first n + 1 cells, one at a time.
+    pub fn nth(&self, n: usize) -> Option<T> {
+        self.iter().nth(n)
+    }
+
+    pub fn iter(&self) -> Iter<T> {
+        Iter { next: self.clone() }
+    }
+
+    // x, f(x), f(f(x)), ...
+    pub fn iterate(seed: T, f: impl Fn(&T) -> T + 'static) -> Self {
+        Self::unfold(seed, move |x| Some((x.clone(), f(x))))
+    }
+
+    // Each cell's element and the next state come from `f(state)`; None ends the stream.
+    pub fn unfold<S: 'static>(seed: S, f: impl Fn(&S) -> Option<(T, S)> + 'static) -> Self {
+        Self::unfold_shared(seed, Rc::new(f))
+    }
+
+    fn unfold_shared<S: 'static>(seed: S, f: Step<S, T>) -> Self {
+        Stream::lazy(move || match f(&seed) {
+            Some((elem, next)) => Node::Cons(elem, Self::unfold_shared(next, f)),
+            None => Node::Nil,
+        })
+    }
+
+    // Lazily wraps an iterator; it is advanced one step per forced cell.
+    pub fn from_iterator(iter: impl Iterator<Item = T> + 'static) -> Self {
+        let iter = RefCell::new(iter);
+        Self::unfold((), move |_| iter.borrow_mut().next().map(|elem| (elem, ())))
+    }
+
+    // The first n elements. Never forces the cell after the n-th.
+    pub fn take(&self, n: usize) -> Self {
+        if n == 0 {
+            return Stream::nil();
+        }
+        let source = self.clone();
+        Stream::lazy(move || match source.uncons() {
+            Some((head, tail)) => Node::Cons(head, tail.take(n - 1)),
+            None => Node::Nil,
+        })
+    }
+
+    // Pairs up elements; ends with the shorter stream.
+    pub fn zip<U: Clone + 'static>(&self, other: &Stream<U>) -> Stream<(T, U)> {
+        let (left, right) = (self.clone(), other.clone());
+        Stream::lazy(move || match (left.uncons(), right.uncons()) {
+            (Some((a, left)), Some((b, right))) => Node::Cons((a, b), left.zip(&right)),
+            _ => Node::Nil,
+        })
+    }
+
+    // a0, b0, a1, b1, ...; when one side ends the rest of the other follows.
+    pub fn interleave(&self, other: &Stream<T>) -> Self {
+        let (first, second) = (self.clone(), other.clone());
+        Stream::lazy(move || match first.uncons() {
+            Some((head, tail)) => Node::Cons(head, second.interleave(&tail)),
+            None => {
+                second.force();
+                second.take_node()
+            }
+        })
+    }
+
+    pub fn map<U: Clone + 'static>(&self, f: impl Fn(&T) -> U + 'static) -> Stream<U> {
+        self.map_shared(Rc::new(f))
+    }
+
+    fn map_shared<U: Clone + 'static>(&self, f: Rc<dyn Fn(&T) -> U>) -> Stream<U> {
+        let source = self.clone();
+        Stream::lazy(move || match source.uncons() {
+            Some((head, tail)) => Node::Cons(f(&head), tail.map_shared(f)),
+            None => Node::Nil,
+        })
+    }
+
+    pub fn filter(&self, keep: impl Fn(&T) -> bool + 'static) -> Self {
+        self.filter_shared(Rc::new(keep))
+    }
+
+    // Skips non-matching cells in a loop, so long gaps don't grow the stack.
+    fn filter_shared(&self, keep: Rc<dyn Fn(&T) -> bool>) -> Self {
+        let source = self.clone();
+        Stream::lazy(move || {
+            let mut cur = source;
+            while let Some((head, tail)) = cur.uncons() {
+                if keep(&head) {
+                    return Node::Cons(head, tail.filter_shared(keep));
+                }
+                cur = tail;
+            }
+            Node::Nil
+        })
+    }
+
+    // A copy of this (forced) cell's node, sharing the tail.
+    fn take_node(&self) -> Node<T> {
+        match &*self.0.borrow() {
+            Thunk::Forced(Node::Cons(head, tail)) => Node::Cons(head.clone(), tail.clone()),
+            _ => Node::Nil,
+        }
+    }
+}
+
+impl<T> Stream<T> {
+    // If this is the last handle to a forced cell, empties it and hands back its tail.
+    fn detach_tail(&self) -> Option<Stream<T>> {
+        if Rc::strong_count(&self.0) != 1 {
+            return None;
+        }
+        let mut state = self.0.try_borrow_mut().ok()?;
+        match std::mem::replace(&mut *state, Thunk::Forcing) {
+            Thunk::Forced(Node::Cons(_, tail)) => Some(tail),
+            _ => None,
+        }
+    }
+}
+
+// Frees a long chain of forced cells one by one instead of recursively.
+impl<T> Drop for Stream<T> {
+    fn drop(&mut self) {
+        let mut next = self.detach_tail();
+        while let Some(stream) = next {
+            next = stream.detach_tail();
+        }
+    }
+}
+
+pub struct Iter<T> {
+    next: Stream<T>,
+}
+
+impl<T: Clone + 'static> Iterator for Iter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (head, tail) = self.next.uncons()?;
+        self.next = tail;
+        Some(head)
+    }
+}
+
+pub fn naturals() -> Stream<u64> {
+    Stream::iterate(0, |n| n + 1)
+}
+
+pub fn fibonacci() -> Stream<u64> {
+    // State: the current number and the next one, if it still fits in a u64.
+    Stream::unfold(Some((0u64, Some(1u64))), |state| {
+        let (a, b) = (*state)?;
+        Some((a, b.map(|b| (b, a.checked_add(b)))))
+    })
+}
+
+fn show<T: Clone + std::fmt::Display + 'static>(label: &str, stream: &Stream<T>) {
+    let items: Vec<String> = stream.iter().map(|x| x.to_string()).collect();
+    println!("{:<12} {}, ...", label, items.join(", "));
+}
+
+// ---------- 1. INFINITE STREAMS ----------
+fn infinite_example() {
+    show("naturals", &naturals().take(10));
+    show("fibonacci", &fibonacci().take(12));
+    show("evens", &naturals().filter(|n| n % 2 == 0).take(5));
+    show("squares", &naturals().map(|n| n * n).take(6));
+    show("interleave", &naturals().interleave(&naturals().map(|n| 100 + n)).take(8));
+
+    let pairs: Vec<(u64, u64)> = naturals().zip(&fibonacci()).take(4).iter().collect();
+    println!("{:<12} {:?}", "zip", pairs);
+
+    // The u64 Fibonacci numbers end where the next one would overflow.
+    assert_eq!(fibonacci().iter().count(), 94);
+}
+
+// ---------- 2. LAZINESS AND MEMOIZATION ----------
+fn memo_example() {
+    let calls = Rc::new(std::cell::Cell::new(0));
+    let counter = calls.clone();
+    let squares = naturals().map(move |n| {
+        counter.set(counter.get() + 1);
+        n * n
+    });
+
+    let first_five = squares.take(5);
+    assert_eq!(calls.get(), 0, "take must not force anything");
+    assert!(!first_five.is_forced());
+
+    assert_eq!(first_five.iter().sum::<u64>(), 30);
+    assert_eq!(first_five.iter().sum::<u64>(), 30);
+    assert_eq!(calls.get(), 5, "each cell is computed once");
+    println!("memoized: 5 squares summed twice with {} calls", calls.get());
+}
+
+fn main() {
+    infinite_example();
+    memo_example();
+}
+
+// ---------- 3. STACK SAFETY ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack_safety_check() {
+        assert_eq!(naturals().nth(5_000_000), Some(5_000_000));
+
+        // A sparse filter skips a million cells inside a single force.
+        let sparse = naturals().filter(|n| n % 1_000_000 == 999_999);
+        assert_eq!(sparse.head(), Some(999_999));
+
+        // Keep the head alive while forcing a million cells, then drop them all.
+        let kept = naturals();
+        assert_eq!(kept.nth(1_000_000), Some(1_000_000));
+        drop(kept);
+    }
+}
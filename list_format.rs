@@ -0,0 +1,309 @@
+/*
+List formats — the text and JSON readers from 1.rs (section 5c) as a module
+
+The boxed List in 1.rs and the arena List in arena_list.rs both read and write
+the same two formats, so they share this file with `mod list_format;`. The
+readers return the elements in order and each list builds itself from them:
+
+Format	Example	Written by	Read by
+text	3, 2, 1, Nil	Display	parse_items
+JSON	[3,2,1]	write_json_items	read_json_items, from_json_with
+
+Errors carry the byte offset where reading stopped: "3, two, Nil" fails at 3.
+*/
+
+// Shared by several programs; each one uses a different subset.
+#![allow(dead_code)]
+
+use std::fmt;
+use std::str::FromStr;
+
+// Where and why a list failed to parse; `pos` is a byte offset into the input.
+#[derive(Debug, PartialEq)]
+pub struct ParseListError {
+    pub pos: usize,
+    pub kind: ParseListErrorKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseListErrorKind {
+    Expected(&'static str),
+    InvalidElement(String),
+    TrailingInput,
+}
+
+impl fmt::Display for ParseListError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ParseListErrorKind::Expected(what) => write!(f, "expected {} at byte {}", what, self.pos),
+            ParseListErrorKind::InvalidElement(why) => write!(f, "invalid element at byte {}: {}", self.pos, why),
+            ParseListErrorKind::TrailingInput => write!(f, "unexpected input after Nil at byte {}", self.pos),
+        }
+    }
+}
+
+impl std::error::Error for ParseListError {}
+
+pub fn parse_error<V>(pos: usize, kind: ParseListErrorKind) -> Result<V, ParseListError> {
+    Err(ParseListError { pos, kind })
+}
+
+// Reads "3, 2, 1, Nil". Elements are split on commas, so elements whose own
+// text contains a comma don't round-trip through this format - use JSON for those.
+// Only the one space Display puts after each comma is skipped: the rest belongs
+// to the element, so " x" comes back as " x" (and " 3" is not an i32).
+pub fn parse_items<T: FromStr>(input: &str) -> Result<Vec<T>, ParseListError>
+where
+    T::Err: fmt::Display,
+{
+    let mut items = Vec::new();
+    let mut pos = 0;
+    loop {
+        let end = input[pos..].find(',').map_or(input.len(), |i| pos + i);
+        let token = &input[pos..end];
+        if token == "Nil" {
+            if end < input.len() {
+                return parse_error(end, ParseListErrorKind::TrailingInput);
+            }
+            return Ok(items);
+        }
+        if token.is_empty() && end == input.len() {
+            return parse_error(pos, ParseListErrorKind::Expected("Nil"));
+        }
+        match token.parse() {
+            Ok(value) => items.push(value),
+            // An empty element is fine for types that parse "" (String), a gap otherwise.
+            Err(_) if token.is_empty() => return parse_error(pos, ParseListErrorKind::Expected("an element")),
+            Err(err) => return parse_error(pos, ParseListErrorKind::InvalidElement(err.to_string())),
+        }
+        if end == input.len() {
+            return parse_error(end, ParseListErrorKind::Expected("`, Nil`"));
+        }
+        pos = end + 1;
+        if input[pos..].starts_with(' ') {
+            pos += 1;
+        }
+    }
+}
+
+// Values that can be written as and read from JSON. `read_json` starts at
+// `*pos` (after any whitespace) and leaves `*pos` just past the value.
+pub trait Json: Sized {
+    fn write_json(&self, out: &mut String);
+    fn read_json(input: &str, pos: &mut usize) -> Result<Self, ParseListError>;
+}
+
+pub fn skip_whitespace(input: &str, pos: &mut usize) {
+    *pos += input[*pos..].len() - input[*pos..].trim_start().len();
+}
+
+// The JSON number at `*pos`, parsed as T.
+fn read_json_number<T: FromStr>(input: &str, pos: &mut usize) -> Result<T, ParseListError>
+where
+    T::Err: fmt::Display,
+{
+    let start = *pos;
+    let len = input[start..]
+        .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+        .unwrap_or(input.len() - start);
+    if len == 0 {
+        return parse_error(start, ParseListErrorKind::Expected("a number"));
+    }
+    match input[start..start + len].parse() {
+        Ok(value) => {
+            *pos += len;
+            Ok(value)
+        }
+        Err(err) => parse_error(start, ParseListErrorKind::InvalidElement(format!("{}", err))),
+    }
+}
+
+macro_rules! json_number {
+    ($($t:ty),*) => {$(
+        impl Json for $t {
+            fn write_json(&self, out: &mut String) {
+                out.push_str(&self.to_string());
+            }
+
+            fn read_json(input: &str, pos: &mut usize) -> Result<Self, ParseListError> {
+                read_json_number(input, pos)
+            }
+        }
+    )*};
+}
+
+json_number!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+// JSON numbers can't be NaN or infinite (`NaN` and `inf` aren't valid JSON), so
+// those three are written as the strings "NaN", "Infinity" and "-Infinity".
+macro_rules! json_float {
+    ($($t:ty),*) => {$(
+        impl Json for $t {
+            fn write_json(&self, out: &mut String) {
+                match *self {
+                    x if x.is_nan() => out.push_str("\"NaN\""),
+                    <$t>::INFINITY => out.push_str("\"Infinity\""),
+                    <$t>::NEG_INFINITY => out.push_str("\"-Infinity\""),
+                    x => out.push_str(&x.to_string()),
+                }
+            }
+
+            fn read_json(input: &str, pos: &mut usize) -> Result<Self, ParseListError> {
+                let start = *pos;
+                if !input[start..].starts_with('"') {
+                    return read_json_number(input, pos);
+                }
+                match String::read_json(input, pos)?.as_str() {
+                    "NaN" => Ok(<$t>::NAN),
+                    "Infinity" => Ok(<$t>::INFINITY),
+                    "-Infinity" => Ok(<$t>::NEG_INFINITY),
+                    _ => parse_error(start, ParseListErrorKind::Expected("a number, \"NaN\" or \"Infinity\"")),
+                }
+            }
+        }
+    )*};
+}
+
+json_float!(f32, f64);
+
+impl Json for bool {
+    fn write_json(&self, out: &mut String) {
+        out.push_str(if *self { "true" } else { "false" });
+    }
+
+    fn read_json(input: &str, pos: &mut usize) -> Result<Self, ParseListError> {
+        for (word, value) in [("true", true), ("false", false)] {
+            if input[*pos..].starts_with(word) {
+                *pos += word.len();
+                return Ok(value);
+            }
+        }
+        parse_error(*pos, ParseListErrorKind::Expected("`true` or `false`"))
+    }
+}
+
+impl Json for String {
+    fn write_json(&self, out: &mut String) {
+        out.push('"');
+        for c in self.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+    }
+
+    fn read_json(input: &str, pos: &mut usize) -> Result<Self, ParseListError> {
+        if !input[*pos..].starts_with('"') {
+            return parse_error(*pos, ParseListErrorKind::Expected("a string"));
+        }
+        let mut value = String::new();
+        let start = *pos + 1;
+        let mut chars = input[start..].char_indices().map(|(i, c)| (start + i, c));
+        while let Some((at, c)) = chars.next() {
+            match c {
+                '"' => {
+                    *pos = at + 1;
+                    return Ok(value);
+                }
+                '\\' => {
+                    let escaped = match chars.next() {
+                        Some((_, '"')) => '"',
+                        Some((_, '\\')) => '\\',
+                        Some((_, '/')) => '/',
+                        Some((_, 'b')) => '\u{8}',
+                        Some((_, 'f')) => '\u{c}',
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 't')) => '\t',
+                        Some((_, 'u')) => {
+                            let code = match hex_escape(&mut chars) {
+                                // Outside the BMP a char is a UTF-16 surrogate pair: "\ud83d\ude00" is 😀.
+                                Some(high @ 0xd800..=0xdbff) => match (chars.next(), chars.next(), hex_escape(&mut chars)) {
+                                    (Some((_, '\\')), Some((_, 'u')), Some(low @ 0xdc00..=0xdfff)) => {
+                                        Some(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+                                    }
+                                    _ => None,
+                                },
+                                code => code,
+                            };
+                            match code.and_then(char::from_u32) {
+                                Some(c) => c,
+                                None => return parse_error(at, ParseListErrorKind::InvalidElement("bad \\u escape".to_string())),
+                            }
+                        }
+                        _ => return parse_error(at, ParseListErrorKind::Expected("a valid escape")),
+                    };
+                    value.push(escaped);
+                }
+                c => value.push(c),
+            }
+        }
+        parse_error(input.len(), ParseListErrorKind::Expected("closing `\"`"))
+    }
+}
+
+// The four hex digits after `\u`; a lone surrogate (d800-dfff) is left for the caller to reject.
+fn hex_escape(chars: &mut impl Iterator<Item = (usize, char)>) -> Option<u32> {
+    let hex: String = chars.take(4).map(|(_, c)| c).collect();
+    if hex.len() != 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(&hex, 16).ok()
+}
+
+// A list is a JSON array: 3, 2, 1, Nil ⇄ [3,2,1]. Lists of lists nest as arrays of arrays.
+pub fn write_json_items<'i, T: Json + 'i>(items: impl IntoIterator<Item = &'i T>, out: &mut String) {
+    out.push('[');
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        item.write_json(out);
+    }
+    out.push(']');
+}
+
+pub fn read_json_items<T: Json>(input: &str, pos: &mut usize) -> Result<Vec<T>, ParseListError> {
+    if !input[*pos..].starts_with('[') {
+        return parse_error(*pos, ParseListErrorKind::Expected("`[`"));
+    }
+    *pos += 1;
+    let mut items = Vec::new();
+    skip_whitespace(input, pos);
+    if input[*pos..].starts_with(']') {
+        *pos += 1;
+        return Ok(items);
+    }
+    loop {
+        skip_whitespace(input, pos);
+        items.push(T::read_json(input, pos)?);
+        skip_whitespace(input, pos);
+        match input[*pos..].chars().next() {
+            Some(',') => *pos += 1,
+            Some(']') => {
+                *pos += 1;
+                return Ok(items);
+            }
+            _ => return parse_error(*pos, ParseListErrorKind::Expected("`,` or `]`")),
+        }
+    }
+}
+
+// Reads a whole document with `read`: whitespace around the value is fine, anything else is an error.
+pub fn from_json_with<V>(input: &str, read: impl FnOnce(&str, &mut usize) -> Result<V, ParseListError>) -> Result<V, ParseListError> {
+    let mut pos = 0;
+    skip_whitespace(input, &mut pos);
+    let value = read(input, &mut pos)?;
+    skip_whitespace(input, &mut pos);
+    if pos < input.len() {
+        return parse_error(pos, ParseListErrorKind::TrailingInput);
+    }
+    Ok(value)
+}
@@ -0,0 +1,412 @@
+/*
+Lock-free stack (Treiber stack) built on the Cons / Nil shape
+
+`List` in 1.rs already is a stack: `prepend` pushes, the head is the top, and
+`Nil` is the bottom. A Treiber stack is the same singly-linked shape, but the
+head pointer is an AtomicPtr that many threads update with compare-and-swap:
+
+    push:  node.next = head;         CAS(head: old → node)   // retry if head moved
+    pop:   next = head.next;         CAS(head: old → next)   // retry if head moved
+
+No thread ever holds a lock, so a stalled thread can't block the others.
+
+🧹 The hard part: freeing popped nodes
+Thread A reads `head`, is paused, and meanwhile thread B pops that node and
+frees it. When A wakes up it reads `head.next` from freed memory, and if the
+allocator hands the same address to a new node, A's CAS can even succeed with
+a stale `next` (the ABA problem).
+
+Hazard pointers fix both:
+✅ Before touching a node a thread publishes its address in a hazard slot and
+   re-checks that the node is still the head.
+✅ A popped node is not freed right away; it goes on a "retired" list.
+✅ Every so often the retired list is scanned: nodes that no hazard slot points
+   to are freed, the rest wait for the next scan.
+
+Operation	Notes
+push(value)	Lock-free
+pop()	Lock-free; returns a clone, the original is dropped when the node is freed
+peek()	Lock-free; a clone of the top value
+
+pop hands out a clone so that a concurrent peek() can still read the node's
+value safely until the node is reclaimed. So every popped value exists twice
+for a while, and a T with a side-effecting Drop sees two drops (of two
+distinct values). Store Arc<T> to make clones cheap and to get exactly one
+drop of the shared value.
+
+$ rustc -O lock_free_stack.rs && ./lock_free_stack
+$ rustc -O --test lock_free_stack.rs && ./lock_free_stack          // the stress tests
+$ rustc +nightly -Zsanitizer=address --test lock_free_stack.rs && ./lock_free_stack
+*/
+
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering::SeqCst};
+
+struct Node<T> {
+    value: T,
+    next: AtomicPtr<Node<T>>,
+    // Link in the retired list once the node has been popped.
+    retired_next: AtomicPtr<Node<T>>,
+}
+
+// One hazard slot. Slots are never freed while the stack lives, only reused.
+struct HazardRecord {
+    protected: AtomicPtr<u8>,
+    in_use: AtomicBool,
+    next: *mut HazardRecord,
+}
+
+pub struct Stack<T> {
+    head: AtomicPtr<Node<T>>,
+    hazards: AtomicPtr<HazardRecord>,
+    retired: AtomicPtr<Node<T>>,
+    retired_count: AtomicUsize,
+}
+
+// SAFETY: values move between threads (Send) and are read through shared
+// references by concurrent peek/pop calls (Sync).
+unsafe impl<T: Send + Sync> Send for Stack<T> {}
+unsafe impl<T: Send + Sync> Sync for Stack<T> {}
+
+// A claimed hazard slot; released on drop.
+struct Hazard<'a> {
+    record: &'a HazardRecord,
+}
+
+impl Hazard<'_> {
+    fn protect<T>(&self, node: *mut Node<T>) {
+        self.record.protected.store(node.cast(), SeqCst);
+    }
+}
+
+impl Drop for Hazard<'_> {
+    fn drop(&mut self) {
+        self.record.protected.store(ptr::null_mut(), SeqCst);
+        self.record.in_use.store(false, SeqCst);
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Clone> Stack<T> {
+    // Scan the retired list once it holds this many nodes more than there are hazard slots.
+    const SCAN_SLACK: usize = 32;
+
+    pub fn new() -> Self {
+        Stack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            hazards: AtomicPtr::new(ptr::null_mut()),
+            retired: AtomicPtr::new(ptr::null_mut()),
+            retired_count: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn push(&self, value: T) {
+        let node = Box::into_raw(Box::new(Node {
+            value,
+            next: AtomicPtr::new(ptr::null_mut()),
+            retired_next: AtomicPtr::new(ptr::null_mut()),
+        }));
+        let mut head = self.head.load(SeqCst);
+        loop {
+            // SAFETY: `node` is not shared until the CAS below succeeds.
+            unsafe { (*node).next.store(head, SeqCst) };
+            match self.head.compare_exchange(head, node, SeqCst, SeqCst) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let hazard = self.acquire_hazard();
+        loop {
+            let head = self.protected_head(&hazard)?;
+            // SAFETY: `head` is protected by our hazard slot, so it hasn't been freed.
+            let next = unsafe { (*head).next.load(SeqCst) };
+            if self.head.compare_exchange(head, next, SeqCst, SeqCst).is_ok() {
+                // A clone, not a move: a peek() that protected this node before the CAS
+                // may be cloning the value right now, and moving it out (ptr::read) would
+                // let us drop or mutate it under that reader. The original is dropped
+                // when the node is reclaimed, after the last hazard on it is gone.
+                // SAFETY: still protected; we are the only thread that unlinked it.
+                let value = unsafe { (*head).value.clone() };
+                drop(hazard);
+                self.retire(head);
+                return Some(value);
+            }
+        }
+    }
+
+    pub fn peek(&self) -> Option<T> {
+        let hazard = self.acquire_hazard();
+        let head = self.protected_head(&hazard)?;
+        // SAFETY: protected by our hazard slot; values are only dropped on reclamation.
+        Some(unsafe { (*head).value.clone() })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(SeqCst).is_null()
+    }
+
+    // Publishes the current head in `hazard` and confirms it is still the head,
+    // so it can't be freed until the hazard is cleared. None when empty.
+    fn protected_head(&self, hazard: &Hazard) -> Option<*mut Node<T>> {
+        let mut head = self.head.load(SeqCst);
+        loop {
+            if head.is_null() {
+                return None;
+            }
+            hazard.protect(head);
+            let current = self.head.load(SeqCst);
+            if current == head {
+                return Some(head);
+            }
+            head = current;
+        }
+    }
+
+    fn acquire_hazard(&self) -> Hazard<'_> {
+        let mut cur = self.hazards.load(SeqCst);
+        while !cur.is_null() {
+            // SAFETY: hazard records live as long as the stack.
+            let record = unsafe { &*cur };
+            if record.in_use.compare_exchange(false, true, SeqCst, SeqCst).is_ok() {
+                return Hazard { record };
+            }
+            cur = record.next;
+        }
+
+        let record = Box::into_raw(Box::new(HazardRecord {
+            protected: AtomicPtr::new(ptr::null_mut()),
+            in_use: AtomicBool::new(true),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.hazards.load(SeqCst);
+        loop {
+            // SAFETY: `record` is not shared until the CAS below succeeds.
+            unsafe { (*record).next = head };
+            match self.hazards.compare_exchange(head, record, SeqCst, SeqCst) {
+                // SAFETY: as above, records live as long as the stack.
+                Ok(_) => return Hazard { record: unsafe { &*record } },
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    fn retire(&self, node: *mut Node<T>) {
+        // Counted before it is published: once it is on the list, a concurrent
+        // scan may free it and decrement, which must not take the count below zero.
+        let retired = self.retired_count.fetch_add(1, SeqCst) + 1;
+        self.push_retired(node);
+        if retired > self.hazard_slots() + Self::SCAN_SLACK {
+            self.scan();
+        }
+    }
+
+    fn push_retired(&self, node: *mut Node<T>) {
+        let mut head = self.retired.load(SeqCst);
+        loop {
+            // SAFETY: `node` is unlinked and owned by the retiring thread until published.
+            unsafe { (*node).retired_next.store(head, SeqCst) };
+            match self.retired.compare_exchange(head, node, SeqCst, SeqCst) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    fn hazard_slots(&self) -> usize {
+        let mut count = 0;
+        let mut cur = self.hazards.load(SeqCst);
+        while !cur.is_null() {
+            count += 1;
+            // SAFETY: hazard records live as long as the stack.
+            cur = unsafe { (*cur).next };
+        }
+        count
+    }
+
+    // Frees every retired node no hazard slot points to; re-retires the rest.
+    fn scan(&self) {
+        let mut node = self.retired.swap(ptr::null_mut(), SeqCst);
+        let mut protected = Vec::new();
+        let mut cur = self.hazards.load(SeqCst);
+        while !cur.is_null() {
+            // SAFETY: hazard records live as long as the stack.
+            unsafe {
+                protected.push((*cur).protected.load(SeqCst));
+                cur = (*cur).next;
+            }
+        }
+
+        while !node.is_null() {
+            // SAFETY: retired nodes are unlinked from the stack, and this thread
+            // took exclusive ownership of the whole retired chain with the swap.
+            let next = unsafe { (*node).retired_next.load(SeqCst) };
+            if protected.contains(&node.cast()) {
+                self.push_retired(node);
+            } else {
+                self.retired_count.fetch_sub(1, SeqCst);
+                // SAFETY: no hazard protects it and it can't become the head again.
+                drop(unsafe { Box::from_raw(node) });
+            }
+            node = next;
+        }
+    }
+}
+
+impl<T: Clone> Default for Stack<T> {
+    fn default() -> Self {
+        Stack::new()
+    }
+}
+
+// `&mut self` means no other thread can be inside the stack anymore.
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        // SAFETY: every pointer below is owned by the stack and freed exactly once.
+        unsafe {
+            let mut node = *self.head.get_mut();
+            while !node.is_null() {
+                let next = (*node).next.load(SeqCst);
+                drop(Box::from_raw(node));
+                node = next;
+            }
+            let mut node = *self.retired.get_mut();
+            while !node.is_null() {
+                let next = (*node).retired_next.load(SeqCst);
+                drop(Box::from_raw(node));
+                node = next;
+            }
+            let mut record = *self.hazards.get_mut();
+            while !record.is_null() {
+                let next = (*record).next;
+                drop(Box::from_raw(record));
+                record = next;
+            }
+        }
+    }
+}
+
+// ---------- 1. SINGLE THREAD ----------
+fn basic_example() {
+    let stack = Stack::new();
+    for i in 1..=3 {
+        stack.push(i);
+    }
+    assert_eq!(stack.peek(), Some(3));
+    assert_eq!(stack.pop(), Some(3));
+    assert_eq!(stack.pop(), Some(2));
+    assert_eq!(stack.pop(), Some(1));
+    assert_eq!(stack.pop(), None);
+    assert!(stack.is_empty());
+    println!("push 1, 2, 3 → pop 3, 2, 1, then None");
+}
+
+fn main() {
+    basic_example();
+}
+
+// ---------- 2. STRESS TESTS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    // Counts drops so leaks and double frees show up as a wrong total.
+    #[derive(Clone)]
+    struct Tracked {
+        id: usize,
+        payload: String,
+        drops: Arc<AtomicUsize>,
+    }
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.drops.fetch_add(1, SeqCst);
+        }
+    }
+
+    // Every thread pushes its own values and pops as many as it pushed;
+    // every value must come out exactly once.
+    fn stress_push_pop(threads: usize, per_thread: usize) {
+        let stack = Arc::new(Stack::new());
+        let drops = Arc::new(AtomicUsize::new(0));
+
+        let workers: Vec<_> = (0..threads)
+            .map(|t| {
+                let (stack, drops) = (stack.clone(), drops.clone());
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    for i in 0..per_thread {
+                        let id = t * per_thread + i;
+                        stack.push(Tracked { id, payload: id.to_string(), drops: drops.clone() });
+                        if i % 3 == 2 {
+                            for _ in 0..2 {
+                                popped.extend(stack.pop().map(|v| v.id));
+                            }
+                        }
+                    }
+                    while popped.len() < per_thread * 2 / 3 {
+                        popped.extend(stack.pop().map(|v| v.id));
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut seen: Vec<usize> = workers.into_iter().flat_map(|w| w.join().unwrap()).collect();
+        while let Some(v) = stack.pop() {
+            seen.push(v.id);
+        }
+        seen.sort_unstable();
+        assert_eq!(seen, (0..threads * per_thread).collect::<Vec<_>>(), "every value popped exactly once");
+
+        drop(Arc::try_unwrap(stack).ok().expect("all workers joined"));
+        // Each value is dropped twice: the clone handed out by pop, and the original on reclamation.
+        assert_eq!(drops.load(SeqCst), 2 * threads * per_thread);
+    }
+
+    // Peekers read values while poppers race to pop and free the same nodes.
+    fn stress_peek_while_popping(threads: usize, values: usize) {
+        let stack = Arc::new(Stack::new());
+        let drops = Arc::new(AtomicUsize::new(0));
+        for id in 0..values {
+            stack.push(Tracked { id, payload: format!("value-{}", id), drops: drops.clone() });
+        }
+
+        let popped = Arc::new(AtomicUsize::new(0));
+        let workers: Vec<_> = (0..threads)
+            .map(|t| {
+                let (stack, popped) = (stack.clone(), popped.clone());
+                thread::spawn(move || {
+                    while !stack.is_empty() {
+                        if t % 2 == 0 {
+                            if let Some(v) = stack.peek() {
+                                assert_eq!(v.payload, format!("value-{}", v.id));
+                            }
+                        } else if stack.pop().is_some() {
+                            popped.fetch_add(1, SeqCst);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert_eq!(popped.load(SeqCst), values);
+    }
+
+    #[test]
+    fn push_pop_check() {
+        stress_push_pop(8, 30_000);
+    }
+
+    #[test]
+    fn peek_while_popping_check() {
+        stress_peek_while_popping(8, 100_000);
+    }
+}
@@ -0,0 +1,342 @@
+/*
+Operation — the arithmetic set from 1.rs (section 4) as a module
+
+The calculator programs (evaluate.rs, ...) share this file with `mod operation;`,
+the same way a crate splits code into modules:
+
+$ rustc evaluate.rs        // rustc finds operation.rs next to it
+
+Operators and their precedence follow the operator table in Primitives.rs
+("Precedence is similar to C"), from loosest to tightest binding:
+
+Level	Operators	Operation
+1	|	BitOr
+2	^	BitXor
+3	&	BitAnd
+4	<< >>	Shl, Shr
+5	+ -	Add, Subtract
+6	* / %	Multiply, Divide, Remainder
+-	pow(a, b) min(a, b) max(a, b)	Pow, Min, Max (function call syntax)
+
+All binary operators are left-associative: 8 - 3 - 2 == (8 - 3) - 2.
+Shifts are arithmetic like the rest: x << y is x · 2^y and x >> y is ⌊x / 2^y⌋,
+so they overflow (and wrap or saturate, by Mode) exactly when a bit is lost.
+
+`run` works on every primitive number type through the `Number` trait below
+(i8..i128, u8..u128, isize, usize, f32, f64); conformance.rs checks every
+operation against every type:
+
+$ rustc conformance.rs && ./conformance
+
+bigint.rs and rational.rs add two exact types that never overflow (see exact.rs).
+*/
+
+// Shared by several programs; each one uses a different subset.
+#![allow(dead_code)]
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Pow,
+    Min,
+    Max,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+}
+
+// Why `run` refused to produce a value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpError {
+    Overflow,
+    DivideByZero,
+    NegativeExponent, // integer pow only
+    NegativeShift,    // << and >> by a negative amount
+    NotANumber,       // float operand or result is NaN
+    Unsupported,      // bit operations and shifts on floats
+}
+
+impl fmt::Display for OpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OpError::Overflow => write!(f, "arithmetic overflow"),
+            OpError::DivideByZero => write!(f, "division by zero"),
+            OpError::NegativeExponent => write!(f, "negative exponent"),
+            OpError::NegativeShift => write!(f, "negative shift amount"),
+            OpError::NotANumber => write!(f, "result is not a number"),
+            OpError::Unsupported => write!(f, "operation not supported for this type"),
+        }
+    }
+}
+
+impl std::error::Error for OpError {}
+
+// What to do when the exact result doesn't fit in the type. Shifts have an
+// exact result too (x << y is x · 2^y, x >> y is ⌊x / 2^y⌋), so i32::MAX << 1
+// overflows like i32::MAX * 2 does.
+// Every other error (division by zero, NaN, ...) is an error in every mode.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Mode {
+    #[default]
+    Checked, // return Err(OpError::Overflow)
+    Wrapping, // integers wrap around (i32::MAX + 1 == i32::MIN), floats give ±inf
+    Saturating, // clamp to MIN / MAX of the type
+}
+
+// Every primitive number type from Primitives.rs: i8..i128, isize, u8..u128, usize, f32, f64.
+//
+// The same rules for all of them:
+// ✅ Divide / Remainder by zero → DivideByZero (for floats too, instead of inf / NaN)
+// ✅ overflow → Overflow, or wrap / saturate by Mode; for floats "overflow" is an
+//    infinite result from finite operands, and wrapping keeps that IEEE ±inf
+// ✅ a NaN operand or result → NotANumber, so NaN never comes out of `run`
+// ✅ integer Pow needs a non-negative exponent; float Pow is powf
+// ✅ Shl / Shr need a non-negative amount and are exact like the rest: any amount,
+//    BITS and more included, shifts bits out (1 << 40 overflows an i32, -1 >> 40
+//    is -1); Wrapping keeps the low bits and Saturating clamps like for Multiply
+// ⚠️ BitAnd, BitOr, BitXor, Shl, Shr are integer-only → Unsupported for floats
+//
+// Clone rather than Copy, so heap-allocated types (BigInt, Rational) fit too.
+pub trait Number: Clone + PartialOrd + fmt::Debug + fmt::Display {
+    const NAME: &'static str;
+
+    // An unsigned integer literal: digits in `radix`, without prefix, sign or `_`.
+    // None when it doesn't fit in the type.
+    fn from_digits(digits: &str, radix: u32) -> Option<Self>;
+
+    fn run(op: Operation, x: Self, y: Self, mode: Mode) -> Result<Self, OpError>;
+}
+
+macro_rules! integer_number {
+    ($($t:ident)*) => {$(
+        impl Number for $t {
+            const NAME: &'static str = stringify!($t);
+
+            fn from_digits(digits: &str, radix: u32) -> Option<Self> {
+                $t::from_str_radix(digits, radix).ok()
+            }
+
+            #[allow(unused_comparisons)] // `< 0` is always false for the unsigned types
+            fn run(op: Operation, x: Self, y: Self, mode: Mode) -> Result<Self, OpError> {
+                use Mode::*;
+                use Operation::*;
+
+                if matches!(op, Divide | Remainder) && y == 0 {
+                    return Err(OpError::DivideByZero);
+                }
+                if op == Pow && y < 0 {
+                    return Err(OpError::NegativeExponent);
+                }
+                if matches!(op, Shl | Shr) && y < 0 {
+                    return Err(OpError::NegativeShift);
+                }
+                // Amounts past BITS shift out every bit, the same as BITS itself.
+                let shift = u32::try_from(y).unwrap_or(u32::MAX).min($t::BITS);
+                // pow takes a u32 exponent. Anything bigger overflows, except for 0, 1 and -1,
+                // whose powers only depend on whether the exponent is odd.
+                let exponent = u32::try_from(y).ok();
+                let small_base = x == 0 || x.checked_mul(x) == Some(1);
+
+                let checked = match op {
+                    Add => x.checked_add(y),
+                    Subtract => x.checked_sub(y),
+                    Multiply => x.checked_mul(y),
+                    Divide => x.checked_div(y),
+                    // checked_rem is None only for MIN % -1, whose exact value 0 fits.
+                    Remainder => Some(x.checked_rem(y).unwrap_or(0)),
+                    Pow => match exponent {
+                        Some(exponent) => x.checked_pow(exponent),
+                        None if small_base => x.checked_pow(2 + (y % 2) as u32),
+                        None => None,
+                    },
+                    Min => Some(x.min(y)),
+                    Max => Some(x.max(y)),
+                    BitAnd => Some(x & y),
+                    BitOr => Some(x | y),
+                    BitXor => Some(x ^ y),
+                    // Exact when shifting back gives x again: no bit, sign bit included, fell off.
+                    Shl if x == 0 => Some(0),
+                    Shl => x.checked_shl(shift).filter(|value| value >> shift == x),
+                    // All bits shifted out leaves the sign: 0, or -1 (all ones) for a negative x.
+                    Shr => Some(x.checked_shr(shift).unwrap_or(if x < 0 { !0 } else { 0 })),
+                };
+
+                match (checked, mode) {
+                    (Some(value), _) => Ok(value),
+                    (None, Checked) => Err(OpError::Overflow),
+                    (None, Wrapping) => Ok(match op {
+                        Add => x.wrapping_add(y),
+                        Subtract => x.wrapping_sub(y),
+                        Multiply => x.wrapping_mul(y),
+                        Divide => x.wrapping_div(y),
+                        Pow => match exponent {
+                            Some(exponent) => x.wrapping_pow(exponent),
+                            None => {
+                                // Square-and-multiply over every bit of the exponent.
+                                let (mut base, mut exponent, mut value) = (x, y, 1 as $t);
+                                while exponent != 0 {
+                                    if exponent % 2 == 1 {
+                                        value = value.wrapping_mul(base);
+                                    }
+                                    base = base.wrapping_mul(base);
+                                    exponent /= 2;
+                                }
+                                value
+                            }
+                        },
+                        Shl => x.checked_shl(shift).unwrap_or(0),
+                        _ => unreachable!("{:?} never overflows", op),
+                    }),
+                    (None, Saturating) => Ok(match op {
+                        Add => x.saturating_add(y),
+                        Subtract => x.saturating_sub(y),
+                        Multiply => x.saturating_mul(y),
+                        Divide => x.saturating_div(y),
+                        Pow => match exponent {
+                            Some(exponent) => x.saturating_pow(exponent),
+                            None if x < 0 && y % 2 == 1 => $t::MIN,
+                            None => $t::MAX,
+                        },
+                        Shl if x < 0 => $t::MIN,
+                        Shl => $t::MAX,
+                        _ => unreachable!("{:?} never overflows", op),
+                    }),
+                }
+            }
+        }
+    )*};
+}
+
+integer_number!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+macro_rules! float_number {
+    ($($t:ident)*) => {$(
+        impl Number for $t {
+            const NAME: &'static str = stringify!($t);
+
+            // Rounded to the nearest float; digits beyond MAX give None instead of inf.
+            fn from_digits(digits: &str, radix: u32) -> Option<Self> {
+                let value = match radix {
+                    10 if digits.bytes().all(|b| b.is_ascii_digit()) => digits.parse().ok()?,
+                    _ => digits.chars().try_fold(0.0, |value: $t, c| Some(value * radix as $t + c.to_digit(radix)? as $t))?,
+                };
+                Some(value).filter(|value: &$t| value.is_finite())
+            }
+
+            fn run(op: Operation, x: Self, y: Self, mode: Mode) -> Result<Self, OpError> {
+                use Operation::*;
+
+                if matches!(op, BitAnd | BitOr | BitXor | Shl | Shr) {
+                    return Err(OpError::Unsupported);
+                }
+                if x.is_nan() || y.is_nan() {
+                    return Err(OpError::NotANumber);
+                }
+                if matches!(op, Divide | Remainder) && y == 0.0 {
+                    return Err(OpError::DivideByZero);
+                }
+                let value = match op {
+                    Add => x + y,
+                    Subtract => x - y,
+                    Multiply => x * y,
+                    Divide => x / y,
+                    Remainder => x % y,
+                    Pow => x.powf(y),
+                    Min => x.min(y),
+                    Max => x.max(y),
+                    _ => unreachable!("integer-only operations return early"),
+                };
+                if value.is_nan() {
+                    return Err(OpError::NotANumber); // inf - inf, 0 * inf, (-8)^0.5, ...
+                }
+                if value.is_infinite() && x.is_finite() && y.is_finite() {
+                    return match mode {
+                        Mode::Checked => Err(OpError::Overflow),
+                        Mode::Wrapping => Ok(value),
+                        Mode::Saturating if value > 0.0 => Ok($t::MAX),
+                        Mode::Saturating => Ok($t::MIN),
+                    };
+                }
+                Ok(value)
+            }
+        }
+    )*};
+}
+
+float_number!(f32 f64);
+
+impl Operation {
+    pub const ALL: [Operation; 13] = [
+        Self::Add,
+        Self::Subtract,
+        Self::Multiply,
+        Self::Divide,
+        Self::Remainder,
+        Self::Pow,
+        Self::Min,
+        Self::Max,
+        Self::BitAnd,
+        Self::BitOr,
+        Self::BitXor,
+        Self::Shl,
+        Self::Shr,
+    ];
+
+    // The infix symbol, or the function name for pow / min / max.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Multiply => "*",
+            Self::Divide => "/",
+            Self::Remainder => "%",
+            Self::Pow => "pow",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::BitAnd => "&",
+            Self::BitOr => "|",
+            Self::BitXor => "^",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
+        }
+    }
+
+    // Binding strength of the infix operators (higher binds tighter); None for function-style ones.
+    pub fn precedence(&self) -> Option<u8> {
+        match self {
+            Self::BitOr => Some(1),
+            Self::BitXor => Some(2),
+            Self::BitAnd => Some(3),
+            Self::Shl | Self::Shr => Some(4),
+            Self::Add | Self::Subtract => Some(5),
+            Self::Multiply | Self::Divide | Self::Remainder => Some(6),
+            Self::Pow | Self::Min | Self::Max => None,
+        }
+    }
+
+    pub fn from_symbol(symbol: &str) -> Option<Operation> {
+        Self::ALL.into_iter().find(|op| op.symbol() == symbol)
+    }
+
+    pub fn run<T: Number>(&self, x: T, y: T) -> Result<T, OpError> {
+        self.run_with(x, y, Mode::Checked)
+    }
+
+    pub fn run_with<T: Number>(&self, x: T, y: T, mode: Mode) -> Result<T, OpError> {
+        T::run(*self, x, y, mode)
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
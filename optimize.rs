@@ -0,0 +1,434 @@
+/*
+Optimizer passes over the three-address IR (ir.rs) — the "LLVM optimizes IR" step
+
+architecture.rs: "LLVM → Optimize intermediate representation (IR)". LLVM runs
+a pipeline of small passes, each doing one rewrite, until nothing changes.
+This module does the same for calculator programs:
+
+Pass	--passes=	Rewrites
+Constant folding	fold	Add(const 2, const 3) → const 5, and constants flow through locals
+Algebraic simplification	simplify	x + 0, x * 1, x - x, x * 0, x & x, pow(x, 1), …
+Common subexpressions	cse	_5 = Add(_0, _1) after _3 = Add(_1, _0) → _5 = _3, then reads of _5 use _3
+Dead bindings	dce	drops assignments nobody reads, then renumbers the locals
+
+let x = 3 + 2 * 4; x - 1        unoptimized: 4 assignments + print
+                                all passes:  print(const 10);
+
+📌 The passes never change what a program prints or which error it stops
+   with, in any Mode:
+   ✅ folding only happens when the checked result exists, and then every Mode agrees;
+   ✅ an operation that may fail (overflow, division by zero) is never removed.
+   compile.rs checks this on random programs for every combination of passes.
+
+The tests at the bottom snapshot the IR before and after each pass; they
+build with the program that uses this module:
+
+$ rustc --test compile.rs && ./compile
+*/
+
+// Shared by several programs; each one uses a different subset.
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ir::{Inst, Local, LocalDecl, Operand, Program, Rvalue};
+use crate::operation::Operation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pass {
+    ConstantFolding,
+    AlgebraicSimplification,
+    CommonSubexpressions,
+    DeadBindings,
+}
+
+impl Pass {
+    // Also the order `optimize` runs them in.
+    pub const ALL: [Pass; 4] =
+        [Self::ConstantFolding, Self::AlgebraicSimplification, Self::CommonSubexpressions, Self::DeadBindings];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ConstantFolding => "fold",
+            Self::AlgebraicSimplification => "simplify",
+            Self::CommonSubexpressions => "cse",
+            Self::DeadBindings => "dce",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Pass> {
+        Self::ALL.into_iter().find(|pass| pass.name() == name)
+    }
+
+    // Runs the pass once. Returns whether it changed anything.
+    pub fn run(&self, program: &mut Program) -> bool {
+        match self {
+            Self::ConstantFolding => fold_constants(program),
+            Self::AlgebraicSimplification => simplify(program),
+            Self::CommonSubexpressions => eliminate_common_subexpressions(program),
+            Self::DeadBindings => eliminate_dead_bindings(program),
+        }
+    }
+}
+
+// One pass often enables another (folding exposes `x * 0`, CSE leaves dead copies),
+// so the enabled passes run in order until a whole round changes nothing.
+pub fn optimize(program: &mut Program, passes: &[Pass]) {
+    const MAX_ROUNDS: usize = 16;
+    for _ in 0..MAX_ROUNDS {
+        let mut changed = false;
+        for pass in Pass::ALL.iter().filter(|pass| passes.contains(pass)) {
+            changed |= pass.run(program);
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+// Calls `f` on every operand an instruction reads.
+fn operands_mut(inst: &mut Inst, mut f: impl FnMut(&mut Operand)) {
+    match inst {
+        Inst::Assign { value: Rvalue::Use(operand), .. } | Inst::Print { value: operand, .. } => f(operand),
+        Inst::Assign { value: Rvalue::Binary(_, x, y), .. } => {
+            f(x);
+            f(y);
+        }
+    }
+}
+
+// ---------- 1. CONSTANT FOLDING ----------
+// The code is straight-line, so "the value a local holds right now" is simply
+// the last constant assigned to it.
+fn fold_constants(program: &mut Program) -> bool {
+    let mut known: HashMap<Local, i32> = HashMap::new();
+    let mut changed = false;
+    for inst in &mut program.insts {
+        operands_mut(inst, |operand| {
+            if let Operand::Local(local) = operand {
+                if let Some(value) = known.get(local) {
+                    *operand = Operand::Const(*value);
+                    changed = true;
+                }
+            }
+        });
+        if let Inst::Assign { dest, value, .. } = inst {
+            if let Rvalue::Binary(op, Operand::Const(x), Operand::Const(y)) = *value {
+                // A failing operation stays, so the program still stops with the same error.
+                if let Ok(result) = op.run(x, y) {
+                    *value = Rvalue::Use(Operand::Const(result));
+                    changed = true;
+                }
+            }
+            match value {
+                Rvalue::Use(Operand::Const(n)) => known.insert(*dest, *n),
+                _ => known.remove(dest),
+            };
+        }
+    }
+    changed
+}
+
+// ---------- 2. ALGEBRAIC SIMPLIFICATION ----------
+// Identities that hold for every i32 and every Mode, and that can't hide an error.
+fn simplify_binary(op: Operation, x: Operand, y: Operand) -> Option<Rvalue> {
+    use Operand::Const;
+    use Operation::*;
+
+    let keep = |operand| Some(Rvalue::Use(operand));
+    let constant = |n| Some(Rvalue::Use(Const(n)));
+    match (op, x, y) {
+        (Add | Subtract | BitOr | BitXor | Shl | Shr, x, Const(0)) => keep(x),
+        (Add | BitOr | BitXor, Const(0), y) => keep(y),
+        (Multiply | Divide | Pow, x, Const(1)) => keep(x),
+        (Multiply, Const(1), y) => keep(y),
+        (BitAnd, x, Const(-1)) => keep(x),
+        (BitAnd, Const(-1), y) => keep(y),
+        (Multiply | BitAnd, _, Const(0)) | (Multiply | BitAnd, Const(0), _) => constant(0),
+        (Remainder, _, Const(1 | -1)) => constant(0), // i32::MIN % -1 is 0 too
+        (Pow, _, Const(0)) => constant(1),
+        (Subtract | BitXor, x, y) if x == y => constant(0),
+        (BitAnd | BitOr | Min | Max, x, y) if x == y => keep(x),
+        _ => None,
+    }
+}
+
+fn simplify(program: &mut Program) -> bool {
+    let mut changed = false;
+    for inst in &mut program.insts {
+        if let Inst::Assign { value, .. } = inst {
+            if let Rvalue::Binary(op, x, y) = *value {
+                if let Some(simpler) = simplify_binary(op, x, y) {
+                    *value = simpler;
+                    changed = true;
+                }
+            }
+        }
+    }
+    changed
+}
+
+// ---------- 3. COMMON SUBEXPRESSIONS ----------
+fn is_commutative(op: Operation) -> bool {
+    use Operation::*;
+    matches!(op, Add | Multiply | Min | Max | BitAnd | BitOr | BitXor)
+}
+
+// Canonical order: locals before constants, lower locals first.
+fn out_of_order(x: Operand, y: Operand) -> bool {
+    match (x, y) {
+        (Operand::Const(_), Operand::Local(_)) => true,
+        (Operand::Local(x), Operand::Local(y)) => y < x,
+        _ => false,
+    }
+}
+
+fn mentions(value: &Rvalue, local: Local) -> bool {
+    match value {
+        Rvalue::Use(operand) => *operand == Operand::Local(local),
+        Rvalue::Binary(_, x, y) => *x == Operand::Local(local) || *y == Operand::Local(local),
+    }
+}
+
+// Local value numbering over straight-line code:
+// `available` maps an operation to the local that already holds its result,
+// `copies` maps a local to the local it was copied from. Both forget a local
+// as soon as it is reassigned (a `mut` binding).
+fn eliminate_common_subexpressions(program: &mut Program) -> bool {
+    let mut available: HashMap<Rvalue, Local> = HashMap::new();
+    let mut copies: HashMap<Local, Local> = HashMap::new();
+    let mut changed = false;
+    for inst in &mut program.insts {
+        operands_mut(inst, |operand| {
+            if let Operand::Local(local) = operand {
+                if let Some(source) = copies.get(local) {
+                    *operand = Operand::Local(*source);
+                    changed = true;
+                }
+            }
+        });
+        let Inst::Assign { dest, value, .. } = inst else {
+            continue;
+        };
+        if let Rvalue::Binary(op, x, y) = value {
+            // Order the operands of commutative operations so Add(_1, _0) finds Add(_0, _1).
+            if is_commutative(*op) && out_of_order(*x, *y) {
+                std::mem::swap(x, y);
+            }
+            if let Some(earlier) = available.get(value) {
+                *value = Rvalue::Use(Operand::Local(*earlier));
+                changed = true;
+            }
+        }
+
+        let dest = *dest;
+        available.retain(|rvalue, local| *local != dest && !mentions(rvalue, dest));
+        copies.retain(|copy, source| *copy != dest && *source != dest);
+        match *value {
+            Rvalue::Use(Operand::Local(source)) if source != dest => {
+                copies.insert(dest, source);
+            }
+            Rvalue::Binary(..) if !mentions(value, dest) => {
+                available.insert(*value, dest);
+            }
+            _ => {}
+        }
+    }
+    changed
+}
+
+// ---------- 4. DEAD BINDINGS ----------
+// Can evaluating this fail in some Mode? Unknown operands are assumed to be the worst case.
+fn may_fail(value: &Rvalue) -> bool {
+    use Operation::*;
+    match value {
+        Rvalue::Use(_) => false,
+        Rvalue::Binary(Min | Max | BitAnd | BitOr | BitXor, _, _) => false,
+        Rvalue::Binary(op, Operand::Const(x), Operand::Const(y)) => op.run(*x, *y).is_err(),
+        Rvalue::Binary(..) => true,
+    }
+}
+
+// Walks backwards keeping the set of locals that are read later on; an
+// assignment to a local outside that set is dead. Then unused locals are
+// dropped and the rest renumbered in order.
+fn eliminate_dead_bindings(program: &mut Program) -> bool {
+    let mut live: HashSet<Local> = HashSet::new();
+    let before = program.insts.len();
+    let mut kept = Vec::with_capacity(before);
+    for mut inst in program.insts.drain(..).rev() {
+        if let Inst::Assign { dest, value, .. } = &inst {
+            if !live.remove(dest) && !may_fail(value) {
+                continue;
+            }
+        }
+        operands_mut(&mut inst, |operand| {
+            if let Operand::Local(local) = operand {
+                live.insert(*local);
+            }
+        });
+        kept.push(inst);
+    }
+    kept.reverse();
+    program.insts = kept;
+    let removed = program.insts.len() != before;
+    renumber_locals(program) || removed
+}
+
+fn renumber_locals(program: &mut Program) -> bool {
+    let mut used = vec![false; program.locals.len()];
+    for inst in &mut program.insts {
+        if let Inst::Assign { dest, .. } = inst {
+            used[dest.0 as usize] = true;
+        }
+        operands_mut(inst, |operand| {
+            if let Operand::Local(local) = operand {
+                used[local.0 as usize] = true;
+            }
+        });
+    }
+    if used.iter().all(|used| *used) {
+        return false;
+    }
+
+    let mut renamed: Vec<Option<Local>> = vec![None; used.len()];
+    let mut locals: Vec<LocalDecl> = Vec::new();
+    for (i, decl) in program.locals.drain(..).enumerate() {
+        if used[i] {
+            renamed[i] = Some(Local(locals.len() as u32));
+            locals.push(decl);
+        }
+    }
+    program.locals = locals;
+    let rename = |local: &mut Local| *local = renamed[local.0 as usize].expect("used locals are kept");
+    for inst in &mut program.insts {
+        if let Inst::Assign { dest, .. } = inst {
+            rename(dest);
+        }
+        operands_mut(inst, |operand| {
+            if let Operand::Local(local) = operand {
+                rename(local);
+            }
+        });
+    }
+    true
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::parse_statements;
+    use crate::ir::lower;
+
+    fn ir_with(source: &str, passes: &[Pass]) -> String {
+        let mut program = lower(&parse_statements(source).unwrap()).unwrap();
+        optimize(&mut program, passes);
+        program.to_string()
+    }
+
+    // Each pass on its own: the IR before, and after running just that pass.
+    fn pass_check(pass: Pass, source: &str, before: &str, after: &str) {
+        assert_eq!(ir_with(source, &[]), before, "{} before", pass.name());
+        assert_eq!(ir_with(source, &[pass]), after, "{} after", pass.name());
+    }
+
+    #[test]
+    fn fold_check() {
+        pass_check(
+            Pass::ConstantFolding,
+            "let x = 2 * 3; x + 4",
+            "debug x => _1;\n\
+             _0 = Multiply(const 2, const 3);\n\
+             _1 = _0;\n\
+             _2 = Add(_1, const 4);\n\
+             print(_2);\n",
+            "debug x => _1;\n\
+             _0 = const 6;\n\
+             _1 = const 6;\n\
+             _2 = const 10;\n\
+             print(const 10);\n",
+        );
+        // An operation that fails is left for the VM, so the error still happens at run time.
+        assert_eq!(ir_with("1 / 0", &[Pass::ConstantFolding]), "_0 = Divide(const 1, const 0);\nprint(_0);\n");
+    }
+
+    #[test]
+    fn simplify_check() {
+        pass_check(
+            Pass::AlgebraicSimplification,
+            "let mut y = 7; y = y * 1 + 0; y - y",
+            "debug y => _0;\n\
+             _0 = const 7;\n\
+             _1 = Multiply(_0, const 1);\n\
+             _2 = Add(_1, const 0);\n\
+             _0 = _2;\n\
+             _3 = Subtract(_0, _0);\n\
+             print(_3);\n",
+            "debug y => _0;\n\
+             _0 = const 7;\n\
+             _1 = _0;\n\
+             _2 = _1;\n\
+             _0 = _2;\n\
+             _3 = const 0;\n\
+             print(_3);\n",
+        );
+    }
+
+    #[test]
+    fn cse_check() {
+        pass_check(
+            Pass::CommonSubexpressions,
+            "let a = 5; let b = 6; (a + b) * (b + a)",
+            "debug a => _0;\n\
+             debug b => _1;\n\
+             _0 = const 5;\n\
+             _1 = const 6;\n\
+             _2 = Add(_0, _1);\n\
+             _3 = Add(_1, _0);\n\
+             _4 = Multiply(_2, _3);\n\
+             print(_4);\n",
+            "debug a => _0;\n\
+             debug b => _1;\n\
+             _0 = const 5;\n\
+             _1 = const 6;\n\
+             _2 = Add(_0, _1);\n\
+             _3 = _2;\n\
+             _4 = Multiply(_2, _2);\n\
+             print(_4);\n",
+        );
+        // A `mut` binding that changes in between makes the second a + b a different value.
+        let ir = ir_with("let mut a = 1; let b = a + 2; a = 5; a + 2; b", &[Pass::CommonSubexpressions]);
+        assert_eq!(ir.matches("Add(").count(), 2, "{}", ir);
+    }
+
+    #[test]
+    fn dead_bindings_check() {
+        pass_check(
+            Pass::DeadBindings,
+            "let unused = 1 + 2; let x = 4; let risky = x / 0; x",
+            "debug unused => _1;\n\
+             debug x => _2;\n\
+             debug risky => _4;\n\
+             _0 = Add(const 1, const 2);\n\
+             _1 = _0;\n\
+             _2 = const 4;\n\
+             _3 = Divide(_2, const 0);\n\
+             _4 = _3;\n\
+             print(_2);\n",
+            // 1 + 2 can't overflow, so it goes; x / 0 can fail, so it stays.
+            "debug x => _0;\n\
+             _0 = const 4;\n\
+             _1 = Divide(_0, const 0);\n\
+             print(_0);\n",
+        );
+    }
+
+    #[test]
+    fn all_passes_check() {
+        assert_eq!(ir_with("let x = 3 + 2 * 4; x - 1", &Pass::ALL), "print(const 10);\n");
+
+        let program = "let a = 5; let mut b = a * 0 + a; b = b + (a + b) - (b + a); let c = b ^ b; b | c";
+        assert_eq!(ir_with(program, &Pass::ALL), "print(const 5);\n");
+    }
+}
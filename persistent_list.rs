@@ -0,0 +1,260 @@
+/*
+Persistent (immutable, structurally shared) linked list
+
+The cons-list `List` in 1.rs owns its tail through a Box, so `prepend(self, val)`
+consumes the old list: you can never hold the old and the new version at once.
+
+A persistent list points at its tail through a reference-counted pointer instead:
+
+    enum List { Cons(T, Box<List>), Nil }      // 1.rs: one owner per node
+    struct List { head: Option<Rc<Node<T>>> }  // here: many lists share one node
+
+✅ prepend(&self, val) returns a NEW list; the old one is still valid.
+✅ The new list shares every node of the old one (its tail), nothing is copied.
+✅ clone() only bumps a reference count → O(1).
+✅ Nodes are freed when the last list that uses them is dropped.
+
+Two flavours, same API:
+
+Flavour	Pointer	Use it when
+rc::List	Rc<Node<T>>	Single-threaded (cheaper, non-atomic counts)
+arc::List	Arc<Node<T>>	Shared between threads (Send + Sync when T is)
+
+Typical uses: undo histories (every state is a list, undo = go back to an older one)
+and snapshots (hand a clone to another thread, keep working on yours).
+
+$ rustc persistent_list.rs
+$ ./persistent_list
+$ rustc --test persistent_list.rs && ./persistent_list   // a million-node list, dropped without recursion
+*/
+
+// Both flavours are the same code with a different pointer type, so generate them.
+macro_rules! persistent_list {
+    ($name:ident, $ptr:ident) => {
+        #[allow(dead_code)]
+        mod $name {
+            use std::fmt;
+            use $crate::ptr::$ptr;
+
+            struct Node<T> {
+                elem: T,
+                next: Option<$ptr<Node<T>>>,
+            }
+
+            pub struct List<T> {
+                head: Option<$ptr<Node<T>>>,
+                len: usize,
+            }
+
+            impl<T> List<T> {
+                pub fn new() -> Self {
+                    List { head: None, len: 0 }
+                }
+
+                // O(1): the new list's tail IS `self`, shared rather than copied.
+                pub fn prepend(&self, elem: T) -> Self {
+                    List {
+                        head: Some($ptr::new(Node { elem, next: self.head.clone() })),
+                        len: self.len + 1,
+                    }
+                }
+
+                // O(1): the list without its first element (Nil stays Nil).
+                pub fn tail(&self) -> Self {
+                    match &self.head {
+                        Some(node) => List { head: node.next.clone(), len: self.len - 1 },
+                        None => List::new(),
+                    }
+                }
+
+                pub fn head(&self) -> Option<&T> {
+                    self.head.as_ref().map(|node| &node.elem)
+                }
+
+                pub fn len(&self) -> usize {
+                    self.len
+                }
+
+                pub fn is_empty(&self) -> bool {
+                    self.head.is_none()
+                }
+
+                // True when both lists start at the very same node (not just equal values).
+                pub fn ptr_eq(&self, other: &Self) -> bool {
+                    match (&self.head, &other.head) {
+                        (Some(a), Some(b)) => $ptr::ptr_eq(a, b),
+                        (None, None) => true,
+                        _ => false,
+                    }
+                }
+
+                pub fn iter(&self) -> Iter<'_, T> {
+                    Iter { next: self.head.as_deref() }
+                }
+            }
+
+            impl<T: fmt::Display> List<T> {
+                pub fn stringify(&self) -> String {
+                    let mut out = String::new();
+                    for elem in self {
+                        out.push_str(&elem.to_string());
+                        out.push_str(", ");
+                    }
+                    out.push_str("Nil");
+                    out
+                }
+            }
+
+            impl<T> Default for List<T> {
+                fn default() -> Self {
+                    List::new()
+                }
+            }
+
+            // O(1): only the head's reference count changes.
+            impl<T> Clone for List<T> {
+                fn clone(&self) -> Self {
+                    List { head: self.head.clone(), len: self.len }
+                }
+            }
+
+            // Free nodes we own exclusively one by one, and stop at the first node
+            // another list still uses. Avoids recursive drops on long lists.
+            // into_inner, not try_unwrap: when two threads drop the last two handles
+            // to a node at once, try_unwrap can fail in both and the node is then
+            // freed by a plain (recursive) drop; into_inner hands it to exactly one.
+            impl<T> Drop for List<T> {
+                fn drop(&mut self) {
+                    let mut head = self.head.take();
+                    while let Some(mut node) = head.and_then($ptr::into_inner) {
+                        head = node.next.take();
+                    }
+                }
+            }
+
+            // Builds a list in iterator order: [1, 2, 3] → 1, 2, 3, Nil.
+            impl<T> FromIterator<T> for List<T> {
+                fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+                    let items: Vec<T> = iter.into_iter().collect();
+                    let mut list = List::new();
+                    for elem in items.into_iter().rev() {
+                        list = list.prepend(elem);
+                    }
+                    list
+                }
+            }
+
+            impl<T: fmt::Debug> fmt::Debug for List<T> {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.debug_list().entries(self.iter()).finish()
+                }
+            }
+
+            impl<T: PartialEq> PartialEq for List<T> {
+                fn eq(&self, other: &Self) -> bool {
+                    self.len == other.len && self.iter().eq(other.iter())
+                }
+            }
+
+            pub struct Iter<'a, T> {
+                next: Option<&'a Node<T>>,
+            }
+
+            impl<'a, T> Iterator for Iter<'a, T> {
+                type Item = &'a T;
+
+                fn next(&mut self) -> Option<Self::Item> {
+                    self.next.map(|node| {
+                        self.next = node.next.as_deref();
+                        &node.elem
+                    })
+                }
+            }
+
+            impl<'a, T> IntoIterator for &'a List<T> {
+                type Item = &'a T;
+                type IntoIter = Iter<'a, T>;
+
+                fn into_iter(self) -> Self::IntoIter {
+                    self.iter()
+                }
+            }
+        }
+    };
+}
+
+// The pointer types the two flavours are built on.
+mod ptr {
+    pub use std::rc::Rc;
+    pub use std::sync::Arc;
+}
+
+persistent_list!(rc, Rc);
+persistent_list!(arc, Arc);
+
+// ---------- 1. SHARING ----------
+fn sharing_example() {
+    let base = rc::List::new().prepend(1).prepend(2);
+    let left = base.prepend(10);
+    let right = base.prepend(20);
+
+    println!("base:  {}", base.stringify());
+    println!("left:  {}", left.stringify());
+    println!("right: {}", right.stringify());
+
+    // Both new lists point at the very same tail nodes.
+    assert!(left.tail().ptr_eq(&base));
+    assert!(right.tail().ptr_eq(&base));
+    assert!(base.clone().ptr_eq(&base));
+}
+
+// ---------- 2. UNDO HISTORY ----------
+fn undo_example() {
+    let mut history = vec![rc::List::new()];
+    for word in ["Hello", "persistent", "world"] {
+        let next = history.last().unwrap().prepend(word);
+        history.push(next);
+    }
+
+    println!("current: {}", history.last().unwrap().stringify());
+    history.pop(); // undo
+    println!("after undo: {}", history.last().unwrap().stringify());
+    assert_eq!(history.last().unwrap().head(), Some(&"persistent"));
+}
+
+// ---------- 3. SNAPSHOTS ACROSS THREADS ----------
+fn snapshot_example() {
+    let list: arc::List<u64> = (1..=100).collect();
+    let snapshot = list.clone();
+
+    let worker = std::thread::spawn(move || snapshot.iter().sum::<u64>());
+    let list = list.prepend(1_000);
+
+    println!("snapshot sum (other thread): {}", worker.join().unwrap());
+    println!("current sum: {}", list.iter().sum::<u64>());
+}
+
+fn main() {
+    sharing_example();
+    undo_example();
+    snapshot_example();
+}
+
+// ---------- 4. LONG LISTS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_list_check() {
+        let mut list = rc::List::new();
+        for i in 0..1_000_000 {
+            list = list.prepend(i);
+        }
+        let shared = list.tail();
+        drop(list);
+        assert_eq!(shared.len(), 999_999);
+        // The last owner frees the remaining 999_999 nodes in a loop, not by recursion.
+        drop(shared);
+    }
+}
@@ -0,0 +1,336 @@
+/*
+ASCII plots of single-variable `Operation` formulas
+
+$ rustc -O plot.rs
+$ ./plot --x=-3:3 --size=41x11 "x * x - 2"
+* x * x - 2
+ 7.0 +*                   |                   *
+     | *                  |                  *
+     |  *                 |                 *
+     |   *                |                *
+     |    **              |              **
+ 2.5 +      *             |             *
+     |       **           |           **
+     |         *          |          *
+     |----------***-------+-------***----------
+     |             ***    |    ***
+-2.0 +                *********
+     ++---------+---------+---------+---------+
+    -3.0      -1.5       0.0       1.5       3.0
+$ rustc --test plot.rs && ./plot           // the tests
+
+Option	Default	Meaning
+--x=FROM:TO	-10:10	the range of x
+--y=FROM:TO	auto	the range of y; auto-scaling takes the smallest and largest value
+--size=COLUMNSxROWS	61x21	the plot area, one sample of x per column
+--precision=N	auto	decimals in the tick labels
+--derivative	off	also plot d/dx of every formula (symbolic.rs), marked `o`
+
+Several formulas share the axes, marked * o # @ % in order.
+
+📌 Tick labels are printed with the width and precision specifiers from
+   formatted.rs: format!("{value:>width$.precision$}") right-aligns every label
+   in the same width, with `precision` decimals chosen from the tick spacing.
+📌 Arithmetic is f64 (Operation::run on f64, via symbolic::eval_at): where a
+   formula fails (division by zero, 0 / 0, overflow) its curve has a gap.
+⚠️ Near a pole (1 / x around 0) auto-scaling is dominated by huge values: set --y.
+*/
+
+mod expression;
+mod operation;
+mod symbolic;
+
+use expression::{parse, Error, Expr, ExprKind};
+use symbolic::{differentiate, eval_at};
+
+const MARKS: [char; 5] = ['*', 'o', '#', '@', '%'];
+// A labelled tick every this many rows / columns.
+const ROW_TICKS: usize = 5;
+const COLUMN_TICKS: usize = 10;
+
+struct Plot {
+    x_range: (f64, f64),
+    y_range: Option<(f64, f64)>,
+    columns: usize,
+    rows: usize,
+    precision: Option<usize>,
+}
+
+impl Default for Plot {
+    fn default() -> Self {
+        Plot { x_range: (-10.0, 10.0), y_range: None, columns: 61, rows: 21, precision: None }
+    }
+}
+
+// ---------- 1. SAMPLING ----------
+// Variables other than `x` can't be plotted; say so instead of drawing nothing.
+fn check_variables(expr: &Expr) -> Result<(), Error> {
+    match &expr.kind {
+        ExprKind::Number(_) => Ok(()),
+        ExprKind::Variable(name) if name == "x" => Ok(()),
+        ExprKind::Variable(name) => {
+            Err(Error::new(format!("cannot plot `{}`: only `x` may vary", name), expr.span))
+        }
+        ExprKind::Negate(inner) => check_variables(inner),
+        ExprKind::Binary(_, left, right) => check_variables(left).and_then(|()| check_variables(right)),
+    }
+}
+
+impl Plot {
+    fn x_at(&self, column: usize) -> f64 {
+        let (from, to) = self.x_range;
+        from + (to - from) * column as f64 / (self.columns - 1) as f64
+    }
+
+    // f(x) for every column; None where it has no finite value.
+    fn sample(&self, expr: &Expr) -> Vec<Option<f64>> {
+        (0..self.columns)
+            .map(|column| eval_at(expr, "x", self.x_at(column)).ok().filter(|y| y.is_finite()))
+            .collect()
+    }
+
+    // --y, or the smallest and largest value; a flat curve gets a range of ±1 around it.
+    fn y_range(&self, curves: &[Vec<Option<f64>>]) -> (f64, f64) {
+        if let Some(range) = self.y_range {
+            return range;
+        }
+        let values = curves.iter().flatten().flatten();
+        let (low, high) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), y| (low.min(*y), high.max(*y)));
+        match (low.is_finite(), low < high) {
+            (false, _) => (-1.0, 1.0),
+            (true, false) => (low - 1.0, high + 1.0),
+            (true, true) => (low, high),
+        }
+    }
+}
+
+// ---------- 2. RENDERING ----------
+// Enough decimals to tell ticks `step` apart: step 2.5 → 1, step 0.05 → 2, step 20 → 0.
+// That is the position of the step's first digit, plus one if the step needs a second one.
+fn precision_for(step: f64) -> usize {
+    if step <= 0.0 || !step.is_finite() {
+        return 0;
+    }
+    let first = -step.log10().floor();
+    let scaled = step * 10f64.powf(first);
+    let second = if (scaled - scaled.round()).abs() > 1e-9 { 1.0 } else { 0.0 };
+    (first + second).clamp(0.0, 6.0) as usize
+}
+
+// The row/column nearest to `value` on an axis from `from` (index 0) to `to` (index len - 1).
+fn index_of(value: f64, from: f64, to: f64, len: usize) -> Option<usize> {
+    let position = ((value - from) / (to - from) * (len - 1) as f64).round();
+    (0.0..=(len - 1) as f64).contains(&position).then_some(position as usize)
+}
+
+impl Plot {
+    fn render(&self, formulas: &[Expr]) -> String {
+        let curves: Vec<Vec<Option<f64>>> = formulas.iter().map(|expr| self.sample(expr)).collect();
+        let (bottom, top) = self.y_range(&curves);
+        let (left, right) = self.x_range;
+
+        // Rows run from `top` down to `bottom`.
+        let mut grid = vec![vec![' '; self.columns]; self.rows];
+        let axis_row = index_of(0.0, top, bottom, self.rows);
+        let axis_column = index_of(0.0, left, right, self.columns);
+        if let Some(row) = axis_row {
+            grid[row].fill('-');
+        }
+        if let Some(column) = axis_column {
+            for (row, line) in grid.iter_mut().enumerate() {
+                line[column] = if Some(row) == axis_row { '+' } else { '|' };
+            }
+        }
+        for (curve, mark) in curves.iter().zip(MARKS.iter().cycle()) {
+            for (column, y) in curve.iter().enumerate() {
+                if let Some(row) = y.and_then(|y| index_of(y, top, bottom, self.rows)) {
+                    grid[row][column] = *mark;
+                }
+            }
+        }
+
+        // y labels, right-aligned in one width: "{value:>width$.precision$}" (formatted.rs)
+        let y_at = |row: usize| top + (bottom - top) * row as f64 / (self.rows - 1) as f64;
+        let y_ticks: Vec<usize> = (0..self.rows).filter(|row| row % ROW_TICKS == 0 || *row == self.rows - 1).collect();
+        let y_step = (top - bottom).abs() * ROW_TICKS as f64 / (self.rows - 1) as f64;
+        let precision = self.precision.unwrap_or_else(|| precision_for(y_step));
+        let labels: Vec<String> = y_ticks.iter().map(|row| format!("{:.precision$}", y_at(*row))).collect();
+        let width = labels.iter().map(|label| label.len()).max().unwrap_or(0);
+
+        let mut out = String::new();
+        let legend: Vec<String> = formulas.iter().zip(MARKS.iter().cycle()).map(|(expr, mark)| format!("{} {}", mark, expr)).collect();
+        out.push_str(&legend.join("   "));
+        out.push('\n');
+        for (row, line) in grid.iter().enumerate() {
+            let cells: String = line.iter().collect();
+            let text = match y_ticks.iter().position(|tick| *tick == row) {
+                Some(i) => format!("{:>width$} +{}", labels[i], cells),
+                None => format!("{:>width$} |{}", "", cells),
+            };
+            out.push_str(text.trim_end());
+            out.push('\n');
+        }
+
+        // x axis: a `+` under every labelled column, labels centred below it.
+        let x_ticks: Vec<usize> = (0..self.columns).filter(|column| column % COLUMN_TICKS == 0 || *column == self.columns - 1).collect();
+        let ruler: String = (0..self.columns).map(|column| if x_ticks.contains(&column) { '+' } else { '-' }).collect();
+        out.push_str(&format!("{:>width$} +{}\n", "", ruler));
+        let x_step = (right - left).abs() * COLUMN_TICKS as f64 / (self.columns - 1) as f64;
+        let x_precision = self.precision.unwrap_or_else(|| precision_for(x_step));
+        let mut axis = String::new();
+        for column in x_ticks {
+            let label = format!("{:.x_precision$}", self.x_at(column));
+            let end = width + 2 + column + label.len().div_ceil(2);
+            // Skip a label that would run into the previous one.
+            if end > axis.len() + label.len() {
+                let pad = end - axis.len();
+                axis.push_str(&format!("{label:>pad$}"));
+            }
+        }
+        out.push_str(axis.trim_end());
+        out.push('\n');
+        out
+    }
+}
+
+// ---------- 3. MAIN ----------
+fn parse_range(text: &str) -> Option<(f64, f64)> {
+    let (from, to) = text.split_once(':')?;
+    let (from, to): (f64, f64) = (from.parse().ok()?, to.parse().ok()?);
+    (from < to && from.is_finite() && to.is_finite()).then_some((from, to))
+}
+
+fn parse_size(text: &str) -> Option<(usize, usize)> {
+    let (columns, rows) = text.split_once('x')?;
+    let (columns, rows) = (columns.parse().ok()?, rows.parse().ok()?);
+    (columns >= 2 && rows >= 2).then_some((columns, rows))
+}
+
+fn usage() -> ! {
+    eprintln!("usage: plot [--x=FROM:TO] [--y=FROM:TO] [--size=COLUMNSxROWS] [--precision=N] [--derivative] FORMULA...");
+    std::process::exit(2);
+}
+
+fn main() {
+    let mut plot = Plot::default();
+    let mut derivatives = false;
+    let mut sources = Vec::new();
+    for arg in std::env::args().skip(1) {
+        if let Some(range) = arg.strip_prefix("--x=") {
+            plot.x_range = parse_range(range).unwrap_or_else(|| usage());
+        } else if let Some(range) = arg.strip_prefix("--y=") {
+            plot.y_range = Some(parse_range(range).unwrap_or_else(|| usage()));
+        } else if let Some(size) = arg.strip_prefix("--size=") {
+            (plot.columns, plot.rows) = parse_size(size).unwrap_or_else(|| usage());
+        } else if let Some(precision) = arg.strip_prefix("--precision=") {
+            plot.precision = Some(precision.parse().unwrap_or_else(|_| usage()));
+        } else if arg == "--derivative" {
+            derivatives = true;
+        } else if arg.starts_with("--") {
+            usage();
+        } else {
+            sources.push(arg);
+        }
+    }
+    if sources.is_empty() {
+        usage();
+    }
+
+    let mut formulas = Vec::new();
+    for source in &sources {
+        let result = parse(source).and_then(|expr| {
+            check_variables(&expr)?;
+            let derivative = if derivatives { Some(differentiate(&expr, "x")?) } else { None };
+            Ok((expr, derivative))
+        });
+        match result {
+            Ok((expr, derivative)) => formulas.extend(std::iter::once(expr).chain(derivative)),
+            Err(err) => {
+                println!("{}", err.render(source));
+                std::process::exit(1);
+            }
+        }
+    }
+    print!("{}", plot.render(&formulas));
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precision_check() {
+        assert_eq!(precision_for(2.5), 1);
+        assert_eq!(precision_for(0.05), 2);
+        assert_eq!(precision_for(0.5), 1);
+        assert_eq!(precision_for(0.25), 2);
+        assert_eq!(precision_for(0.1), 1);
+        assert_eq!(precision_for(1.0), 0);
+        assert_eq!(precision_for(20.0), 0);
+        assert_eq!(precision_for(1e-9), 6);
+        // The same specifiers as formatted.rs, with the width and precision as arguments.
+        let (value, width, precision) = (-1.5, 6, 2);
+        assert_eq!(format!("{value:>width$.precision$}"), " -1.50");
+    }
+
+    fn render(plot: &Plot, sources: &[&str]) -> String {
+        let formulas: Vec<Expr> = sources.iter().map(|source| parse(source).unwrap()).collect();
+        plot.render(&formulas)
+    }
+
+    #[test]
+    fn render_check() {
+        let plot = Plot { x_range: (-3.0, 3.0), columns: 41, rows: 11, ..Plot::default() };
+        let expected = "\
+* x * x - 2
+ 7.0 +*                   |                   *
+     | *                  |                  *
+     |  *                 |                 *
+     |   *                |                *
+     |    **              |              **
+ 2.5 +      *             |             *
+     |       **           |           **
+     |         *          |          *
+     |----------***-------+-------***----------
+     |             ***    |    ***
+-2.0 +                *********
+     ++---------+---------+---------+---------+
+    -3.0      -1.5       0.0       1.5       3.0
+";
+        assert_eq!(render(&plot, &["x * x - 2"]), expected);
+    }
+
+    #[test]
+    fn scaling_check() {
+        let small = Plot { x_range: (0.0, 4.0), columns: 5, rows: 3, ..Plot::default() };
+        // A constant: the range is widened by 1 each way, so the x axis is at the bottom.
+        assert_eq!(render(&small, &["1"]), "* 1\n2 +|\n  |*****\n0 ++----\n  ++---+\n   0   4\n");
+        // Two curves share one scale; the later mark wins where they meet.
+        assert_eq!(render(&small, &["x", "4 - x"]), "* x   o 4 - x\n4 +o   *\n  ||oo*\n0 +**-oo\n  ++---+\n   0   4\n");
+        // 1 / x has no value at 0, so the curve has a gap; --y keeps the scale sane.
+        let pole = Plot { x_range: (-2.0, 2.0), y_range: Some((-1.0, 1.0)), columns: 5, rows: 5, precision: Some(1) };
+        let expected = "\
+* 1 / x
+ 1.0 +  |*
+     |  | *
+     |--+--
+     |* |
+-1.0 + *|
+     ++---+
+    -2.0 2.0
+";
+        assert_eq!(render(&pole, &["1 / x"]), expected);
+    }
+
+    #[test]
+    fn errors_check() {
+        let err = check_variables(&parse("x * y + 1").unwrap()).unwrap_err();
+        assert_eq!(err.message, "cannot plot `y`: only `x` may vary");
+        assert_eq!((err.span.start, err.span.end), (4, 5));
+        assert_eq!(parse_range("-3:3"), Some((-3.0, 3.0)));
+        assert_eq!(parse_range("3:-3"), None);
+        assert_eq!(parse_size("80x24"), Some((80, 24)));
+        assert_eq!(parse_size("1x24"), None);
+    }
+}
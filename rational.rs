@@ -0,0 +1,381 @@
+/*
+Rational — exact fractions for `Operation`, built on BigInt (bigint.rs)
+
+A Rational is always kept in lowest terms with a positive denominator, so two
+equal fractions have the same representation (derived PartialEq / Hash work):
+
+    6 / -4  →  Rational { numer: -3, denom: 2 }  →  prints "-3/2"
+
+Operation	Rational result
++ - * /	exact; / by zero → DivideByZero
+%	x - y * trunc(x / y), like i32: 7/2 % 1 == 1/2, -7/2 % 1 == -1/2
+pow	integer exponents only, negative ones too: pow(2/3, -2) == 9/4
+	(a fractional exponent is usually irrational → Unsupported)
+min max	exact comparison
+& | ^ << >>	Unsupported, as for floats
+
+📌 Parsing: "3", "-3/4", "0x1f/3", "1.25" (== 5/4), "0.(3)" (repeating digits, == 1/3).
+📌 Printing: Display ("-3/2"), to_str_radix(radix) for any base, and
+   to_positional(radix, max_digits) for the expansion: 1/6 → "0.1(6)", 1/3 in base 3 → "0.1".
+⚠️ Numerators and denominators share BigInt's MAX_BITS limit (→ OpError::Overflow).
+*/
+
+// Shared by several programs; each one uses a different subset.
+#![allow(dead_code)]
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops;
+use std::str::FromStr;
+
+use crate::bigint::{BigInt, MAX_BITS};
+use crate::operation::{Mode, Number, OpError, Operation};
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numer: BigInt,
+    denom: BigInt, // > 0, and gcd(numer, denom) == 1
+}
+
+// ---------- 1. RATIONAL ----------
+impl Rational {
+    // numer / denom in lowest terms; None when denom is zero.
+    pub fn new(numer: BigInt, denom: BigInt) -> Option<Rational> {
+        if denom.is_zero() {
+            return None;
+        }
+        let gcd = numer.gcd(&denom);
+        let (mut numer, mut denom) = (&numer / &gcd, &denom / &gcd);
+        if denom.is_negative() {
+            (numer, denom) = (-numer, -denom);
+        }
+        Some(Rational { numer, denom })
+    }
+
+    pub fn zero() -> Rational {
+        Rational::from(BigInt::zero())
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        &self.numer
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        &self.denom
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.numer.is_negative()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == BigInt::one()
+    }
+
+    // 1 / self; None for zero.
+    pub fn recip(&self) -> Option<Rational> {
+        Rational::new(self.denom.clone(), self.numer.clone())
+    }
+
+    // Rounds toward zero, like `as` from a float.
+    pub fn trunc(&self) -> BigInt {
+        &self.numer / &self.denom
+    }
+
+    // Size of the bigger of numerator and denominator.
+    fn bits(&self) -> u64 {
+        self.numer.bits().max(self.denom.bits())
+    }
+
+    pub fn pow(&self, exponent: i64) -> Option<Rational> {
+        let base = if exponent < 0 { self.recip()? } else { self.clone() };
+        let exponent = exponent.unsigned_abs();
+        Rational::new(base.numer.pow(exponent), base.denom.pow(exponent))
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(value: BigInt) -> Self {
+        Rational { numer: value, denom: BigInt::one() }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Rational::from(BigInt::from(value))
+    }
+}
+
+impl Ord for Rational {
+    // a/b < c/d  ⇔  a·d < c·b, since b and d are positive.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.numer * &other.denom).cmp(&(&other.numer * &self.denom))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// ---------- 2. OPERATORS ----------
+impl ops::Neg for &Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational { numer: -&self.numer, denom: self.denom.clone() }
+    }
+}
+
+impl ops::Add for &Rational {
+    type Output = Rational;
+
+    fn add(self, other: &Rational) -> Rational {
+        let numer = &(&self.numer * &other.denom) + &(&other.numer * &self.denom);
+        Rational::new(numer, &self.denom * &other.denom).expect("denominators are not zero")
+    }
+}
+
+impl ops::Sub for &Rational {
+    type Output = Rational;
+
+    fn sub(self, other: &Rational) -> Rational {
+        self + &-other
+    }
+}
+
+impl ops::Mul for &Rational {
+    type Output = Rational;
+
+    fn mul(self, other: &Rational) -> Rational {
+        Rational::new(&self.numer * &other.numer, &self.denom * &other.denom).expect("denominators are not zero")
+    }
+}
+
+// Panics on division by zero, like the primitive integers.
+impl ops::Div for &Rational {
+    type Output = Rational;
+
+    fn div(self, other: &Rational) -> Rational {
+        Rational::new(&self.numer * &other.denom, &self.denom * &other.numer).expect("attempt to divide by zero")
+    }
+}
+
+macro_rules! owned_operator {
+    ($($trait:ident $method:ident)*) => {$(
+        impl ops::$trait for Rational {
+            type Output = Rational;
+
+            fn $method(self, other: Rational) -> Rational {
+                ops::$trait::$method(&self, &other)
+            }
+        }
+    )*};
+}
+
+owned_operator!(Add add Sub sub Mul mul Div div);
+
+impl ops::Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        -&self
+    }
+}
+
+// ---------- 3. OPERATION ----------
+// Like BigInt, nothing overflows short of MAX_BITS, so Mode makes no difference.
+impl Number for Rational {
+    const NAME: &'static str = "rational";
+
+    fn from_digits(digits: &str, radix: u32) -> Option<Self> {
+        BigInt::from_str_radix(digits, radix).ok().map(Rational::from)
+    }
+
+    fn run(op: Operation, x: Self, y: Self, _: Mode) -> Result<Self, OpError> {
+        use Operation::*;
+
+        let value = match op {
+            Add => &x + &y,
+            Subtract => &x - &y,
+            Multiply => {
+                if x.bits() + y.bits() > 2 * MAX_BITS {
+                    return Err(OpError::Overflow);
+                }
+                &x * &y
+            }
+            Divide | Remainder if y.is_zero() => return Err(OpError::DivideByZero),
+            Divide => &x / &y,
+            Remainder => &x - &(&y * &Rational::from((&x / &y).trunc())),
+            Pow => {
+                if !y.is_integer() {
+                    return Err(OpError::Unsupported);
+                }
+                if x.is_zero() && y.is_negative() {
+                    return Err(OpError::DivideByZero);
+                }
+                // 0, 1 and -1 stay small whatever the exponent.
+                if x.is_zero() || x.bits() <= 1 && x.is_integer() {
+                    let value = if y.is_zero() { Rational::from(1) } else { x.clone() };
+                    let even = !y.numer.is_odd() && !y.is_zero();
+                    return Ok(if x.is_negative() && even { -value } else { value });
+                }
+                let exponent = y.numer.to_i128().and_then(|y| i64::try_from(y).ok()).ok_or(OpError::Overflow)?;
+                if (x.bits() - 1).saturating_mul(exponent.unsigned_abs()) > MAX_BITS {
+                    return Err(OpError::Overflow);
+                }
+                x.pow(exponent).expect("x is not zero")
+            }
+            Min => x.min(y),
+            Max => x.max(y),
+            BitAnd | BitOr | BitXor | Shl | Shr => return Err(OpError::Unsupported),
+        };
+        if value.bits() > MAX_BITS {
+            return Err(OpError::Overflow);
+        }
+        Ok(value)
+    }
+}
+
+// ---------- 4. PARSING AND PRINTING ----------
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseRationalError {
+    pub input: String,
+}
+
+impl fmt::Display for ParseRationalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid fraction `{}`", self.input)
+    }
+}
+
+impl std::error::Error for ParseRationalError {}
+
+impl Rational {
+    // "a", "a/b", "a.bc" or "a.b(cd)" (cd repeating), digits in `radix`, optional sign.
+    pub fn from_str_radix(text: &str, radix: u32) -> Result<Rational, ParseRationalError> {
+        let error = || ParseRationalError { input: text.to_string() };
+        let (negative, body) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        // The sign was taken off above, so the parts must be bare digits.
+        let integer = |digits: &str| {
+            if digits.starts_with(['-', '+']) {
+                return Err(error());
+            }
+            BigInt::from_str_radix(digits, radix).map_err(|_| error())
+        };
+
+        let value = if let Some((numer, denom)) = body.split_once('/') {
+            Rational::new(integer(numer)?, integer(denom)?).ok_or_else(error)?
+        } else if let Some((whole, fraction)) = body.split_once('.') {
+            // whole.fixed(repeat) == whole + fixed / r^n + repeat / (r^n · (r^k - 1))
+            let (fixed, repeat) = match fraction.strip_suffix(')').and_then(|f| f.split_once('(')) {
+                Some((fixed, repeat)) => (fixed, Some(repeat)),
+                None => (fraction, None),
+            };
+            let r = BigInt::from(radix);
+            let shift = r.pow(fixed.len() as u64);
+            let mut value = Rational::from(integer(whole)?);
+            if !fixed.is_empty() {
+                value = &value + &Rational::new(integer(fixed)?, shift.clone()).expect("r^n > 0");
+            } else if repeat.is_none() {
+                return Err(error()); // "1."
+            }
+            if let Some(repeat) = repeat {
+                let period = &r.pow(repeat.len() as u64) - &BigInt::one();
+                value = &value + &Rational::new(integer(repeat)?, &shift * &period).ok_or_else(error)?;
+            }
+            value
+        } else {
+            Rational::from(integer(body)?)
+        };
+        Ok(if negative { -value } else { value })
+    }
+
+    // "numer/denom" (or just "numer") in `radix`.
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        match self.is_integer() {
+            true => self.numer.to_str_radix(radix),
+            false => format!("{}/{}", self.numer.to_str_radix(radix), self.denom.to_str_radix(radix)),
+        }
+    }
+
+    // The positional expansion in `radix`, with a repeating part in parentheses:
+    // 1/4 → "0.25", 1/6 → "0.1(6)", 22/7 → "3.(142857)". Gives up after
+    // `max_digits` fraction digits and ends with "…" instead.
+    pub fn to_positional(&self, radix: u32, max_digits: usize) -> String {
+        let denom = &self.denom;
+        let numer = self.numer.abs();
+        let (whole, mut remainder) = numer.div_rem(denom).expect("denom is not zero");
+        let mut text = if self.is_negative() { "-".to_string() } else { String::new() };
+        text.push_str(&whole.to_str_radix(radix));
+        if remainder.is_zero() {
+            return text;
+        }
+
+        // Long division; a remainder seen before means the digits from there on repeat.
+        let r = BigInt::from(radix);
+        let mut digits = String::new();
+        let mut seen: HashMap<BigInt, usize> = HashMap::new();
+        while !remainder.is_zero() {
+            if let Some(start) = seen.get(&remainder) {
+                digits.insert(*start, '(');
+                digits.push(')');
+                break;
+            }
+            if digits.len() == max_digits {
+                digits.push('…');
+                break;
+            }
+            seen.insert(remainder.clone(), digits.len());
+            let (digit, rest) = (&remainder * &r).div_rem(denom).expect("denom is not zero");
+            digits.push_str(&digit.to_str_radix(radix));
+            remainder = rest;
+        }
+        format!("{}.{}", text, digits)
+    }
+}
+
+// "-3/4", "0x1f/3", "1.25", "0.(3)"
+impl FromStr for Rational {
+    type Err = ParseRationalError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (sign, rest) = match text.as_bytes().first() {
+            Some(b'-' | b'+') => text.split_at(1),
+            _ => ("", text),
+        };
+        let (radix, body) = match rest.get(..2) {
+            Some("0x" | "0X") => (16, &rest[2..]),
+            Some("0o" | "0O") => (8, &rest[2..]),
+            Some("0b" | "0B") => (2, &rest[2..]),
+            _ => (10, rest),
+        };
+        if body.starts_with(['-', '+']) {
+            return Err(ParseRationalError { input: text.to_string() });
+        }
+        Rational::from_str_radix(&format!("{}{}", sign, body), radix)
+            .map_err(|_| ParseRationalError { input: text.to_string() })
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.to_str_radix(10))
+    }
+}
+
+impl fmt::Debug for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
@@ -0,0 +1,406 @@
+/*
+Reverse Polish notation for `Operation` — a stack calculator, and infix ⇄ RPN
+
+Operands go on a stack; an operation pops its operands and pushes the result.
+No precedence and no parentheses:
+
+infix	RPN
+3 + 4 * 2	3 4 2 * +
+(3 + 4) * 2	3 4 + 2 *
+pow(2, 10) - -1	2 10 pow 1 neg -
+
+Word	Stack effect	Meaning
+42 0xff -7	( -- n )	push a number
++ - * / % & | ^ << >>	( x y -- x∘y )	the infix operators
+pow min max	( x y -- f(x, y) )	the function-style operations
+neg	( x -- -x )	unary minus (`-` alone is always subtraction)
+dup	( x -- x x )	copy the top value
+swap	( x y -- y x )	exchange the top two
+drop	( x -- )	discard the top value
+clear	( … -- )	empty the stack
+
+$ rustc -O rpn.rs
+$ ./rpn                                  // the stack is printed after every line
+rpn> 3 4 +
+[7]
+rpn> dup *
+[49]
+rpn> 2 0 /
+error: division by zero
+  |  2 0 /
+  |      ^
+[49]                                     // a failed line leaves the stack as it was
+
+$ ./rpn --to-rpn "(3 + 4) * 2"
+3 4 + 2 *
+$ ./rpn --to-infix "3 4 + 2 * 1 2 - -"
+(3 + 4) * 2 - (1 - 2)                    // only the parentheses the tree needs
+$ rustc --test rpn.rs && ./rpn           // the tests
+
+📌 Arithmetic is `Operation::run_with`, so overflow and division by zero behave
+   exactly as in evaluate.rs. Variables are allowed when converting, not when evaluating.
+*/
+
+mod expression;
+mod operation;
+#[cfg(test)]
+mod testing;
+
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use expression::{parse, Error, Expr, ExprKind, Literal, Span};
+use operation::{Mode, Number, Operation};
+
+// ---------- 1. WORDS ----------
+#[derive(Debug, Clone, PartialEq)]
+enum Word {
+    Number { literal: Literal, negative: bool },
+    Variable(String),
+    Op(Operation),
+    Negate,
+    Dup,
+    Swap,
+    Drop,
+    Clear,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    word: Word,
+    span: Span,
+}
+
+// Words are separated by whitespace.
+fn tokenize(source: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        let len = rest[start..].find(char::is_whitespace).unwrap_or(rest.len() - start);
+        let offset = source.len() - rest.len() + start;
+        let text = &rest[start..start + len];
+        let span = Span::new(offset, offset + len);
+        rest = &rest[start + len..];
+
+        let unsigned = text.strip_prefix('-').filter(|digits| digits.starts_with(|c: char| c.is_ascii_digit()));
+        let word = match text {
+            "neg" => Word::Negate,
+            "dup" => Word::Dup,
+            "swap" => Word::Swap,
+            "drop" => Word::Drop,
+            "clear" => Word::Clear,
+            _ if text.starts_with(|c: char| c.is_ascii_digit()) || unsigned.is_some() => {
+                let literal = Literal::parse(unsigned.unwrap_or(text));
+                let literal = literal.ok_or_else(|| Error::new(format!("invalid number `{}`", text), span))?;
+                Word::Number { literal, negative: unsigned.is_some() }
+            }
+            _ => match Operation::from_symbol(text) {
+                Some(op) => Word::Op(op),
+                None if text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => Word::Variable(text.to_string()),
+                None => return Err(Error::new(format!("unknown word `{}`", text), span)),
+            },
+        };
+        tokens.push(Token { word, span });
+    }
+    Ok(tokens)
+}
+
+// ---------- 2. STACK MACHINE ----------
+// The stack words work the same whatever the values are, so evaluating and
+// converting to infix share this loop: `leaf` makes a value from a number or
+// variable, `binary` and `negate` combine values.
+type Combine<'a, V> = &'a dyn Fn(Operation, V, V, &Token) -> Result<V, Error>;
+
+struct Machine<'a, V> {
+    leaf: &'a dyn Fn(&Token) -> Result<V, Error>,
+    binary: Combine<'a, V>,
+    negate: &'a dyn Fn(V, &Token) -> Result<V, Error>,
+}
+
+impl<V: Clone> Machine<'_, V> {
+    // Runs every word on `stack`. On error the stack is left as it was.
+    fn run(&self, source: &str, stack: &mut Vec<V>) -> Result<(), Error> {
+        let mut work = stack.clone();
+        for token in tokenize(source)? {
+            let needed = match token.word {
+                Word::Op(_) | Word::Swap => 2,
+                Word::Negate | Word::Dup | Word::Drop => 1,
+                _ => 0,
+            };
+            if work.len() < needed {
+                let word = &source[token.span.start..token.span.end];
+                let values = if needed == 1 { "value" } else { "values" };
+                let message = format!("`{}` needs {} {} on the stack, found {}", word, needed, values, work.len());
+                return Err(Error::new(message, token.span));
+            }
+            match token.word {
+                Word::Number { .. } | Word::Variable(_) => work.push((self.leaf)(&token)?),
+                Word::Op(op) => {
+                    let y = work.pop().expect("checked above");
+                    let x = work.pop().expect("checked above");
+                    work.push((self.binary)(op, x, y, &token)?);
+                }
+                Word::Negate => {
+                    let x = work.pop().expect("checked above");
+                    work.push((self.negate)(x, &token)?);
+                }
+                Word::Dup => work.push(work.last().expect("checked above").clone()),
+                Word::Swap => {
+                    let len = work.len();
+                    work.swap(len - 1, len - 2);
+                }
+                Word::Drop => {
+                    work.pop();
+                }
+                Word::Clear => work.clear(),
+            }
+        }
+        *stack = work;
+        Ok(())
+    }
+}
+
+// Evaluates RPN on a stack of numbers; errors point at the word that failed.
+fn evaluate<T: Number>(source: &str, stack: &mut Vec<T>, mode: Mode) -> Result<(), Error> {
+    let leaf = |token: &Token| match &token.word {
+        Word::Number { literal, negative } => {
+            let value = literal.value::<T>(token.span)?;
+            match negative {
+                true => negate(value, token, mode),
+                false => Ok(value),
+            }
+        }
+        Word::Variable(name) => Err(Error::new(format!("cannot find value `{}` in this scope", name), token.span)),
+        _ => unreachable!("only numbers and variables are leaves"),
+    };
+    let binary = |op: Operation, x: T, y: T, token: &Token| {
+        op.run_with(x, y, mode).map_err(|err| Error::new(err.to_string(), token.span))
+    };
+    let negate_word = |x: T, token: &Token| negate(x, token, mode);
+    Machine { leaf: &leaf, binary: &binary, negate: &negate_word }.run(source, stack)
+}
+
+fn negate<T: Number>(x: T, token: &Token, mode: Mode) -> Result<T, Error> {
+    let zero = T::from_digits("0", 10).expect("every type has a zero");
+    Operation::Subtract.run_with(zero, x, mode).map_err(|err| Error::new(err.to_string(), token.span))
+}
+
+// ---------- 3. CONVERSION ----------
+// RPN → expression tree: the same machine, with trees on the stack.
+fn rpn_to_expr(source: &str) -> Result<Expr, Error> {
+    let leaf = |token: &Token| {
+        Ok(match &token.word {
+            Word::Number { literal, negative: false } => Expr::number(literal.clone(), token.span),
+            Word::Number { literal, negative: true } => {
+                let digits = Span::new(token.span.start + 1, token.span.end);
+                Expr::negate(Expr::number(literal.clone(), digits), token.span)
+            }
+            Word::Variable(name) => Expr::variable(name.clone(), token.span),
+            _ => unreachable!("only numbers and variables are leaves"),
+        })
+    };
+    let binary = |op: Operation, x: Expr, y: Expr, token: &Token| {
+        let span = x.span.to(y.span).to(token.span);
+        Ok(Expr::binary(op, x, y, span))
+    };
+    let negate = |x: Expr, token: &Token| {
+        let span = x.span.to(token.span);
+        Ok(Expr::negate(x, span))
+    };
+    let mut stack = Vec::new();
+    Machine { leaf: &leaf, binary: &binary, negate: &negate }.run(source, &mut stack)?;
+    match stack.len() {
+        1 => Ok(stack.pop().expect("one value")),
+        n => Err(Error::new(format!("expected one value on the stack, found {}", n), Span::new(0, source.len()))),
+    }
+}
+
+// Expression tree → RPN: operands first, then the operation (post-order).
+fn expr_to_rpn(expr: &Expr) -> String {
+    match &expr.kind {
+        ExprKind::Number(literal) => literal.to_string(),
+        ExprKind::Variable(name) => name.clone(),
+        ExprKind::Negate(inner) => format!("{} neg", expr_to_rpn(inner)),
+        ExprKind::Binary(op, left, right) => format!("{} {} {}", expr_to_rpn(left), expr_to_rpn(right), op),
+    }
+}
+
+fn infix_to_rpn(source: &str) -> Result<String, Error> {
+    Ok(expr_to_rpn(&parse(source)?))
+}
+
+fn rpn_to_infix(source: &str) -> Result<String, Error> {
+    Ok(rpn_to_expr(source)?.to_string())
+}
+
+// ---------- 4. MAIN ----------
+fn show_stack<T: Number>(stack: &[T]) -> String {
+    let values: Vec<String> = stack.iter().map(|value| value.to_string()).collect();
+    format!("[{}]", values.join(" "))
+}
+
+fn usage() -> ! {
+    eprintln!("usage: rpn                      read RPN lines from stdin, printing the stack");
+    eprintln!("       rpn --to-rpn INFIX       convert an infix expression to RPN");
+    eprintln!("       rpn --to-infix RPN       convert RPN to infix with minimal parentheses");
+    std::process::exit(2);
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [flag, source] = args.as_slice() {
+        let result = match flag.as_str() {
+            "--to-rpn" => infix_to_rpn(source),
+            "--to-infix" => rpn_to_infix(source),
+            _ => usage(),
+        };
+        match result {
+            Ok(text) => println!("{}", text),
+            Err(err) => {
+                println!("{}", err.render(source));
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if !args.is_empty() {
+        usage();
+    }
+
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    let mut stack: Vec<i32> = Vec::new();
+    loop {
+        if interactive {
+            print!("rpn> ");
+            io::stdout().flush().expect("failed to write stdout");
+        }
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).expect("failed to read stdin") == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if let Err(err) = evaluate(line, &mut stack, Mode::Checked) {
+            println!("{}", err.render(line));
+        }
+        println!("{}", show_stack(&stack));
+    }
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::{random_expression, SEED};
+
+    fn run(source: &str) -> Result<Vec<i32>, Error> {
+        let mut stack = Vec::new();
+        evaluate(source, &mut stack, Mode::Checked).map(|()| stack)
+    }
+
+    #[test]
+    fn stack_words_check() {
+        assert_eq!(run("1 2 3"), Ok(vec![1, 2, 3]));
+        assert_eq!(run("1 2 swap"), Ok(vec![2, 1]));
+        assert_eq!(run("1 2 dup"), Ok(vec![1, 2, 2]));
+        assert_eq!(run("1 2 drop"), Ok(vec![1]));
+        assert_eq!(run("1 2 clear 3"), Ok(vec![3]));
+        assert_eq!(run(""), Ok(vec![]));
+
+        let err = run("1 +").unwrap_err();
+        assert_eq!(err.message, "`+` needs 2 values on the stack, found 1");
+        assert_eq!(err.span, Span::new(2, 3));
+        assert_eq!(run("swap").unwrap_err().message, "`swap` needs 2 values on the stack, found 0");
+        assert_eq!(run("drop").unwrap_err().message, "`drop` needs 1 value on the stack, found 0");
+        assert_eq!(run("1 $").unwrap_err().message, "unknown word `$`");
+        assert_eq!(run("1 2x").unwrap_err().message, "invalid number `2x`");
+
+        // A failing line leaves the stack untouched.
+        let mut stack = vec![49];
+        let err = evaluate("2 0 /", &mut stack, Mode::Checked).unwrap_err();
+        assert_eq!((err.message.as_str(), err.span), ("division by zero", Span::new(4, 5)));
+        assert_eq!(stack, vec![49]);
+        assert_eq!(show_stack(&stack), "[49]");
+    }
+
+    #[test]
+    fn evaluate_check() {
+        assert_eq!(run("3 4 + 2 *"), Ok(vec![14]));
+        assert_eq!(run("3 dup *"), Ok(vec![9]));
+        assert_eq!(run("10 3 - 2 -"), Ok(vec![5]));
+        assert_eq!(run("10 3 2 - -"), Ok(vec![9]));
+        assert_eq!(run("2 10 pow 1 neg -"), Ok(vec![1025]));
+        assert_eq!(run("-7 2 / 0xff 0b1 <<"), Ok(vec![-3, 510]));
+        assert_eq!(run("3 7 min 5 max"), Ok(vec![5]));
+        assert_eq!(run("x 1 +").unwrap_err().message, "cannot find value `x` in this scope");
+        assert_eq!(run("2147483647 1 +").unwrap_err().message, "arithmetic overflow");
+        // `-` before digits is `neg` on the literal, as in infix, so i32::MIN needs arithmetic.
+        assert_eq!(run("-2147483648").unwrap_err().message, "literal `2147483648` does not fit in i32");
+        assert_eq!(run("-2147483647 1 -"), Ok(vec![i32::MIN]));
+
+        let mut stack: Vec<i32> = Vec::new();
+        evaluate("2147483647 1 +", &mut stack, Mode::Wrapping).unwrap();
+        assert_eq!(stack, vec![i32::MIN]);
+        let mut floats: Vec<f64> = Vec::new();
+        evaluate("7 2 / -1 *", &mut floats, Mode::Checked).unwrap();
+        assert_eq!(floats, vec![-3.5]);
+    }
+
+    #[test]
+    fn conversion_check() {
+        let cases = [
+            ("3 + 4 * 2", "3 4 2 * +"),
+            ("(3 + 4) * 2", "3 4 + 2 *"),
+            ("8 - 3 - 2", "8 3 - 2 -"),
+            ("8 - (3 - 2)", "8 3 2 - -"),
+            ("1 | 2 & 3", "1 2 3 & |"),
+            ("pow(2, 10) - -1", "2 10 pow 1 neg -"),
+            ("-(x + 1) * 0xff", "x 1 + neg 0xff *"),
+            ("min(a, b << 1)", "a b 1 << min"),
+        ];
+        for (infix, rpn) in cases {
+            assert_eq!(infix_to_rpn(infix), Ok(rpn.to_string()), "{}", infix);
+            assert_eq!(rpn_to_infix(rpn), Ok(infix.to_string()), "{}", rpn);
+        }
+
+        // Redundant parentheses go, needed ones stay.
+        let printed = |source: &str| parse(source).unwrap().to_string();
+        assert_eq!(printed("((1 + 2)) + 3"), "1 + 2 + 3");
+        assert_eq!(printed("1 + (2 + 3)"), "1 + (2 + 3)");
+        assert_eq!(printed("(2 * 3) + (4 % 5)"), "2 * 3 + 4 % 5");
+        assert_eq!(printed("(1 << 2) & (3 | 4)"), "1 << 2 & (3 | 4)");
+        assert_eq!(printed("-(2) - (-3)"), "-2 - -3");
+        assert_eq!(printed("max((1 + 2), (x))"), "max(1 + 2, x)");
+        assert_eq!(printed("-(pow(2, 3))"), "-pow(2, 3)");
+        assert_eq!(printed("--(1 * 2)"), "--(1 * 2)");
+
+        assert_eq!(rpn_to_infix("1 2 dup * swap -"), Ok("2 * 2 - 1".to_string()));
+        assert_eq!(rpn_to_infix("-5 3 *"), Ok("-5 * 3".to_string()));
+        assert_eq!(rpn_to_infix("1 2").unwrap_err().message, "expected one value on the stack, found 2");
+        assert_eq!(rpn_to_infix("").unwrap_err().message, "expected one value on the stack, found 0");
+        let spans = rpn_to_expr("10 x -").unwrap();
+        assert_eq!(spans.span, Span::new(0, 6));
+    }
+
+    // infix → RPN → infix gives back the same tree, and RPN evaluates to what the
+    // tree-walking evaluator computes, errors included.
+    #[test]
+    fn round_trip_check() {
+        let mut state = SEED;
+        for _ in 0..3000 {
+            let source = random_expression(&mut state, 4, &[]);
+            let expr = parse(&source).unwrap();
+            let rpn = expr_to_rpn(&expr);
+            let infix = rpn_to_infix(&rpn).unwrap();
+            assert_eq!(expr_to_rpn(&parse(&infix).unwrap()), rpn, "{} → {} → {}", source, rpn, infix);
+            assert_eq!(parse(&expr.to_string()).unwrap().to_string(), expr.to_string());
+            assert!(infix.len() <= source.len(), "{} printed longer as {}", source, infix);
+
+            for mode in [Mode::Checked, Mode::Wrapping, Mode::Saturating] {
+                let mut stack = Vec::new();
+                let result = evaluate(&rpn, &mut stack, mode).map(|()| stack);
+                let expected = expr.eval_with(mode).map(|value: i32| vec![value]);
+                assert_eq!(result.map_err(|err| err.message), expected.map_err(|err| err.message), "{}", rpn);
+            }
+        }
+    }
+}
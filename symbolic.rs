@@ -0,0 +1,271 @@
+/*
+Symbolic derivatives of `Operation` expressions — d/dx on the expression.rs tree
+
+The tree from expression.rs already has variables (`x`, `y`, ...), so a
+derivative is just another tree, built by the usual rules:
+
+Expression	d/dx
+c, y	0	(constants and other variables)
+x	1
+-u	-u'
+u + v, u - v	u' + v', u' - v'
+u * v	u' * v + u * v'	(product rule)
+u / v	(u' * v - u * v') / (v * v)	(quotient rule)
+pow(u, n)	n * pow(u, n - 1) * u'	(n must not depend on x)
+
+Those trees are correct but unreadable, `(1 * x + x * 1) + (0 * x + 3 * 1)`,
+so `simplify` cleans them up (folding constants, x + 0, x * 1, x + x → 2 * x,
+...) to `2 * x + 3`.
+
+📌 The tree is read as real-number arithmetic: evaluate it with eval_at (f64).
+   With i32, 1 / 2 is 0 and derivatives mean nothing.
+⚠️ min, max, %, the bit operations and shifts have no derivative here, nor
+   pow with an exponent that depends on x (that needs ln, which Operation lacks).
+⚠️ simplify assumes denominators are not zero: 0 / v → 0 and v / v → 1.
+*/
+
+// Shared by several programs; each one uses a different subset.
+#![allow(dead_code)]
+
+use crate::expression::{Error, Expr, ExprKind, Literal, Span};
+use crate::operation::{Mode, Operation};
+
+// ---------- 1. DERIVATIVES ----------
+// Builders for new nodes; every node gets the span of the expression it came from.
+fn number(n: i64, span: Span) -> Expr {
+    let literal = Literal { digits: n.unsigned_abs().to_string(), radix: 10 };
+    match n < 0 {
+        true => Expr::negate(Expr::number(literal, span), span),
+        false => Expr::number(literal, span),
+    }
+}
+
+fn binary(op: Operation, left: Expr, right: Expr, span: Span) -> Expr {
+    Expr::binary(op, left, right, span)
+}
+
+pub fn depends_on(expr: &Expr, var: &str) -> bool {
+    match &expr.kind {
+        ExprKind::Number(_) => false,
+        ExprKind::Variable(name) => name == var,
+        ExprKind::Negate(inner) => depends_on(inner, var),
+        ExprKind::Binary(_, left, right) => depends_on(left, var) || depends_on(right, var),
+    }
+}
+
+// The derivative with respect to `var`, exactly as the rules give it.
+pub fn derivative(expr: &Expr, var: &str) -> Result<Expr, Error> {
+    use Operation::*;
+
+    let span = expr.span;
+    if !depends_on(expr, var) {
+        return Ok(number(0, span));
+    }
+    Ok(match &expr.kind {
+        ExprKind::Number(_) => unreachable!("constants don't depend on anything"),
+        ExprKind::Variable(_) => number(1, span),
+        ExprKind::Negate(inner) => Expr::negate(derivative(inner, var)?, span),
+        ExprKind::Binary(op, u, v) => {
+            let (u, v) = (u.as_ref().clone(), v.as_ref().clone());
+            match op {
+                Add | Subtract => binary(*op, derivative(&u, var)?, derivative(&v, var)?, span),
+                Multiply => {
+                    let left = binary(Multiply, derivative(&u, var)?, v.clone(), span);
+                    let right = binary(Multiply, u.clone(), derivative(&v, var)?, span);
+                    binary(Add, left, right, span)
+                }
+                Divide => {
+                    let left = binary(Multiply, derivative(&u, var)?, v.clone(), span);
+                    let right = binary(Multiply, u.clone(), derivative(&v, var)?, span);
+                    let numerator = binary(Subtract, left, right, span);
+                    binary(Divide, numerator, binary(Multiply, v.clone(), v, span), span)
+                }
+                Pow if depends_on(&v, var) => {
+                    let message = format!("cannot differentiate `pow` with an exponent that depends on `{}`", var);
+                    return Err(Error::new(message, v.span));
+                }
+                Pow => {
+                    let lowered = binary(Pow, u.clone(), binary(Subtract, v.clone(), number(1, span), span), span);
+                    binary(Multiply, binary(Multiply, v, lowered, span), derivative(&u, var)?, span)
+                }
+                Remainder | Min | Max | BitAnd | BitOr | BitXor | Shl | Shr => {
+                    return Err(Error::new(format!("cannot differentiate `{}`", op), span));
+                }
+            }
+        }
+    })
+}
+
+// The derivative, simplified.
+pub fn differentiate(expr: &Expr, var: &str) -> Result<Expr, Error> {
+    Ok(simplify(&derivative(expr, var)?))
+}
+
+// ---------- 2. SIMPLIFICATION ----------
+// The value of a constant subtree, if it is a (possibly negated) integer literal.
+fn constant(expr: &Expr) -> Option<i64> {
+    match &expr.kind {
+        ExprKind::Number(literal) => literal.value::<i64>(expr.span).ok(),
+        ExprKind::Negate(inner) => constant(inner)?.checked_neg(),
+        _ => None,
+    }
+}
+
+// Equal trees, whatever their spans.
+fn same(a: &Expr, b: &Expr) -> bool {
+    a.to_string() == b.to_string()
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// Rewrites the tree bottom-up until nothing changes.
+pub fn simplify(expr: &Expr) -> Expr {
+    const MAX_ROUNDS: usize = 16;
+    let mut current = expr.clone();
+    for _ in 0..MAX_ROUNDS {
+        let next = simplify_once(&current);
+        if same(&next, &current) {
+            break;
+        }
+        current = next;
+    }
+    current
+}
+
+fn simplify_once(expr: &Expr) -> Expr {
+    let span = expr.span;
+    match &expr.kind {
+        ExprKind::Number(_) | ExprKind::Variable(_) => expr.clone(),
+        ExprKind::Negate(inner) => simplify_negate(simplify_once(inner), span),
+        ExprKind::Binary(op, left, right) => simplify_binary(*op, simplify_once(left), simplify_once(right), span),
+    }
+}
+
+fn simplify_negate(inner: Expr, span: Span) -> Expr {
+    if let Some(n) = constant(&inner).and_then(i64::checked_neg) {
+        return number(n, span);
+    }
+    let inner_span = inner.span;
+    match inner.into_kind() {
+        ExprKind::Negate(inner) => *inner,                                          // --u → u
+        ExprKind::Binary(Operation::Subtract, a, b) => binary(Operation::Subtract, *b, *a, span), // -(a - b) → b - a
+        kind => Expr::negate(Expr { kind, span: inner_span }, span),
+    }
+}
+
+// Constant folding for the exact cases; a fraction is only reduced (6 / 4 → 3 / 2).
+fn fold(op: Operation, a: i64, b: i64, span: Span) -> Option<Expr> {
+    use Operation::*;
+    let value = match op {
+        Add => a.checked_add(b)?,
+        Subtract => a.checked_sub(b)?,
+        Multiply => a.checked_mul(b)?,
+        Pow => a.checked_pow(u32::try_from(b).ok()?)?,
+        Divide if b != 0 && a % b == 0 => a / b,
+        Divide if b != 0 => {
+            let divisor = gcd(a, b) * b.signum();
+            if divisor == 1 {
+                return None;
+            }
+            return Some(binary(Divide, number(a / divisor, span), number(b / divisor, span), span));
+        }
+        _ => return None,
+    };
+    Some(number(value, span))
+}
+
+fn simplify_binary(op: Operation, a: Expr, b: Expr, span: Span) -> Expr {
+    use Operation::*;
+
+    let (ca, cb) = (constant(&a), constant(&b));
+    if let (Some(x), Some(y)) = (ca, cb) {
+        if let Some(folded) = fold(op, x, y, span) {
+            return folded;
+        }
+    }
+    let negated = |expr: &Expr| match &expr.kind {
+        ExprKind::Negate(inner) => Some(inner.as_ref().clone()),
+        _ => None,
+    };
+    let operands = |expr: &Expr, wanted: Operation| match &expr.kind {
+        ExprKind::Binary(op, x, y) if *op == wanted => Some((x.as_ref().clone(), y.as_ref().clone())),
+        _ => None,
+    };
+
+    match op {
+        Add if ca == Some(0) => b,
+        Add | Subtract if cb == Some(0) => a,
+        Add if same(&a, &b) => binary(Multiply, number(2, span), a, span),
+        Add if negated(&b).is_some() => binary(Subtract, a, negated(&b).expect("checked"), span), // a + -v → a - v
+        Add if negated(&a).is_some() => binary(Subtract, b, negated(&a).expect("checked"), span), // -u + b → b - u
+        Add if operands(&a, Subtract).is_some_and(|(_, v)| same(&v, &b)) => operands(&a, Subtract).expect("checked").0, // (u - v) + v → u
+        Subtract if ca == Some(0) => simplify_negate(b, span),
+        Subtract if same(&a, &b) => number(0, span),
+        Subtract if negated(&b).is_some() => binary(Add, a, negated(&b).expect("checked"), span), // a - -v → a + v
+        // (u + v) - u → v, (u + v) - v → u
+        Subtract if operands(&a, Add).is_some_and(|(u, v)| same(&u, &b) || same(&v, &b)) => {
+            let (u, v) = operands(&a, Add).expect("checked");
+            if same(&u, &b) {
+                v
+            } else {
+                u
+            }
+        }
+        Multiply if ca == Some(0) || cb == Some(0) => number(0, span),
+        Multiply if ca == Some(1) => b,
+        Multiply if cb == Some(1) => a,
+        Multiply if ca == Some(-1) => simplify_negate(b, span),
+        Multiply if cb == Some(-1) => simplify_negate(a, span),
+        // Constants go first: x * 2 → 2 * x, then 2 * (3 * x) → 6 * x.
+        Multiply if cb.is_some() && ca.is_none() => binary(Multiply, b, a, span),
+        Multiply if ca.is_some() && operands(&b, Multiply).is_some_and(|(c, _)| constant(&c).is_some()) => {
+            let (c, v) = operands(&b, Multiply).expect("checked");
+            binary(Multiply, simplify_binary(Multiply, a, c, span), v, span)
+        }
+        // u * (c * v) → c * (u * v), so constants bubble up to the front.
+        Multiply if operands(&b, Multiply).is_some_and(|(c, _)| constant(&c).is_some()) => {
+            let (c, v) = operands(&b, Multiply).expect("checked");
+            binary(Multiply, c, binary(Multiply, a, v, span), span)
+        }
+        Multiply if same(&a, &b) => binary(Pow, a, number(2, span), span),
+        // pow(u, n) * u → pow(u, n + 1)
+        Multiply if operands(&a, Pow).is_some_and(|(u, n)| same(&u, &b) && constant(&n).is_some()) => {
+            let (u, n) = operands(&a, Pow).expect("checked");
+            binary(Pow, u, simplify_binary(Add, n, number(1, span), span), span)
+        }
+        Divide if cb == Some(1) => a,
+        Divide if ca == Some(0) => number(0, span),
+        Divide if same(&a, &b) => number(1, span),
+        Pow if cb == Some(0) || ca == Some(1) => number(1, span),
+        Pow if cb == Some(1) => a,
+        // pow(pow(u, m), n) → pow(u, m * n)
+        Pow if cb.is_some() && operands(&a, Pow).is_some_and(|(_, m)| constant(&m).is_some()) => {
+            let (u, m) = operands(&a, Pow).expect("checked");
+            binary(Pow, u, simplify_binary(Multiply, m, b, span), span)
+        }
+        _ => binary(op, a, b, span),
+    }
+}
+
+// ---------- 3. NUMBERS ----------
+// The value of `expr` with `var` set to `x`, in f64.
+pub fn eval_at(expr: &Expr, var: &str, x: f64) -> Result<f64, Error> {
+    expr.eval_in(Mode::Checked, &|name, span| match name == var {
+        true => Ok(x),
+        false => Err(Error::new(format!("cannot find value `{}` in this scope", name), span)),
+    })
+}
+
+// The central difference (f(x + h) - f(x - h)) / 2h, an estimate of f'(x)
+// with an error proportional to h².
+pub fn finite_difference(expr: &Expr, var: &str, x: f64) -> Option<f64> {
+    let h = 1e-5 * x.abs().max(1.0);
+    let (above, below) = (eval_at(expr, var, x + h).ok()?, eval_at(expr, var, x - h).ok()?);
+    Some((above - below) / (2.0 * h))
+}
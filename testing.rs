@@ -0,0 +1,50 @@
+/*
+Fixtures for the randomized tests — a seeded xorshift generator and random
+calculator expressions
+
+Programs with randomized tests include it in test builds only:
+
+    #[cfg(test)]
+    mod testing;
+
+next_random(&mut state)	xorshift64: fast, reproducible, no extra crates
+SEED	the starting state every test uses, so a failure reproduces on the next run
+random_expression	calculator source text with operands likely to hit every error
+
+📌 The expression fixture spells the operators out as source text instead of
+   going through operation.rs, so it can't drift along with the code under
+   test — and programs that don't parse expressions can still use the generator.
+*/
+
+// Shared by several programs; each one uses a different subset.
+#![allow(dead_code)]
+
+pub const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+pub fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+// Infix operators first, then the function-style ones.
+const OPERATORS: [&str; 13] = ["+", "-", "*", "/", "%", "&", "|", "^", "<<", ">>", "pow", "min", "max"];
+const INFIX: usize = 10;
+const OPERANDS: [&str; 9] = ["0", "1", "2", "3", "7", "-1", "31", "65535", "2147483647"];
+
+// A random expression of at most `depth` levels; `names` are variables it may read.
+pub fn random_expression(state: &mut u64, depth: u32, names: &[&str]) -> String {
+    let pick = next_random(state);
+    if depth == 0 || pick.is_multiple_of(4) {
+        let choice = (pick / 4 % (OPERANDS.len() + names.len()) as u64) as usize;
+        return OPERANDS.get(choice).unwrap_or_else(|| &names[choice - OPERANDS.len()]).to_string();
+    }
+    let op = (pick / 4 % OPERATORS.len() as u64) as usize;
+    let (x, y) = (random_expression(state, depth - 1, names), random_expression(state, depth - 1, names));
+    match op {
+        _ if op >= INFIX => format!("{}({}, {})", OPERATORS[op], x, y),
+        _ if pick.is_multiple_of(7) => format!("-({} {} {})", x, OPERATORS[op], y),
+        _ => format!("({} {} {})", x, OPERATORS[op], y),
+    }
+}
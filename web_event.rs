@@ -0,0 +1,940 @@
+/*
+WebEvent streams — the enum and dispatcher from 1.rs (section 3), plus a log
+that records events with their time and replays them later
+
+A recording is line-delimited JSON (NDJSON): one event per line, stamped with
+microseconds since the recording started (Instant, so the clock never runs
+backwards):
+
+{"t_us":0,"type":"PageLoad"}
+{"t_us":1250340,"type":"KeyPress","key":"a"}
+{"t_us":1874002,"type":"Click","x":10,"y":20}
+
+Besides PageLoad, KeyPress(char) and Click { x, y } there is the rest of real
+input, each with its own JSON fields:
+
+Variant	Carries	Log fields
+Key(KeyEvent)	key (Char, Enter, Up, F(5), ...), modifiers, press or release	key, modifiers?, state?
+Mouse(MouseEvent)	Press / Release / Drag(button), Move; position, modifiers	action, button, x, y, modifiers?
+Scroll	position, dx / dy (dy < 0 is up), modifiers	x, y, dx, dy, modifiers?
+Resize	width, height	width, height
+Paste(String)	a bracketed paste, as one event	text
+FocusIn, FocusOut	-	-
+
+{"t_us":2001000,"type":"Key","key":"Up","modifiers":"Ctrl+Shift"}
+{"t_us":2400000,"type":"Mouse","action":"Drag","button":"Right","x":4,"y":7}
+
+📌 `KeyEvent::from('a').into()` is KeyPress('a') and a plain left press is Click:
+   From<KeyEvent> / From<MouseEvent> keep the original variants for the plain
+   cases, so KeyPress and Click handlers keep working. key_event() and
+   mouse_event() go the other way, reading both forms as one.
+📌 WebEvent, EventKind, Key and Button are #[non_exhaustive] so new kinds of
+   input can be added later. The attribute only binds outside the defining
+   crate: the programs here include this file as a module and still match
+   exhaustively, but once it moves into a library, callers need a `_` arm.
+
+Replay feeds the events to a Dispatcher, so the handlers that saw them live
+(`inspect` and friends) see them again, in the same order:
+
+Speed	Event at t is dispatched at
+Original	t after replay started
+Scaled(2.0)	t / 2 (twice as fast; 0.5 is half speed)
+Fastest	immediately, no waiting
+
+Speed::scaled(f) is the only way to get Scaled: it returns None unless f is
+finite and > 0, so no replay waits forever. replay_with takes the Clock to wait
+on; replay uses the system clock, tests a fake one.
+
+📌 A bug report is the log file: replay it and the handlers get exactly the
+   events the user produced, whatever the speed.
+📌 Replay waits for deadlines measured from its start, not for the gaps between
+   events, so slow handlers don't make it drift further and further behind.
+⚠️ The log is checked when it is read: bad JSON, an unknown type or a time that
+   goes backwards is an error with its line number.
+*/
+
+// Shared by several programs; each one uses a different subset.
+#![allow(dead_code)]
+
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+// ---------- 1. EVENTS AND DISPATCH ----------
+// Ctrl, Alt, Shift and Meta as bits: `Modifiers::CTRL | Modifiers::SHIFT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers(0);
+    pub const SHIFT: Modifiers = Modifiers(1);
+    pub const ALT: Modifiers = Modifiers(2);
+    pub const CTRL: Modifiers = Modifiers(4);
+    pub const META: Modifiers = Modifiers(8);
+    const NAMES: [(Modifiers, &'static str); 4] =
+        [(Modifiers::CTRL, "Ctrl"), (Modifiers::ALT, "Alt"), (Modifiers::SHIFT, "Shift"), (Modifiers::META, "Meta")];
+
+    pub fn contains(self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    // "Ctrl+Shift" → CTRL | SHIFT; "" → NONE.
+    pub fn from_names(text: &str) -> Option<Modifiers> {
+        text.split('+').filter(|name| !name.is_empty()).try_fold(Modifiers::NONE, |modifiers, name| {
+            let (modifier, _) = Self::NAMES.iter().find(|(_, known)| *known == name)?;
+            Some(modifiers | *modifier)
+        })
+    }
+}
+
+impl std::ops::BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, other: Modifiers) -> Modifiers {
+        Modifiers(self.0 | other.0)
+    }
+}
+
+// "Ctrl+Shift", always in the order Ctrl, Alt, Shift, Meta; "" for none.
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = Self::NAMES.iter().filter(|(modifier, _)| self.contains(*modifier)).map(|(_, name)| *name).collect();
+        f.write_str(&names.join("+"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Key {
+    Char(char),
+    Enter,
+    Tab,
+    BackTab, // Shift-Tab
+    Backspace,
+    Escape,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    Insert,
+    Delete,
+    PageUp,
+    PageDown,
+    F(u8), // F1 - F24
+}
+
+const NAMED_KEYS: [Key; 15] = [
+    Key::Enter,
+    Key::Tab,
+    Key::BackTab,
+    Key::Backspace,
+    Key::Escape,
+    Key::Up,
+    Key::Down,
+    Key::Left,
+    Key::Right,
+    Key::Home,
+    Key::End,
+    Key::Insert,
+    Key::Delete,
+    Key::PageUp,
+    Key::PageDown,
+];
+
+impl Key {
+    // The inverse of Display: "a" is Char('a'), "F5" is F(5), "Up" is Up.
+    pub fn from_name(name: &str) -> Option<Key> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Some(Key::Char(c));
+        }
+        if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse().ok()).filter(|n| (1..=24).contains(n)) {
+            return Some(Key::F(n));
+        }
+        NAMED_KEYS.into_iter().find(|key| key.to_string() == name)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Char(c) => write!(f, "{}", c),
+            Key::F(n) => write!(f, "F{}", n),
+            named => write!(f, "{:?}", named),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyState {
+    Press,
+    Release, // only from terminals and browsers that report releases
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub modifiers: Modifiers,
+    pub state: KeyState,
+}
+
+impl KeyEvent {
+    pub fn new(key: Key, modifiers: Modifiers) -> Self {
+        KeyEvent { key, modifiers, state: KeyState::Press }
+    }
+
+    pub fn release(key: Key, modifiers: Modifiers) -> Self {
+        KeyEvent { key, modifiers, state: KeyState::Release }
+    }
+}
+
+impl From<char> for KeyEvent {
+    fn from(c: char) -> Self {
+        KeyEvent::new(Key::Char(c), Modifiers::NONE)
+    }
+}
+
+impl From<Key> for KeyEvent {
+    fn from(key: Key) -> Self {
+        KeyEvent::new(key, Modifiers::NONE)
+    }
+}
+
+// "Ctrl+Up", "a"
+impl fmt::Display for KeyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.modifiers.is_empty() {
+            write!(f, "{}+", self.modifiers)?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Button {
+    Left,
+    Middle,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MouseAction {
+    Press(Button),
+    Release(Button),
+    Drag(Button), // moved with the button held
+    Move,         // moved with no button held
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseEvent {
+    pub action: MouseAction,
+    pub x: i64,
+    pub y: i64,
+    pub modifiers: Modifiers,
+}
+
+// #[non_exhaustive]: a match in another crate needs a `_` arm, so new kinds of
+// input can be added without breaking it. Inside this crate it changes nothing.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum WebEvent {
+    PageLoad,
+    KeyPress(char),           // a character key pressed with no modifiers
+    Click { x: i64, y: i64 }, // the left button pressed with no modifiers
+    Key(KeyEvent),            // every other key press or release
+    Mouse(MouseEvent),        // every other button, release, drag or move
+    Scroll { x: i64, y: i64, dx: i64, dy: i64, modifiers: Modifiers }, // dy < 0: up
+    Resize { width: u32, height: u32 },
+    Paste(String), // bracketed paste: the text as one event, not as key presses
+    FocusIn,
+    FocusOut,
+}
+
+// The plain cases become the original variants, so handlers for KeyPress and
+// Click see them however the event was produced.
+impl From<KeyEvent> for WebEvent {
+    fn from(event: KeyEvent) -> Self {
+        match event {
+            KeyEvent { key: Key::Char(c), modifiers: Modifiers::NONE, state: KeyState::Press } => WebEvent::KeyPress(c),
+            event => WebEvent::Key(event),
+        }
+    }
+}
+
+impl From<MouseEvent> for WebEvent {
+    fn from(event: MouseEvent) -> Self {
+        match event {
+            MouseEvent { action: MouseAction::Press(Button::Left), x, y, modifiers: Modifiers::NONE } => WebEvent::Click { x, y },
+            event => WebEvent::Mouse(event),
+        }
+    }
+}
+
+// The variant of an event without its data: what handlers subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum EventKind {
+    PageLoad,
+    KeyPress,
+    Click,
+    Key,
+    Mouse,
+    Scroll,
+    Resize,
+    Paste,
+    FocusIn,
+    FocusOut,
+}
+
+impl WebEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            WebEvent::PageLoad => EventKind::PageLoad,
+            WebEvent::KeyPress(_) => EventKind::KeyPress,
+            WebEvent::Click { .. } => EventKind::Click,
+            WebEvent::Key(_) => EventKind::Key,
+            WebEvent::Mouse(_) => EventKind::Mouse,
+            WebEvent::Scroll { .. } => EventKind::Scroll,
+            WebEvent::Resize { .. } => EventKind::Resize,
+            WebEvent::Paste(_) => EventKind::Paste,
+            WebEvent::FocusIn => EventKind::FocusIn,
+            WebEvent::FocusOut => EventKind::FocusOut,
+        }
+    }
+
+    // Any key event, KeyPress('a') included, as a KeyEvent.
+    pub fn key_event(&self) -> Option<KeyEvent> {
+        match self {
+            WebEvent::KeyPress(c) => Some(KeyEvent::from(*c)),
+            WebEvent::Key(event) => Some(*event),
+            _ => None,
+        }
+    }
+
+    // Any mouse event, Click included, as a MouseEvent.
+    pub fn mouse_event(&self) -> Option<MouseEvent> {
+        match self {
+            WebEvent::Click { x, y } => {
+                Some(MouseEvent { action: MouseAction::Press(Button::Left), x: *x, y: *y, modifiers: Modifiers::NONE })
+            }
+            WebEvent::Mouse(event) => Some(*event),
+            _ => None,
+        }
+    }
+}
+
+// What a handler returns: let later handlers see the event, or not.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Propagation {
+    Continue,
+    Stop,
+}
+
+// Returned when a handler is registered; pass it to `off` to remove the handler.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandlerId(u64);
+
+type Handler = Box<dyn FnMut(&WebEvent) -> Propagation>;
+
+struct Registration {
+    id: HandlerId,
+    kind: Option<EventKind>, // None: every event
+    handler: Handler,
+}
+
+// Handlers run in the order they were registered, whether they asked for one
+// kind of event or for all of them, until one returns Propagation::Stop.
+#[derive(Default)]
+pub struct Dispatcher {
+    handlers: Vec<Registration>,
+    next_id: u64,
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn register(&mut self, kind: Option<EventKind>, handler: Handler) -> HandlerId {
+        let id = HandlerId(self.next_id);
+        self.next_id += 1;
+        self.handlers.push(Registration { id, kind, handler });
+        id
+    }
+
+    pub fn on(&mut self, kind: EventKind, handler: impl FnMut(&WebEvent) -> Propagation + 'static) -> HandlerId {
+        self.register(Some(kind), Box::new(handler))
+    }
+
+    pub fn on_any(&mut self, handler: impl FnMut(&WebEvent) -> Propagation + 'static) -> HandlerId {
+        self.register(None, Box::new(handler))
+    }
+
+    // false if the handler was already removed. Ids are never reused.
+    pub fn off(&mut self, id: HandlerId) -> bool {
+        let before = self.handlers.len();
+        self.handlers.retain(|registration| registration.id != id);
+        self.handlers.len() < before
+    }
+
+    // Returns how many handlers saw the event.
+    pub fn dispatch(&mut self, event: &WebEvent) -> usize {
+        let mut called = 0;
+        for registration in &mut self.handlers {
+            if registration.kind.is_some_and(|kind| kind != event.kind()) {
+                continue;
+            }
+            called += 1;
+            if (registration.handler)(event) == Propagation::Stop {
+                break;
+            }
+        }
+        called
+    }
+}
+
+// The logger: one handler for every event.
+pub fn inspect(event: &WebEvent) -> Propagation {
+    match event {
+        WebEvent::PageLoad => println!("Page loaded"),
+        WebEvent::KeyPress(c) => println!("Key pressed: {}", c),
+        WebEvent::Click { x, y } => println!("Clicked at: {}, {}", x, y),
+        WebEvent::Key(key) => match key.state {
+            KeyState::Press => println!("Key pressed: {}", key),
+            KeyState::Release => println!("Key released: {}", key),
+        },
+        WebEvent::Mouse(MouseEvent { action, x, y, modifiers }) if modifiers.is_empty() => {
+            println!("Mouse {:?} at: {}, {}", action, x, y)
+        }
+        WebEvent::Mouse(MouseEvent { action, x, y, modifiers }) => println!("Mouse {}+{:?} at: {}, {}", modifiers, action, x, y),
+        WebEvent::Scroll { x, y, dx, dy, .. } => println!("Scrolled by {}, {} at: {}, {}", dx, dy, x, y),
+        WebEvent::Resize { width, height } => println!("Resized to {}x{}", width, height),
+        WebEvent::Paste(text) => println!("Pasted: {:?}", text),
+        WebEvent::FocusIn => println!("Focus gained"),
+        WebEvent::FocusOut => println!("Focus lost"),
+    }
+    Propagation::Continue
+}
+
+// ---------- 2. THE LOG FORMAT ----------
+// An event and when it happened, relative to the start of the recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recorded {
+    pub at: Duration,
+    pub event: WebEvent,
+}
+
+// Where and why a log line failed to parse; `pos` is a byte offset into the line.
+#[derive(Debug, PartialEq)]
+pub struct ParseEventError {
+    pub pos: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseEventError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.pos)
+    }
+}
+
+impl std::error::Error for ParseEventError {}
+
+fn parse_error<V>(pos: usize, message: impl Into<String>) -> Result<V, ParseEventError> {
+    Err(ParseEventError { pos, message: message.into() })
+}
+
+fn write_string(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// A field value: the log only has strings and integers.
+#[derive(Debug, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i128),
+}
+
+fn skip_whitespace(input: &str, pos: &mut usize) {
+    *pos += input[*pos..].len() - input[*pos..].trim_start().len();
+}
+
+fn read_string(input: &str, pos: &mut usize) -> Result<String, ParseEventError> {
+    if !input[*pos..].starts_with('"') {
+        return parse_error(*pos, "expected a string");
+    }
+    let mut value = String::new();
+    let start = *pos + 1;
+    let mut chars = input[start..].char_indices().map(|(i, c)| (start + i, c));
+    while let Some((at, c)) = chars.next() {
+        match c {
+            '"' => {
+                *pos = at + 1;
+                return Ok(value);
+            }
+            '\\' => {
+                let escaped = match chars.next() {
+                    Some((_, '"')) => '"',
+                    Some((_, '\\')) => '\\',
+                    Some((_, '/')) => '/',
+                    Some((_, 'b')) => '\u{8}',
+                    Some((_, 'f')) => '\u{c}',
+                    Some((_, 'n')) => '\n',
+                    Some((_, 'r')) => '\r',
+                    Some((_, 't')) => '\t',
+                    Some((_, 'u')) => {
+                        let code = match hex_escape(&mut chars) {
+                            // Outside the BMP a char is a UTF-16 surrogate pair: "\ud83d\ude00" is 😀.
+                            Some(high @ 0xd800..=0xdbff) => match (chars.next(), chars.next(), hex_escape(&mut chars)) {
+                                (Some((_, '\\')), Some((_, 'u')), Some(low @ 0xdc00..=0xdfff)) => {
+                                    Some(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+                                }
+                                _ => None,
+                            },
+                            code => code,
+                        };
+                        match code.and_then(char::from_u32) {
+                            Some(c) => c,
+                            None => return parse_error(at, "bad \\u escape"),
+                        }
+                    }
+                    _ => return parse_error(at, "expected a valid escape"),
+                };
+                value.push(escaped);
+            }
+            c => value.push(c),
+        }
+    }
+    parse_error(input.len(), "expected closing `\"`")
+}
+
+// The four hex digits after `\u`; None unless there are exactly four.
+fn hex_escape(chars: &mut impl Iterator<Item = (usize, char)>) -> Option<u32> {
+    let hex: String = chars.take(4).map(|(_, c)| c).collect();
+    if hex.len() != 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(&hex, 16).ok()
+}
+
+fn read_value(input: &str, pos: &mut usize) -> Result<Value, ParseEventError> {
+    if input[*pos..].starts_with('"') {
+        return read_string(input, pos).map(Value::String);
+    }
+    let start = *pos;
+    let len = input[start..].find(|c: char| !(c.is_ascii_digit() || c == '-')).unwrap_or(input.len() - start);
+    match input[start..start + len].parse() {
+        Ok(n) => {
+            *pos += len;
+            Ok(Value::Integer(n))
+        }
+        Err(_) => parse_error(start, "expected a string or an integer"),
+    }
+}
+
+// {"key":value,...} → the fields in order, each with the position of its value.
+fn read_object(input: &str) -> Result<Vec<(String, usize, Value)>, ParseEventError> {
+    let mut pos = 0;
+    skip_whitespace(input, &mut pos);
+    if !input[pos..].starts_with('{') {
+        return parse_error(pos, "expected `{`");
+    }
+    pos += 1;
+    let mut fields = Vec::new();
+    skip_whitespace(input, &mut pos);
+    if input[pos..].starts_with('}') {
+        pos += 1;
+    } else {
+        loop {
+            skip_whitespace(input, &mut pos);
+            let key = read_string(input, &mut pos)?;
+            skip_whitespace(input, &mut pos);
+            if !input[pos..].starts_with(':') {
+                return parse_error(pos, "expected `:`");
+            }
+            pos += 1;
+            skip_whitespace(input, &mut pos);
+            let at = pos;
+            fields.push((key, at, read_value(input, &mut pos)?));
+            skip_whitespace(input, &mut pos);
+            match input[pos..].chars().next() {
+                Some(',') => pos += 1,
+                Some('}') => {
+                    pos += 1;
+                    break;
+                }
+                _ => return parse_error(pos, "expected `,` or `}`"),
+            }
+        }
+    }
+    skip_whitespace(input, &mut pos);
+    if pos < input.len() {
+        return parse_error(pos, "unexpected input after `}`");
+    }
+    Ok(fields)
+}
+
+// The fields of one line. Each is taken once; whatever is left over is unknown.
+struct Fields<'a> {
+    line: &'a str,
+    fields: Vec<(String, usize, Value)>, // name, position of the value, value
+}
+
+impl Fields<'_> {
+    fn take(&mut self, name: &str) -> Option<(usize, Value)> {
+        let i = self.fields.iter().position(|(key, _, _)| key == name)?;
+        let (_, at, value) = self.fields.remove(i);
+        Some((at, value))
+    }
+
+    fn optional_string(&mut self, name: &str) -> Result<Option<(usize, String)>, ParseEventError> {
+        match self.take(name) {
+            Some((at, Value::String(text))) => Ok(Some((at, text))),
+            Some((at, _)) => parse_error(at, format!("`{}` must be a string", name)),
+            None => Ok(None),
+        }
+    }
+
+    fn string(&mut self, name: &str) -> Result<(usize, String), ParseEventError> {
+        match self.optional_string(name)? {
+            Some(field) => Ok(field),
+            None => parse_error(self.line.len(), format!("missing field `{}`", name)),
+        }
+    }
+
+    // `expected` says what fits, for values out of the type's range.
+    fn integer<T: TryFrom<i128>>(&mut self, name: &str, expected: &str) -> Result<T, ParseEventError> {
+        match self.take(name) {
+            Some((at, Value::Integer(n))) => T::try_from(n).or_else(|_| parse_error(at, format!("`{}` must be {}", name, expected))),
+            Some((at, _)) => parse_error(at, format!("`{}` must be an integer", name)),
+            None => parse_error(self.line.len(), format!("missing field `{}`", name)),
+        }
+    }
+
+    fn modifiers(&mut self) -> Result<Modifiers, ParseEventError> {
+        match self.optional_string("modifiers")? {
+            Some((at, text)) => Modifiers::from_names(&text).map_or_else(|| parse_error(at, format!("unknown modifiers `{}`", text)), Ok),
+            None => Ok(Modifiers::NONE),
+        }
+    }
+
+    fn button(&mut self) -> Result<Button, ParseEventError> {
+        let (at, name) = self.string("button")?;
+        match name.as_str() {
+            "Left" => Ok(Button::Left),
+            "Middle" => Ok(Button::Middle),
+            "Right" => Ok(Button::Right),
+            _ => parse_error(at, format!("unknown button `{}`", name)),
+        }
+    }
+
+    fn finish(self) -> Result<(), ParseEventError> {
+        match self.fields.first() {
+            Some((key, at, _)) => parse_error(*at, format!("unknown field `{}`", key)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn write_field(out: &mut String, name: &str, value: &str) {
+    out.push_str(&format!(",\"{}\":", name));
+    write_string(value, out);
+}
+
+impl Recorded {
+    pub fn to_json(&self) -> String {
+        let mut out = format!("{{\"t_us\":{}", self.at.as_micros());
+        let modifiers = |out: &mut String, modifiers: Modifiers| {
+            if !modifiers.is_empty() {
+                write_field(out, "modifiers", &modifiers.to_string());
+            }
+        };
+        match &self.event {
+            WebEvent::PageLoad => write_field(&mut out, "type", "PageLoad"),
+            WebEvent::KeyPress(c) => {
+                write_field(&mut out, "type", "KeyPress");
+                write_field(&mut out, "key", c.encode_utf8(&mut [0; 4]));
+            }
+            WebEvent::Click { x, y } => {
+                write_field(&mut out, "type", "Click");
+                out.push_str(&format!(",\"x\":{},\"y\":{}", x, y));
+            }
+            WebEvent::Key(event) => {
+                write_field(&mut out, "type", "Key");
+                write_field(&mut out, "key", &event.key.to_string());
+                modifiers(&mut out, event.modifiers);
+                if event.state == KeyState::Release {
+                    write_field(&mut out, "state", "Release");
+                }
+            }
+            WebEvent::Mouse(event) => {
+                write_field(&mut out, "type", "Mouse");
+                let (action, button) = match event.action {
+                    MouseAction::Press(button) => ("Press", Some(button)),
+                    MouseAction::Release(button) => ("Release", Some(button)),
+                    MouseAction::Drag(button) => ("Drag", Some(button)),
+                    MouseAction::Move => ("Move", None),
+                };
+                write_field(&mut out, "action", action);
+                if let Some(button) = button {
+                    write_field(&mut out, "button", &format!("{:?}", button));
+                }
+                out.push_str(&format!(",\"x\":{},\"y\":{}", event.x, event.y));
+                modifiers(&mut out, event.modifiers);
+            }
+            WebEvent::Scroll { x, y, dx, dy, modifiers: held } => {
+                write_field(&mut out, "type", "Scroll");
+                out.push_str(&format!(",\"x\":{},\"y\":{},\"dx\":{},\"dy\":{}", x, y, dx, dy));
+                modifiers(&mut out, *held);
+            }
+            WebEvent::Resize { width, height } => {
+                write_field(&mut out, "type", "Resize");
+                out.push_str(&format!(",\"width\":{},\"height\":{}", width, height));
+            }
+            WebEvent::Paste(text) => {
+                write_field(&mut out, "type", "Paste");
+                write_field(&mut out, "text", text);
+            }
+            WebEvent::FocusIn => write_field(&mut out, "type", "FocusIn"),
+            WebEvent::FocusOut => write_field(&mut out, "type", "FocusOut"),
+        }
+        out.push('}');
+        out
+    }
+
+    // Fields may come in any order; missing, repeated or unknown ones are errors.
+    pub fn from_json(line: &str) -> Result<Recorded, ParseEventError> {
+        let fields = read_object(line)?;
+        for (i, (key, at, _)) in fields.iter().enumerate() {
+            if fields[..i].iter().any(|(other, _, _)| other == key) {
+                return parse_error(*at, format!("duplicate field `{}`", key));
+            }
+        }
+        let mut fields = Fields { line, fields };
+        const COORDINATE: &str = "a 64-bit integer";
+
+        let t_us: u64 = fields.integer("t_us", "a time in microseconds")?;
+        let (at, kind) = fields.string("type")?;
+        let event = match kind.as_str() {
+            "PageLoad" => WebEvent::PageLoad,
+            "KeyPress" => {
+                let (at, key) = fields.string("key")?;
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => WebEvent::KeyPress(c),
+                    _ => return parse_error(at, "`key` must be one character"),
+                }
+            }
+            "Click" => WebEvent::Click { x: fields.integer("x", COORDINATE)?, y: fields.integer("y", COORDINATE)? },
+            "Key" => {
+                let (at, name) = fields.string("key")?;
+                let Some(key) = Key::from_name(&name) else {
+                    return parse_error(at, format!("unknown key `{}`", name));
+                };
+                let modifiers = fields.modifiers()?;
+                let state = match fields.optional_string("state")? {
+                    None => KeyState::Press,
+                    Some((_, state)) if state == "Press" => KeyState::Press,
+                    Some((_, state)) if state == "Release" => KeyState::Release,
+                    Some((at, state)) => return parse_error(at, format!("unknown key state `{}`", state)),
+                };
+                WebEvent::Key(KeyEvent { key, modifiers, state })
+            }
+            "Mouse" => {
+                let (at, action) = fields.string("action")?;
+                let action = match action.as_str() {
+                    "Press" => MouseAction::Press(fields.button()?),
+                    "Release" => MouseAction::Release(fields.button()?),
+                    "Drag" => MouseAction::Drag(fields.button()?),
+                    "Move" => MouseAction::Move,
+                    _ => return parse_error(at, format!("unknown mouse action `{}`", action)),
+                };
+                let (x, y) = (fields.integer("x", COORDINATE)?, fields.integer("y", COORDINATE)?);
+                WebEvent::Mouse(MouseEvent { action, x, y, modifiers: fields.modifiers()? })
+            }
+            "Scroll" => WebEvent::Scroll {
+                x: fields.integer("x", COORDINATE)?,
+                y: fields.integer("y", COORDINATE)?,
+                dx: fields.integer("dx", COORDINATE)?,
+                dy: fields.integer("dy", COORDINATE)?,
+                modifiers: fields.modifiers()?,
+            },
+            "Resize" => {
+                let size = "a size from 0 to 4294967295";
+                WebEvent::Resize { width: fields.integer("width", size)?, height: fields.integer("height", size)? }
+            }
+            "Paste" => WebEvent::Paste(fields.string("text")?.1),
+            "FocusIn" => WebEvent::FocusIn,
+            "FocusOut" => WebEvent::FocusOut,
+            _ => return parse_error(at, format!("unknown event type `{}`", kind)),
+        };
+        fields.finish()?;
+        Ok(Recorded { at: Duration::from_micros(t_us), event })
+    }
+}
+
+// A log that failed to read: the 1-based line, and what was wrong with it.
+#[derive(Debug, PartialEq)]
+pub struct LogError {
+    pub line: usize,
+    pub error: ParseEventError,
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl std::error::Error for LogError {}
+
+// Blank lines are skipped; times must never decrease.
+pub fn parse_log(text: &str) -> Result<Vec<Recorded>, LogError> {
+    let mut events: Vec<Recorded> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let recorded = Recorded::from_json(line).map_err(|error| LogError { line: i + 1, error })?;
+        if let Some(previous) = events.last() {
+            if recorded.at < previous.at {
+                let error = ParseEventError { pos: 0, message: "time goes backwards".to_string() };
+                return Err(LogError { line: i + 1, error });
+            }
+        }
+        events.push(recorded);
+    }
+    Ok(events)
+}
+
+// ---------- 3. RECORDING ----------
+// Writes one line per event as it happens. Wrap files in a BufWriter only if
+// losing the last events in a crash is acceptable.
+pub struct Recorder<W: Write> {
+    out: W,
+    start: Instant,
+    last: Duration,
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(out: W) -> Self {
+        Recorder { out, start: Instant::now(), last: Duration::ZERO }
+    }
+
+    // Stamps the event with the time since `new`.
+    pub fn record(&mut self, event: WebEvent) -> io::Result<()> {
+        let at = self.start.elapsed();
+        self.record_at(at, event)
+    }
+
+    // For events that carry their own timestamp; it must not be before the last one.
+    pub fn record_at(&mut self, at: Duration, event: WebEvent) -> io::Result<()> {
+        if at < self.last {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "event recorded out of order"));
+        }
+        self.last = at;
+        writeln!(self.out, "{}", Recorded { at, event }.to_json())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+// ---------- 4. REPLAY ----------
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Speed {
+    Original,
+    Scaled(Factor), // > 1 is faster
+    Fastest,
+}
+
+// A speed factor that is finite and > 0. The field is private, so the only way
+// to get one is Speed::scaled: 0, a negative factor or NaN would never finish.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Factor(f64);
+
+impl Factor {
+    pub fn get(self) -> f64 {
+        self.0
+    }
+}
+
+impl Speed {
+    // None unless `factor` is finite and > 0.
+    pub fn scaled(factor: f64) -> Option<Speed> {
+        (factor.is_finite() && factor > 0.0).then_some(Speed::Scaled(Factor(factor)))
+    }
+
+    // When the event recorded at `at` is due, counted from the start of the replay.
+    pub fn deadline(self, at: Duration) -> Duration {
+        match self {
+            Speed::Original => at,
+            // A tiny factor would overflow Duration (div_f64 panics): saturate instead.
+            Speed::Scaled(Factor(factor)) => Duration::try_from_secs_f64(at.as_secs_f64() / factor).unwrap_or(Duration::MAX),
+            Speed::Fastest => Duration::ZERO,
+        }
+    }
+}
+
+// Where replay gets the time and how it waits, so tests can run it on a fake clock.
+pub trait Clock {
+    // Time since the clock was created.
+    fn elapsed(&self) -> Duration;
+    fn sleep(&mut self, duration: Duration);
+}
+
+// Instant and thread::sleep.
+pub struct SystemClock(Instant);
+
+impl SystemClock {
+    pub fn new() -> Self {
+        SystemClock(Instant::now())
+    }
+}
+
+impl Clock for SystemClock {
+    fn elapsed(&self) -> Duration {
+        self.0.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+// Dispatches every event at its deadline; returns how many handler calls were made.
+pub fn replay(events: &[Recorded], speed: Speed, dispatcher: &mut Dispatcher) -> usize {
+    replay_with(events, speed, dispatcher, &mut SystemClock::new())
+}
+
+// `replay` on the given clock.
+pub fn replay_with(events: &[Recorded], speed: Speed, dispatcher: &mut Dispatcher, clock: &mut impl Clock) -> usize {
+    let mut calls = 0;
+    for recorded in events {
+        let wait = speed.deadline(recorded.at).saturating_sub(clock.elapsed());
+        if !wait.is_zero() {
+            clock.sleep(wait);
+        }
+        calls += dispatcher.dispatch(&recorded.event);
+    }
+    calls
+}
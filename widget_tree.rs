@@ -0,0 +1,453 @@
+/*
+Clicks through a tree of `Rectangle` widgets — hit-testing, then capture and bubble
+
+Every widget is a `Rectangle` (custom_types.rs) inside its parent's. A click goes
+to the deepest widget under the point (the target), and the handlers along the
+path from the root see it in three phases, as in the DOM:
+
+Phase	Widgets, in order	Handlers that run
+Capture	root → the target's parent	on_capture
+Target	the target	on_capture, then on_bubble
+Bubble	the target's parent → root	on_bubble
+
+$ rustc -O widget_tree.rs
+$ ./widget_tree
+Click at 44, 14 → ok
+  capture window (44, 14)
+  capture content (44, 11)
+  capture dialog (24, 6)
+  target  ok (2, 1)
+  bubble  dialog (24, 6): stop
+...
+$ ./widget_tree events.ndjson            // the Click, Mouse and Scroll events of a recording (event_replay.rs)
+$ rustc --test widget_tree.rs && ./widget_tree   // the tests
+
+📌 Coordinates are screen cells, y grows downwards (the rows of input_decoder.rs).
+   A rectangle holds its top-left edge but not its bottom-right one, so a point
+   on the border between two siblings belongs to exactly one of them.
+📌 Hit-testing starts at the root and only looks inside a widget's children:
+   a child that sticks out of its parent is clipped, like overflow: hidden.
+   Children added later are drawn on top, so they are tried first.
+📌 Handlers get an EventContext: the event, the target, the widget whose handler
+   this is, the phase, and the point relative to that widget's top-left corner.
+📌 Propagation::Stop ends the dispatch at once, as in web_event.rs's Dispatcher:
+   stopping in capture keeps the event from the target, stopping at the target
+   keeps it from bubbling. (The DOM's stopPropagation would still run the other
+   handlers of the same widget; here Stop is stopImmediatePropagation.)
+⚠️ Click, Mouse and Scroll carry a position; every other event has no target
+   and `dispatch` returns None, as it does for a point outside the root.
+*/
+
+mod web_event;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use web_event::{parse_log, Propagation, WebEvent};
+
+// ---------- 1. GEOMETRY ----------
+// The custom_types.rs shapes in f64, which holds every event coordinate up to 2^53
+// exactly (f32 would round clicks apart from 2^24 on).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rectangle {
+    top_left: Point,
+    bottom_right: Point,
+}
+
+impl Rectangle {
+    fn new(left: f64, top: f64, right: f64, bottom: f64) -> Self {
+        Rectangle { top_left: Point { x: left, y: top }, bottom_right: Point { x: right, y: bottom } }
+    }
+
+    // Half-open: the top and left edges are inside, the bottom and right ones aren't.
+    fn contains(&self, point: Point) -> bool {
+        let Rectangle { top_left, bottom_right } = self;
+        (top_left.x..bottom_right.x).contains(&point.x) && (top_left.y..bottom_right.y).contains(&point.y)
+    }
+}
+
+// Where an event happened, if it has a place at all.
+fn position(event: &WebEvent) -> Option<Point> {
+    let (x, y) = match event {
+        WebEvent::Scroll { x, y, .. } => (*x, *y),
+        event => event.mouse_event().map(|mouse| (mouse.x, mouse.y))?,
+    };
+    Some(Point { x: x as f64, y: y as f64 })
+}
+
+// ---------- 2. THE TREE ----------
+// An index into WidgetTree::widgets; the root is WidgetId(0).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WidgetId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Capture,
+    Target,
+    Bubble,
+}
+
+// What a handler is told about the event it is seeing. The demo only reads
+// `phase` and `local`; handlers in the tests use the rest.
+#[allow(dead_code)]
+struct EventContext<'a> {
+    event: &'a WebEvent,
+    target: WidgetId,
+    current: WidgetId, // the widget this handler is registered on
+    phase: Phase,
+    local: Point, // the event's position relative to `current`
+}
+
+type Handler = Box<dyn FnMut(&EventContext) -> Propagation>;
+
+struct Widget {
+    name: String,
+    bounds: Rectangle,
+    parent: Option<WidgetId>,
+    children: Vec<WidgetId>,
+    capture: Vec<Handler>,
+    bubble: Vec<Handler>,
+}
+
+struct WidgetTree {
+    widgets: Vec<Widget>,
+}
+
+// Who saw an event: the target and how many handlers ran before it was done.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Delivered {
+    target: WidgetId,
+    calls: usize,
+    stopped: bool, // a handler returned Propagation::Stop
+}
+
+impl WidgetTree {
+    const ROOT: WidgetId = WidgetId(0);
+
+    fn new(name: &str, bounds: Rectangle) -> Self {
+        let root = Widget { name: name.to_string(), bounds, parent: None, children: Vec::new(), capture: Vec::new(), bubble: Vec::new() };
+        WidgetTree { widgets: vec![root] }
+    }
+
+    // Bounds are in screen coordinates, not relative to the parent.
+    fn add(&mut self, parent: WidgetId, name: &str, bounds: Rectangle) -> WidgetId {
+        let id = WidgetId(self.widgets.len());
+        let widget = Widget { name: name.to_string(), bounds, parent: Some(parent), children: Vec::new(), capture: Vec::new(), bubble: Vec::new() };
+        self.widgets.push(widget);
+        self.widgets[parent.0].children.push(id);
+        id
+    }
+
+    fn name(&self, id: WidgetId) -> &str {
+        &self.widgets[id.0].name
+    }
+
+    #[allow(dead_code)] // the demo page only bubbles
+    fn on_capture(&mut self, id: WidgetId, handler: impl FnMut(&EventContext) -> Propagation + 'static) {
+        self.widgets[id.0].capture.push(Box::new(handler));
+    }
+
+    fn on_bubble(&mut self, id: WidgetId, handler: impl FnMut(&EventContext) -> Propagation + 'static) {
+        self.widgets[id.0].bubble.push(Box::new(handler));
+    }
+
+    // The deepest widget containing the point, or None outside the root.
+    fn hit_test(&self, point: Point) -> Option<WidgetId> {
+        if !self.widgets[Self::ROOT.0].bounds.contains(point) {
+            return None;
+        }
+        let mut hit = Self::ROOT;
+        // The topmost child containing the point, until there is none.
+        while let Some(&child) = self.widgets[hit.0].children.iter().rev().find(|child| self.widgets[child.0].bounds.contains(point)) {
+            hit = child;
+        }
+        Some(hit)
+    }
+
+    // The root, then every widget down to `id`.
+    fn path(&self, id: WidgetId) -> Vec<WidgetId> {
+        let mut path = vec![id];
+        while let Some(parent) = self.widgets[path[path.len() - 1].0].parent {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+
+    fn dispatch(&mut self, event: &WebEvent) -> Option<Delivered> {
+        let point = position(event)?;
+        let target = self.hit_test(point)?;
+        let path = self.path(target);
+        let (ancestors, _) = path.split_at(path.len() - 1);
+
+        // Every (widget, phase, is a capture handler) in the order they run.
+        let steps = ancestors
+            .iter()
+            .map(|&id| (id, Phase::Capture, true))
+            .chain([(target, Phase::Target, true), (target, Phase::Target, false)])
+            .chain(ancestors.iter().rev().map(|&id| (id, Phase::Bubble, false)));
+
+        let mut calls = 0;
+        for (current, phase, capture) in steps {
+            let widget = &mut self.widgets[current.0];
+            let local = Point { x: point.x - widget.bounds.top_left.x, y: point.y - widget.bounds.top_left.y };
+            let context = EventContext { event, target, current, phase, local };
+            let handlers = if capture { &mut widget.capture } else { &mut widget.bubble };
+            for handler in handlers {
+                calls += 1;
+                if handler(&context) == Propagation::Stop {
+                    return Some(Delivered { target, calls, stopped: true });
+                }
+            }
+        }
+        Some(Delivered { target, calls, stopped: false })
+    }
+}
+
+// ---------- 3. A PAGE ----------
+// window
+// ├── toolbar: save, open
+// └── content
+//     └── dialog: ok, cancel
+// The dialog is modal: clicks inside it don't bubble past it.
+fn page() -> WidgetTree {
+    let mut tree = WidgetTree::new("window", Rectangle::new(0.0, 0.0, 80.0, 24.0));
+    let toolbar = tree.add(WidgetTree::ROOT, "toolbar", Rectangle::new(0.0, 0.0, 80.0, 3.0));
+    tree.add(toolbar, "save", Rectangle::new(1.0, 1.0, 10.0, 2.0));
+    tree.add(toolbar, "open", Rectangle::new(11.0, 1.0, 20.0, 2.0));
+    let content = tree.add(WidgetTree::ROOT, "content", Rectangle::new(0.0, 3.0, 80.0, 24.0));
+    let dialog = tree.add(content, "dialog", Rectangle::new(20.0, 8.0, 60.0, 16.0));
+    tree.add(dialog, "ok", Rectangle::new(42.0, 13.0, 50.0, 15.0));
+    tree.add(dialog, "cancel", Rectangle::new(51.0, 13.0, 59.0, 15.0));
+    tree.on_bubble(dialog, |_| Propagation::Stop);
+    tree
+}
+
+// Adds a handler for both phases on every widget, pushing one line per widget and phase.
+fn trace(tree: &mut WidgetTree) -> Rc<RefCell<Vec<String>>> {
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    for i in 0..tree.widgets.len() {
+        let name = tree.widgets[i].name.clone();
+        for capture in [true, false] {
+            let (lines, name) = (Rc::clone(&lines), name.clone());
+            let handler = move |context: &EventContext| {
+                // The target runs both lists; one line is enough.
+                if !capture && context.phase == Phase::Target {
+                    return Propagation::Continue;
+                }
+                let phase = format!("{:?}", context.phase).to_lowercase();
+                lines.borrow_mut().push(format!("{:<7} {} ({}, {})", phase, name, context.local.x, context.local.y));
+                Propagation::Continue
+            };
+            // Ahead of the page's own handlers, so a Stop still shows the widget.
+            let handlers = if capture { &mut tree.widgets[i].capture } else { &mut tree.widgets[i].bubble };
+            handlers.insert(0, Box::new(handler));
+        }
+    }
+    lines
+}
+
+fn show(tree: &mut WidgetTree, lines: &RefCell<Vec<String>>, event: &WebEvent) {
+    lines.borrow_mut().clear();
+    let Some(point) = position(event) else {
+        return;
+    };
+    let kind = format!("{:?}", event.kind());
+    match tree.dispatch(event) {
+        None => println!("{} at {}, {} → outside the window", kind, point.x, point.y),
+        Some(delivered) => {
+            println!("{} at {}, {} → {}", kind, point.x, point.y, tree.name(delivered.target));
+            let mut lines = lines.borrow_mut();
+            if delivered.stopped {
+                if let Some(last) = lines.last_mut() {
+                    last.push_str(": stop");
+                }
+            }
+            for line in lines.iter() {
+                println!("  {}", line);
+            }
+        }
+    }
+}
+
+// ---------- 4. MAIN ----------
+fn main() {
+    let mut tree = page();
+    let lines = trace(&mut tree);
+    let events = match std::env::args().nth(1) {
+        Some(path) => {
+            let parsed = std::fs::read_to_string(&path).map_err(|err| err.to_string()).and_then(|text| parse_log(&text).map_err(|err| err.to_string()));
+            match parsed {
+                Ok(recorded) => recorded.into_iter().map(|recorded| recorded.event).collect(),
+                Err(err) => {
+                    eprintln!("error: {}: {}", path, err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => vec![WebEvent::Click { x: 44, y: 14 }, WebEvent::Click { x: 5, y: 1 }, WebEvent::Click { x: 30, y: 20 }, WebEvent::Click { x: 90, y: 5 }],
+    };
+    for event in &events {
+        show(&mut tree, &lines, event);
+    }
+}
+
+// ---------- CHECKS ----------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use web_event::{Button, KeyEvent, Modifiers, MouseAction, MouseEvent};
+
+    fn at(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    #[test]
+    fn hit_test_check() {
+        let tree = page();
+        let hit = |x, y| tree.hit_test(at(x, y)).map(|id| tree.name(id).to_string());
+        assert_eq!(hit(44.0, 14.0).as_deref(), Some("ok"));
+        assert_eq!(hit(30.0, 10.0).as_deref(), Some("dialog"));
+        assert_eq!(hit(5.0, 20.0).as_deref(), Some("content"));
+        assert_eq!(hit(5.0, 1.0).as_deref(), Some("save"));
+        assert_eq!(hit(10.5, 1.5).as_deref(), Some("toolbar")); // between the buttons
+        assert_eq!(hit(80.0, 5.0), None);
+        assert_eq!(hit(-0.5, 5.0), None);
+
+        // Edges: top-left inside, bottom-right outside.
+        assert_eq!(hit(42.0, 13.0).as_deref(), Some("ok"));
+        assert_eq!(hit(50.0, 14.0).as_deref(), Some("dialog"));
+        assert_eq!(hit(0.0, 3.0).as_deref(), Some("content"));
+        assert_eq!(hit(0.0, 2.9).as_deref(), Some("toolbar"));
+
+        // Overlapping siblings: the one added last is on top. Children are clipped to their parent.
+        let mut tree = WidgetTree::new("root", Rectangle::new(0.0, 0.0, 10.0, 10.0));
+        let below = tree.add(WidgetTree::ROOT, "below", Rectangle::new(0.0, 0.0, 6.0, 6.0));
+        let above = tree.add(WidgetTree::ROOT, "above", Rectangle::new(4.0, 4.0, 8.0, 8.0));
+        let overhang = tree.add(below, "overhang", Rectangle::new(5.0, 0.0, 9.0, 2.0));
+        assert_eq!(tree.hit_test(at(5.0, 5.0)), Some(above));
+        assert_eq!(tree.hit_test(at(3.0, 3.0)), Some(below));
+        assert_eq!(tree.hit_test(at(5.5, 1.0)), Some(overhang));
+        assert_eq!(tree.hit_test(at(7.0, 1.0)), Some(WidgetTree::ROOT));
+        assert_eq!(tree.path(overhang), [WidgetTree::ROOT, below, overhang]);
+
+        // One cell wide, past the point where f32 stops telling neighbouring integers apart.
+        let far = 16_777_217;
+        let mut tree = WidgetTree::new("root", Rectangle::new(0.0, 0.0, 2.0 * far as f64, 1.0));
+        let cell = tree.add(WidgetTree::ROOT, "cell", Rectangle::new(far as f64, 0.0, (far + 1) as f64, 1.0));
+        let delivered = tree.dispatch(&WebEvent::Click { x: far, y: 0 });
+        assert_eq!(delivered.map(|delivered| delivered.target), Some(cell));
+        assert_eq!(tree.hit_test(at((far - 1) as f64, 0.0)), Some(WidgetTree::ROOT));
+    }
+
+    #[test]
+    fn propagation_check() {
+        let mut tree = page();
+        let lines = trace(&mut tree);
+        let click = |tree: &mut WidgetTree, x, y| {
+            lines.borrow_mut().clear();
+            let delivered = tree.dispatch(&WebEvent::Click { x, y });
+            (delivered, lines.borrow().clone())
+        };
+
+        // Capture from the root down, the target, and bubbling back up until the dialog stops it.
+        let (delivered, seen) = click(&mut tree, 44, 14);
+        assert_eq!(
+            seen,
+            [
+                "capture window (44, 14)",
+                "capture content (44, 11)",
+                "capture dialog (24, 6)",
+                "target  ok (2, 1)",
+                "bubble  dialog (24, 6)",
+            ]
+        );
+        assert_eq!(delivered, Some(Delivered { target: WidgetId(6), calls: 7, stopped: true }));
+
+        // Outside the dialog nothing stops it.
+        let (delivered, seen) = click(&mut tree, 5, 1);
+        assert_eq!(seen, ["capture window (5, 1)", "capture toolbar (5, 1)", "target  save (4, 0)", "bubble  toolbar (5, 1)", "bubble  window (5, 1)"]);
+        assert_eq!(delivered.map(|delivered| (delivered.calls, delivered.stopped)), Some((6, false)));
+
+        // A target with no children, then the root itself: both lists of handlers, no capture or bubble.
+        let (delivered, seen) = click(&mut tree, 0, 23);
+        assert_eq!(delivered.map(|delivered| delivered.target), Some(WidgetId(4)));
+        assert_eq!(seen, ["capture window (0, 23)", "target  content (0, 20)", "bubble  window (0, 23)"]);
+        let mut bare = WidgetTree::new("root", Rectangle::new(0.0, 0.0, 4.0, 4.0));
+        let phases = Rc::new(RefCell::new(Vec::new()));
+        for capture in [true, false] {
+            let phases = Rc::clone(&phases);
+            let handler = move |context: &EventContext| {
+                phases.borrow_mut().push((context.phase, capture));
+                Propagation::Continue
+            };
+            if capture {
+                bare.on_capture(WidgetTree::ROOT, handler);
+            } else {
+                bare.on_bubble(WidgetTree::ROOT, handler);
+            }
+        }
+        assert_eq!(bare.dispatch(&WebEvent::Click { x: 1, y: 1 }).map(|delivered| delivered.calls), Some(2));
+        assert_eq!(*phases.borrow(), [(Phase::Target, true), (Phase::Target, false)]);
+
+        // A capture handler that stops keeps the event from everything below it.
+        let mut tree = page();
+        let toolbar = WidgetId(1);
+        tree.on_capture(toolbar, |context| if context.local.x < 10.0 { Propagation::Stop } else { Propagation::Continue });
+        let lines = trace(&mut tree);
+        assert_eq!(tree.dispatch(&WebEvent::Click { x: 5, y: 1 }).map(|delivered| delivered.stopped), Some(true));
+        assert_eq!(*lines.borrow(), ["capture window (5, 1)", "capture toolbar (5, 1)"]);
+        lines.borrow_mut().clear();
+        assert_eq!(tree.dispatch(&WebEvent::Click { x: 15, y: 1 }).map(|delivered| delivered.stopped), Some(false));
+        assert_eq!(lines.borrow().len(), 5);
+
+        // Every handler learns the target; the first Stop wins, even on the same widget.
+        let mut tree = page();
+        let targets = Rc::new(RefCell::new(Vec::new()));
+        for id in [WidgetTree::ROOT, WidgetId(3)] {
+            for stop in [true, false] {
+                let targets = Rc::clone(&targets);
+                tree.on_capture(id, move |context| {
+                    targets.borrow_mut().push((context.current, context.target));
+                    if stop && context.phase == Phase::Target { Propagation::Stop } else { Propagation::Continue }
+                });
+            }
+        }
+        let delivered = tree.dispatch(&WebEvent::Click { x: 12, y: 1 });
+        assert_eq!(delivered, Some(Delivered { target: WidgetId(3), calls: 3, stopped: true }));
+        assert_eq!(*targets.borrow(), [(WidgetTree::ROOT, WidgetId(3)), (WidgetTree::ROOT, WidgetId(3)), (WidgetId(3), WidgetId(3))]);
+    }
+
+    #[test]
+    fn events_check() {
+        let mut tree = page();
+        let kinds = Rc::new(RefCell::new(Vec::new()));
+        let seen = Rc::clone(&kinds);
+        tree.on_bubble(WidgetTree::ROOT, move |context| {
+            seen.borrow_mut().push(format!("{:?}", context.event.kind()));
+            Propagation::Continue
+        });
+
+        // Everything with a position is routed: Click, the other mouse events, Scroll.
+        let drag = MouseEvent { action: MouseAction::Drag(Button::Left), x: 5, y: 20, modifiers: Modifiers::NONE };
+        let ctrl_click = MouseEvent { action: MouseAction::Press(Button::Left), x: 5, y: 1, modifiers: Modifiers::CTRL };
+        let scroll = WebEvent::Scroll { x: 5, y: 20, dx: 0, dy: 1, modifiers: Modifiers::NONE };
+        assert_eq!(tree.dispatch(&drag.into()).map(|delivered| delivered.target), Some(WidgetId(4)));
+        assert_eq!(tree.dispatch(&ctrl_click.into()).map(|delivered| delivered.target), Some(WidgetId(2)));
+        assert_eq!(tree.dispatch(&scroll).map(|delivered| delivered.target), Some(WidgetId(4)));
+        assert_eq!(*kinds.borrow(), ["Mouse", "Mouse", "Scroll"]);
+
+        // Nothing else has a target.
+        assert_eq!(tree.dispatch(&WebEvent::PageLoad), None);
+        assert_eq!(tree.dispatch(&KeyEvent::from('a').into()), None);
+        assert_eq!(tree.dispatch(&WebEvent::Resize { width: 80, height: 24 }), None);
+        assert_eq!(tree.dispatch(&WebEvent::Click { x: -1, y: 0 }), None);
+        assert_eq!(kinds.borrow().len(), 3);
+    }
+}